@@ -1,8 +1,26 @@
+use crate::calendar::Upcoming;
+use crate::dbus;
+use crate::digest::{self, Digest};
+use crate::highlight::Highlight;
+use crate::hooks::{self, HookAction, Hooks};
+use crate::hud_config::{self, Guides, HudConfig, Overflow, TextConfig};
+use crate::instance;
 use crate::ipc;
 use crate::loader::*;
+use crate::power::Alert;
+use crate::push::Pusher;
+use crate::rules::{self, Rules};
 use crate::shell;
+use crate::snapshot;
 use crate::surface::*;
+use crate::systemd;
 use crate::theme::{self, ThemeColors, ThemeMode};
+use crate::tour::{self, Tour};
+use crate::update;
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use iced::{Color, Element, Font, Subscription, Task};
 use iced_layershell::build_pattern::daemon;
@@ -14,9 +32,26 @@ pub(crate) const EDGE_MARGIN: u16 = 40;
 
 const TICK_MS: u64 = 80;
 
+/// How long a rule's `attention` text stays in the warning band.
+const ATTENTION_DURATION: Duration = Duration::from_secs(10);
+
+/// A theme probe still running after this is presumed stuck, and the next
+/// refresh starts another.
+const THEME_PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Redraw interval with `overflow: scroll`, so pages turn on time.
+const PAGE_TICK_MS: u64 = 1000;
+
+/// Frame interval while a theme crossfade is running.
+const THEME_FRAME_MS: u64 = 16;
+
+/// `Hud::new` taking longer than this delays the first frame noticeably;
+/// anything that spawns processes or waits on disk belongs in a task.
+const STARTUP_BUDGET: Duration = Duration::from_millis(50);
+
 // --- HUD State ---
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub(crate) enum HudMode {
     Hidden,
     Visible,
@@ -30,9 +65,60 @@ pub(crate) struct Hud {
     pub(crate) demo_loader: Option<DemoLoader>,
     pub(crate) theme_mode: ThemeMode,
     pub(crate) colors: ThemeColors,
+    pub(crate) theme_transition: Option<theme::ThemeTransition>,
+    /// When the running theme detection or screen sample started.
+    pub(crate) theme_probe: Option<Instant>,
     pub(crate) backdrop: bool,
     pub(crate) target_output: Option<String>,
+    /// Move to the keyboard-focused output (`screen follow`).
+    pub(crate) follow_focus: bool,
+    /// Minimal density: shell widgets collapse to status dots (`minimal`).
+    pub(crate) minimal: bool,
+    /// Logical width of the HUD surface, once the compositor has sized it.
+    pub(crate) surface_width: Option<f32>,
+    /// Ctrl state and unapplied scroll, for Ctrl+scroll text zoom.
+    pub(crate) ctrl_held: bool,
+    pub(crate) zoom_scroll: f32,
+    /// Interval for systemd watchdog pings, when the unit sets `WatchdogSec`.
+    pub(crate) watchdog: Option<Duration>,
     pub(crate) shells: Option<shell::ShellState>,
+    /// First-run demo mode: no `shells.md` yet, so demo widgets and the demo
+    /// loader run, flagged in the info line.
+    pub(crate) demo: bool,
+    pub(crate) hud_config: HudConfig,
+    pub(crate) guides: Guides,
+    /// Region outlined via `dev-hud-ctl highlight`, with its expiry.
+    pub(crate) highlight: Option<(Highlight, Instant)>,
+    /// Newer release tag found by the update check, shown in the info line.
+    pub(crate) update_available: Option<String>,
+    /// Onboarding tour, on first start or `dev-hud-ctl tour`.
+    pub(crate) tour: Option<Tour>,
+    /// Next meeting from `hud.md`'s `# calendar` files.
+    pub(crate) calendar: Option<Upcoming>,
+    /// Battery/thermal thresholds currently crossed (`# alerts`).
+    pub(crate) alerts: Vec<Alert>,
+    /// User rules from `rules.md`.
+    pub(crate) rules: Rules,
+    /// Texts flagged by a rule's `attention` action, with their expiry.
+    pub(crate) attention: Vec<(String, Instant)>,
+    /// A new attention text arrived since the last tick (`# attention`
+    /// `focus: on` acts on it there).
+    attention_raised: bool,
+    /// Set while the HUD is focused because of attention: when to drop
+    /// back to visible at the latest.
+    auto_focus: Option<Instant>,
+    /// `peek`: the mode it switched to and when to switch back.
+    peek: Option<(HudMode, Instant)>,
+    /// Widget labels hidden by a rule's or hook's `hide` action.
+    pub(crate) hidden_widgets: HashSet<String>,
+    /// Compiled `hooks.rhai`, if present.
+    pub(crate) hooks: Option<Hooks>,
+    /// Phone pushes for attention texts and alerts (`# push`).
+    pub(crate) pusher: Pusher,
+    /// Events collected for the daily email (`# digest`).
+    pub(crate) digest: Digest,
+    /// Template variables last handed to the shell thread.
+    published_vars: BTreeMap<String, String>,
 }
 
 impl Hud {
@@ -44,6 +130,301 @@ impl Hud {
         FONT_OPTIONS[self.font_index].0
     }
 
+    /// Values for `{name}` placeholders in shell widget commands.
+    fn template_vars(&self) -> BTreeMap<String, String> {
+        let hud_mode = match self.mode {
+            HudMode::Hidden => "hidden",
+            HudMode::Visible => "visible",
+            HudMode::Focused => "focused",
+        };
+        BTreeMap::from([
+            (
+                "target_output".to_string(),
+                self.target_output.clone().unwrap_or_default(),
+            ),
+            (
+                "theme".to_string(),
+                if self.colors.is_dark { "dark" } else { "light" }.to_string(),
+            ),
+            ("hud_mode".to_string(), hud_mode.to_string()),
+        ])
+    }
+
+    /// Run `rules.md` against widget output, exits and spawn errors.
+    fn check_shell_rules(&mut self, event: &shell::ShellEvent) {
+        let (trigger, label, texts) = match event {
+            shell::ShellEvent::Output { label, lines } => (
+                rules::Trigger::Line,
+                label,
+                lines.iter().map(|l| l.text.clone()).collect(),
+            ),
+            shell::ShellEvent::Exited {
+                label,
+                exit_code,
+                signal,
+            } => (
+                rules::Trigger::Exit,
+                label,
+                vec![rules::exit_text(*exit_code, signal.as_deref())],
+            ),
+            shell::ShellEvent::Error { label, error } => {
+                (rules::Trigger::Error, label, vec![error.clone()])
+            }
+            _ => return,
+        };
+        for text in &texts {
+            self.apply_rules(rules::Event {
+                trigger,
+                widget: Some(label),
+                text,
+            });
+        }
+    }
+
+    /// Carry out the actions of every rule that fires for `event`.
+    fn apply_rules(&mut self, event: rules::Event) {
+        for rule in self.rules.fire(&event, Instant::now()) {
+            eprintln!("[dev-hud] rule '{}': {}", rule.name, event.text);
+            for action in &rule.actions {
+                match action {
+                    rules::Action::Notify => rules::notify(&rule.name, event.text),
+                    rules::Action::Run => {
+                        if let Some(command) = &rule.run {
+                            let env = [
+                                ("DEV_HUD_RULE", rule.name.as_str()),
+                                ("DEV_HUD_WIDGET", event.widget.unwrap_or_default()),
+                                ("DEV_HUD_TEXT", event.text),
+                            ];
+                            rules::spawn(command, &env);
+                        }
+                    }
+                    rules::Action::Attention => {
+                        self.flag_attention(format!("{}: {}", rule.name, event.text));
+                    }
+                    rules::Action::Hide => {
+                        if let Some(label) = rule.target(&event) {
+                            self.hidden_widgets.insert(label.to_string());
+                        }
+                    }
+                    rules::Action::Show => {
+                        if let Some(label) = rule.target(&event) {
+                            self.hidden_widgets.remove(label);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Run `hooks.rhai` on widget output (rewriting or dropping lines) and
+    /// exits, then carry out the actions the hooks asked for.
+    fn run_shell_hooks(&mut self, event: &mut shell::ShellEvent) {
+        let Some(hooks) = &mut self.hooks else {
+            return;
+        };
+        match event {
+            shell::ShellEvent::Output { label, lines } => {
+                lines.retain_mut(|line| {
+                    match hooks.on_shell_line(label, std::mem::take(&mut line.text)) {
+                        Some(text) => {
+                            line.text = text;
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            }
+            shell::ShellEvent::Exited {
+                label,
+                exit_code,
+                signal,
+            } => hooks.on_shell_exit(label, &rules::exit_text(*exit_code, signal.as_deref())),
+            _ => {}
+        }
+        let actions = hooks.take_actions();
+        self.apply_hook_actions(actions);
+    }
+
+    fn apply_hook_actions(&mut self, actions: Vec<HookAction>) {
+        for action in actions {
+            match action {
+                HookAction::Notify { title, body } => rules::notify(&title, &body),
+                HookAction::Run(command) => rules::spawn(&command, &[]),
+                HookAction::Attention(text) => self.flag_attention(text),
+                HookAction::Hide(label) => {
+                    self.hidden_widgets.insert(label);
+                }
+                HookAction::Show(label) => {
+                    self.hidden_widgets.remove(&label);
+                }
+            }
+        }
+    }
+
+    /// Show `text` in the warning band for `ATTENTION_DURATION`.
+    fn flag_attention(&mut self, text: String) {
+        let now = Instant::now();
+        let len = self.attention.len();
+        self.attention.retain(|(t, _)| *t != text);
+        // Only newly raised texts are pushed; a repeat just extends the flag
+        if self.attention.len() == len {
+            self.push(&text, now);
+            // A hidden HUD stays hidden
+            self.attention_raised |= self.mode == HudMode::Visible;
+        }
+        self.attention.push((text, now + ATTENTION_DURATION));
+    }
+
+    /// Send `text` to the phone and record it for the digest.
+    fn push(&mut self, text: &str, now: Instant) {
+        let title = instance::suffixed("dev-hud");
+        self.pusher.push(&self.hud_config.push, &title, text, now);
+        if self.hud_config.digest.enabled() {
+            self.digest.record(text);
+        }
+    }
+
+    /// Mail what the digest collected, on a background thread.
+    fn send_digest(&mut self) {
+        let Some(body) = self.digest.take_body() else {
+            eprintln!("[dev-hud] digest: nothing to report");
+            return;
+        };
+        let config = self.hud_config.digest.clone();
+        let subject = format!("{} digest", instance::suffixed("dev-hud"));
+        std::thread::spawn(move || match digest::send(&config, &subject, body) {
+            Ok(()) => eprintln!("[dev-hud] digest: sent"),
+            Err(e) => eprintln!("[dev-hud] digest: {e}"),
+        });
+    }
+
+    /// Switch to `target` colors, crossfading over `theme::TRANSITION_DURATION`
+    /// while the HUD is on screen. `colors.is_dark` reflects the target at once.
+    fn transition_to(&mut self, target: ThemeColors) {
+        let target = target.with_text_scale(self.hud_config.text.scale);
+        let already_there = match &self.theme_transition {
+            Some(tr) => *tr.target() == target,
+            None => self.colors == target,
+        };
+        if already_there {
+            return;
+        }
+        if self.mode == HudMode::Hidden {
+            self.theme_transition = None;
+            self.colors = target;
+            return;
+        }
+        // Start from whatever is on screen, even mid-transition
+        let tr = theme::ThemeTransition::new(self.colors.clone(), target);
+        self.colors = tr.sample(Instant::now()).0;
+        self.theme_transition = Some(tr);
+    }
+
+    /// Apply a new text scale to the palette on screen and the one being
+    /// transitioned to.
+    fn apply_text_scale(&mut self, scale: f32) {
+        self.colors = self.colors.clone().with_text_scale(scale);
+        if let Some(tr) = &self.theme_transition {
+            let target = tr.target().clone().with_text_scale(scale);
+            self.theme_transition = Some(theme::ThemeTransition::new(self.colors.clone(), target));
+        }
+    }
+
+    /// Change the text scale by `steps` tenths (0 resets it) and save it to
+    /// `hud.md`.
+    fn step_text_scale(&mut self, steps: i32) {
+        let current = self.hud_config.text.scale;
+        let scale = if steps == 0 {
+            1.0
+        } else {
+            ((current * 10.0).round() + steps as f32) / 10.0
+        }
+        .clamp(TextConfig::MIN_SCALE, TextConfig::MAX_SCALE);
+        if scale == current {
+            return;
+        }
+        self.hud_config.text.scale = scale;
+        self.apply_text_scale(scale);
+        eprintln!("[dev-hud] text scale -> {scale}");
+        if let Err(e) = hud_config::save_entry("text", "scale", &scale.to_string()) {
+            eprintln!("[dev-hud] text scale: cannot save: {e}");
+        }
+    }
+
+    /// Close the tour and remember that it has been seen.
+    fn end_tour(&mut self) {
+        self.tour = None;
+        tour::mark_seen();
+        eprintln!("[dev-hud] tour: done");
+    }
+
+    /// Show the demo loader. Its GIF and SVG frames are prepared on a
+    /// background thread the first time; it spins as text until they arrive.
+    fn start_loader(&mut self) -> Task<Message> {
+        let loader = DemoLoader::new();
+        let ready = loader.assets.is_some();
+        self.demo_loader = Some(loader);
+        if ready {
+            return Task::none();
+        }
+        spawn_blocking(loader_assets, Message::LoaderAssetsReady)
+    }
+
+    /// With `# attention` `focus: on`, go from visible to focused when a new
+    /// attention text is raised, and back once the band is empty or the
+    /// timeout passes. Leaving focused mode by hand cancels it.
+    fn update_auto_focus(&mut self, now: Instant) -> Task<Message> {
+        let raised = std::mem::take(&mut self.attention_raised);
+        match self.auto_focus {
+            Some(_) if self.mode != HudMode::Focused => {
+                self.auto_focus = None;
+                Task::none()
+            }
+            Some(until) if self.attention.is_empty() || now >= until => {
+                self.auto_focus = None;
+                eprintln!("[dev-hud] attention: back to visible");
+                self.handle_message(Message::ToggleFocus)
+            }
+            None if raised && self.hud_config.attention.focus && self.mode == HudMode::Visible => {
+                eprintln!("[dev-hud] attention: focusing the HUD");
+                let task = self.handle_message(Message::ToggleFocus);
+                let timeout = Duration::from_secs(self.hud_config.attention.timeout_secs);
+                self.auto_focus = Some(now + timeout);
+                task
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// Switch back once a `peek` runs out. Changing the mode by hand during
+    /// the peek keeps the new mode.
+    fn update_peek(&mut self, now: Instant) -> Task<Message> {
+        match self.peek {
+            Some((mode, _)) if mode != self.mode => {
+                self.peek = None;
+                Task::none()
+            }
+            Some((mode, until)) if now >= until => {
+                self.peek = None;
+                eprintln!("[dev-hud] peek: over");
+                match mode {
+                    HudMode::Visible => self.handle_message(Message::ToggleVisibility),
+                    HudMode::Focused => self.handle_message(Message::ToggleFocus),
+                    HudMode::Hidden => Task::none(),
+                }
+            }
+            _ => Task::none(),
+        }
+    }
+
+    /// A manual screen choice ends `screen follow`.
+    fn stop_following(&mut self) {
+        if self.follow_focus {
+            self.follow_focus = false;
+            eprintln!("[dev-hud] screen follow: off (screen chosen manually)");
+        }
+    }
+
     /// Recreate the main surface on the current target output.
     fn recreate_surface(&mut self) -> Task<Message> {
         let remove_task = if let Some(id) = self.surface_id.take() {
@@ -67,18 +448,91 @@ impl Hud {
 pub(crate) enum Message {
     ToggleVisibility,
     ToggleFocus,
+    /// Show a hidden HUD (or focus a visible one) for a while, then switch
+    /// back (`dev-hud-ctl peek [secs]`).
+    Peek(Duration),
     DemoLoaderToggle,
     DemoLoaderChange,
+    /// Whether shells.md exists, checked after the first frame: enables the
+    /// shell widgets, or demo mode on first runs without one.
+    ShellsDetected(bool),
+    /// GIF and SVG frames for the demo loader, prepared off the UI thread.
+    LoaderAssetsReady(LoaderAssets),
     FontChange,
+    /// Leave demo mode and keep it off on later starts.
+    DemoDismiss,
     Tick,
     ThemeSet(ThemeMode),
     ThemeToggle,
     ThemeRefresh,
+    /// The desktop's dark preference (`auto` theme).
+    SystemThemeDetected(bool),
+    /// Luminance behind the HUD (`adaptive` theme), if it could be sampled.
+    BackgroundSampled(Option<f32>),
+    ThemeFrame,
     BackdropToggle,
     ScreenCycle,
+    /// Outputs found for `ScreenCycle`, listed off the UI thread.
+    OutputsListed(Vec<String>),
     ScreenSet(String),
+    ScreenFollowToggle,
+    FocusedOutput(String),
+    SurfaceResized(iced::Size),
+    /// Step the text scale by this many tenths; 0 resets it.
+    TextScale(i32),
+    /// Ctrl+scroll in focused mode, in lines (positive is up).
+    ZoomScroll(f32),
+    ModifiersChanged(iced::keyboard::Modifiers),
+    /// Handled by `update`, so pings stop if the event loop hangs.
+    WatchdogPing,
     ShellEvent(shell::ShellEvent),
     ShellToggle,
+    MinimalToggle,
+    HudConfigReloaded(Box<HudConfig>),
+    GuidesCycle,
+    GuidesSet(Guides),
+    Highlight(Highlight),
+    HighlightClear,
+    /// Save the HUD surface to a PNG (`dev-hud-ctl snapshot <path>`).
+    Snapshot(PathBuf),
+    SnapshotTaken(PathBuf, iced::window::Screenshot),
+    /// Ask GitHub for the latest release (`# updates` or `dev-hud-ctl update check`).
+    UpdateCheck,
+    /// The latest release tag, or why it couldn't be fetched.
+    UpdateChecked(Result<String, String>),
+    /// Start the onboarding tour over (`dev-hud-ctl tour`).
+    TourStart,
+    /// Whether the tour was finished before, checked at startup.
+    TourSeen(bool),
+    TourNext,
+    TourEnd,
+    CalendarUpdated(Option<Upcoming>),
+    AlertsUpdated(Vec<Alert>),
+    RulesReloaded(Vec<rules::Rule>),
+    /// `hooks.rhai`'s source, read off the UI thread; compiled on it
+    /// (scripts aren't `Send`).
+    HooksChanged(Option<String>),
+    /// Time to mail the daily digest (or `dev-hud-ctl digest`).
+    DigestDue,
+    /// A block with an action was clicked in a plugin widget.
+    PluginClick {
+        label: String,
+        action: String,
+    },
+}
+
+/// Run blocking `work` (process spawns, decoding) on its own thread and
+/// deliver the result as a message, so it never stalls a frame.
+fn spawn_blocking<T: Send + 'static>(
+    work: impl FnOnce() -> T + Send + 'static,
+    done: impl Fn(T) -> Message + Send + 'static,
+) -> Task<Message> {
+    let (tx, rx) = futures::channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(work());
+    });
+    // A panicked worker has already printed its message
+    Task::perform(rx, Result::ok).and_then(move |value| Task::done(done(value)))
 }
 
 pub(crate) fn run() -> Result<(), iced_layershell::Error> {
@@ -104,48 +558,120 @@ pub(crate) fn run() -> Result<(), iced_layershell::Error> {
 
 impl Hud {
     fn new() -> (Self, Task<Message>) {
+        let started = Instant::now();
         let theme_mode = ThemeMode::Dark;
-        let colors = theme::resolve(theme_mode);
 
-        // Default output: DEV_HUD_SCREEN env var, falling back to any active monitor
-        let target_output = std::env::var("DEV_HUD_SCREEN")
+        // Default output: DEV_HUD_SCREEN env var, falling back to any active monitor.
+        // "follow" starts on any monitor and then tracks keyboard focus.
+        let screen_env = std::env::var("DEV_HUD_SCREEN")
             .ok()
             .filter(|s| !s.is_empty());
+        let follow_focus = screen_env.as_deref() == Some("follow");
+        let target_output = screen_env.filter(|_| !follow_focus);
         if let Some(ref name) = target_output {
             eprintln!("[dev-hud] target screen: {name} (from DEV_HUD_SCREEN)");
         }
+        if follow_focus {
+            eprintln!("[dev-hud] screen follow: on (from DEV_HUD_SCREEN)");
+        }
 
-        // Auto-enable shell widgets if config file exists
-        let shells = if shell::config_file_path().exists() {
-            eprintln!("[dev-hud] shells: auto-enabled (config file found)");
-            Some(shell::ShellState::default())
-        } else {
-            None
-        };
+        // Text scale and guides are part of the first frame
+        let hud_config = hud_config::load();
+
+        // Type=notify units wait for this; the daemon loop is about to start
+        systemd::notify("READY=1");
+        let watchdog = systemd::watchdog_interval();
+        if let Some(interval) = watchdog {
+            eprintln!("[dev-hud] systemd watchdog: pinging every {interval:?}");
+        }
+        let colors = theme::resolve(theme_mode).with_text_scale(hud_config.text.scale);
+        let guides = hud_config.guides.mode;
 
         let (id, task) = Message::layershell_open(visible_settings(target_output.as_deref()));
         eprintln!("[dev-hud] booting -> Visible (surface {id})");
+        let mut hud = Self {
+            mode: HudMode::Visible,
+            surface_id: Some(id),
+            font_index: 0,
+            demo_loader: None,
+            theme_mode,
+            colors,
+            theme_transition: None,
+            theme_probe: None,
+            backdrop: false,
+            target_output,
+            follow_focus,
+            minimal: false,
+            surface_width: None,
+            ctrl_held: false,
+            zoom_scroll: 0.0,
+            watchdog,
+            shells: None,
+            demo: false,
+            hud_config,
+            guides,
+            highlight: None,
+            update_available: None,
+            tour: None,
+            calendar: None,
+            alerts: Vec::new(),
+            rules: Rules::new(Vec::new()),
+            attention: Vec::new(),
+            attention_raised: false,
+            auto_focus: None,
+            peek: None,
+            hidden_widgets: HashSet::new(),
+            hooks: None,
+            pusher: Pusher::default(),
+            digest: Digest::default(),
+            published_vars: BTreeMap::new(),
+        };
+        hud.published_vars = hud.template_vars();
+        shell::set_template_vars(hud.published_vars.clone());
+        // Shell widgets (or the demo), rules, hooks and the tour come up
+        // right after the first frame
+        let detect_task = spawn_blocking(
+            || shell::config_file_path().exists(),
+            Message::ShellsDetected,
+        );
+        let load_tasks = [
+            spawn_blocking(rules::load, Message::RulesReloaded),
+            spawn_blocking(hooks::read_source, Message::HooksChanged),
+            spawn_blocking(tour::seen, Message::TourSeen),
+        ];
+
+        let elapsed = started.elapsed();
+        if elapsed > STARTUP_BUDGET {
+            eprintln!(
+                "[dev-hud] startup: {elapsed:?} before the first frame (budget {STARTUP_BUDGET:?})"
+            );
+        }
         (
-            Self {
-                mode: HudMode::Visible,
-                surface_id: Some(id),
-                font_index: 0,
-                demo_loader: None,
-                theme_mode,
-                colors,
-                backdrop: false,
-                target_output,
-                shells,
-            },
-            task,
+            hud,
+            Task::batch([task, detect_task].into_iter().chain(load_tasks)),
         )
     }
 
     fn namespace() -> String {
-        String::from("dev-hud")
+        instance::suffixed("dev-hud")
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        let mode = self.mode;
+        let task = self.handle_message(message);
+        // Screen, theme and mode changes can re-expand widget commands
+        let vars = self.template_vars();
+        if vars != self.published_vars {
+            if self.mode != mode {
+                dbus::mode_changed(&vars["hud_mode"]);
+            }
+            shell::set_template_vars(vars.clone());
+            self.published_vars = vars;
+        }
+        task
+    }
+
+    fn handle_message(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::ToggleVisibility => match self.mode {
                 HudMode::Hidden => {
@@ -163,6 +689,7 @@ impl Hud {
                         Task::none()
                     };
                     self.mode = HudMode::Hidden;
+                    self.highlight = None;
                     eprintln!("[dev-hud] {mode:?} -> Hidden");
                     task
                 }
@@ -199,6 +726,8 @@ impl Hud {
                         Message::layershell_open(visible_settings(self.target_output.as_deref()));
                     self.surface_id = Some(id);
                     self.mode = HudMode::Visible;
+                    // Modifier releases after this aren't delivered
+                    self.ctrl_held = false;
                     eprintln!("[dev-hud] Focused -> Visible");
                     Task::batch([remove_task, open_task])
                 }
@@ -207,19 +736,58 @@ impl Hud {
                 if self.demo_loader.is_some() {
                     self.demo_loader = None;
                     eprintln!("[dev-hud] demo loader: off");
+                    Task::none()
                 } else {
-                    self.demo_loader = Some(DemoLoader::new());
                     eprintln!("[dev-hud] demo loader: on (braille)");
+                    self.start_loader()
                 }
-                Task::none()
             }
             Message::DemoLoaderChange => {
                 if let Some(loader) = &mut self.demo_loader {
                     loader.cycle_style();
                     eprintln!("[dev-hud] demo loader: style -> {}", loader.style.label());
+                    Task::none()
                 } else {
-                    self.demo_loader = Some(DemoLoader::new());
                     eprintln!("[dev-hud] demo loader: on (braille)");
+                    self.start_loader()
+                }
+            }
+            Message::ShellsDetected(found) => {
+                if self.shells.is_some() {
+                    // Enabled over IPC in the meantime
+                    return Task::none();
+                }
+                if found {
+                    eprintln!("[dev-hud] shells: auto-enabled (config file found)");
+                    self.shells = Some(shell::ShellState::default());
+                } else if self.hud_config.demo.auto {
+                    eprintln!(
+                        "[dev-hud] demo mode: on (no shells.md; `dev-hud-ctl demo dismiss` ends it)"
+                    );
+                    self.demo = true;
+                    self.shells = Some(shell::ShellState::default());
+                    return self.start_loader();
+                }
+                Task::none()
+            }
+            Message::LoaderAssetsReady(assets) => {
+                if let Some(loader) = &mut self.demo_loader {
+                    loader.assets = Some(assets);
+                }
+                Task::none()
+            }
+            Message::DemoDismiss => {
+                if self.demo {
+                    self.demo = false;
+                    self.demo_loader = None;
+                    self.shells = None;
+                    eprintln!("[dev-hud] demo mode: dismissed");
+                }
+                if self.hud_config.demo.auto {
+                    self.hud_config.demo.auto = false;
+                    if let Err(e) = hud_config::save_entry("demo", "auto", "off") {
+                        eprintln!("[dev-hud] demo mode: cannot save: {e}");
+                    }
                 }
                 Task::none()
             }
@@ -232,11 +800,46 @@ impl Hud {
                 if let Some(loader) = &mut self.demo_loader {
                     loader.tick();
                 }
-                Task::none()
+                if self
+                    .highlight
+                    .is_some_and(|(_, until)| Instant::now() >= until)
+                {
+                    self.highlight = None;
+                }
+                let now = Instant::now();
+                self.attention.retain(|(_, until)| now < *until);
+                if let Some(tour) = &mut self.tour
+                    && !tour.tick(now)
+                {
+                    self.end_tour();
+                }
+                let peek_task = self.update_peek(now);
+                Task::batch([peek_task, self.update_auto_focus(now)])
+            }
+            Message::Peek(duration) => {
+                let until = Instant::now() + duration;
+                // Peeking again while a peek is up only extends it
+                if let Some((mode, _)) = self.peek
+                    && mode == self.mode
+                {
+                    self.peek = Some((mode, until));
+                    return Task::none();
+                }
+                let task = match self.mode {
+                    HudMode::Hidden => self.handle_message(Message::ToggleVisibility),
+                    HudMode::Visible => self.handle_message(Message::ToggleFocus),
+                    HudMode::Focused => {
+                        eprintln!("[dev-hud] peek: already focused");
+                        return Task::none();
+                    }
+                };
+                eprintln!("[dev-hud] peek: {:?} for {duration:?}", self.mode);
+                self.peek = Some((self.mode, until));
+                task
             }
             Message::ThemeSet(mode) => {
                 self.theme_mode = mode;
-                self.colors = theme::resolve(mode);
+                self.transition_to(theme::resolve(mode));
                 if mode == ThemeMode::Adaptive {
                     self.backdrop = true;
                 }
@@ -244,11 +847,11 @@ impl Hud {
                 Task::none()
             }
             Message::ThemeToggle => {
-                self.colors = if self.colors.is_dark {
+                self.transition_to(if self.colors.is_dark {
                     ThemeColors::light()
                 } else {
                     ThemeColors::dark()
-                };
+                });
                 eprintln!(
                     "[dev-hud] theme toggle -> {} (mode stays {:?})",
                     if self.colors.is_dark { "dark" } else { "light" },
@@ -257,39 +860,74 @@ impl Hud {
                 Task::none()
             }
             Message::ThemeRefresh => {
-                match self.theme_mode {
+                // Screen capture can block on the compositor; keep it off the UI thread
+                if self
+                    .theme_probe
+                    .is_some_and(|started| started.elapsed() < THEME_PROBE_TIMEOUT)
+                {
+                    return Task::none();
+                }
+                let task = match self.theme_mode {
                     ThemeMode::Auto => {
-                        let dark = theme::detect_system_dark();
-                        let was_dark = self.colors.is_dark;
-                        self.colors = if dark {
-                            ThemeColors::dark()
-                        } else {
-                            ThemeColors::light()
-                        };
-                        if was_dark != self.colors.is_dark {
-                            eprintln!(
-                                "[dev-hud] auto: switched to {}",
-                                if self.colors.is_dark { "dark" } else { "light" }
-                            );
-                        }
+                        spawn_blocking(theme::detect_system_dark, Message::SystemThemeDetected)
                     }
                     ThemeMode::Adaptive => {
-                        if let Some(lum) = theme::sample_bg_luminance() {
-                            let was_dark = self.colors.is_dark;
-                            self.colors = if lum <= 0.5 {
-                                ThemeColors::dark()
-                            } else {
-                                ThemeColors::light()
-                            };
-                            if was_dark != self.colors.is_dark {
-                                eprintln!(
-                                    "[dev-hud] adaptive: switched to {} (lum={lum:.3})",
-                                    if self.colors.is_dark { "dark" } else { "light" }
-                                );
-                            }
-                        }
+                        let output = self.target_output.clone();
+                        spawn_blocking(
+                            move || theme::sample_bg_luminance(output.as_deref()),
+                            Message::BackgroundSampled,
+                        )
+                    }
+                    _ => return Task::none(),
+                };
+                self.theme_probe = Some(Instant::now());
+                task
+            }
+            Message::SystemThemeDetected(dark) => {
+                self.theme_probe = None;
+                if self.theme_mode == ThemeMode::Auto {
+                    let was_dark = self.colors.is_dark;
+                    self.transition_to(if dark {
+                        ThemeColors::dark()
+                    } else {
+                        ThemeColors::light()
+                    });
+                    if was_dark != self.colors.is_dark {
+                        eprintln!(
+                            "[dev-hud] auto: switched to {}",
+                            if self.colors.is_dark { "dark" } else { "light" }
+                        );
+                    }
+                }
+                Task::none()
+            }
+            Message::BackgroundSampled(lum) => {
+                self.theme_probe = None;
+                if self.theme_mode == ThemeMode::Adaptive
+                    && let Some(lum) = lum
+                {
+                    let was_dark = self.colors.is_dark;
+                    self.transition_to(if lum <= 0.5 {
+                        ThemeColors::dark()
+                    } else {
+                        ThemeColors::light()
+                    });
+                    if was_dark != self.colors.is_dark {
+                        eprintln!(
+                            "[dev-hud] adaptive: switched to {} (lum={lum:.3})",
+                            if self.colors.is_dark { "dark" } else { "light" }
+                        );
+                    }
+                }
+                Task::none()
+            }
+            Message::ThemeFrame => {
+                if let Some(tr) = &self.theme_transition {
+                    let (colors, done) = tr.sample(Instant::now());
+                    self.colors = colors;
+                    if done {
+                        self.theme_transition = None;
                     }
-                    _ => {}
                 }
                 Task::none()
             }
@@ -298,8 +936,9 @@ impl Hud {
                 eprintln!("[dev-hud] backdrop -> {}", self.backdrop);
                 Task::none()
             }
-            Message::ScreenCycle => {
-                let outputs = enumerate_outputs();
+            // cosmic-randr/wlr-randr can take a while to answer
+            Message::ScreenCycle => spawn_blocking(enumerate_outputs, Message::OutputsListed),
+            Message::OutputsListed(outputs) => {
                 if outputs.is_empty() {
                     eprintln!("[dev-hud] screen cycle: no outputs found (is wlr-randr installed?)");
                     return Task::none();
@@ -314,6 +953,7 @@ impl Hud {
                 };
                 let next_output = &outputs[next_idx];
                 self.target_output = Some(next_output.clone());
+                self.stop_following();
                 eprintln!(
                     "[dev-hud] screen -> {} ({}/{})",
                     next_output,
@@ -324,13 +964,70 @@ impl Hud {
             }
             Message::ScreenSet(ref name) => {
                 self.target_output = Some(name.clone());
+                self.stop_following();
                 eprintln!("[dev-hud] screen -> {name}");
                 self.recreate_surface()
             }
-            Message::ShellEvent(event) => {
+            Message::ScreenFollowToggle => {
+                self.follow_focus = !self.follow_focus;
+                eprintln!(
+                    "[dev-hud] screen follow: {}",
+                    if self.follow_focus { "on" } else { "off" }
+                );
+                Task::none()
+            }
+            Message::FocusedOutput(name) => {
+                if !self.follow_focus || self.target_output.as_ref() == Some(&name) {
+                    return Task::none();
+                }
+                eprintln!("[dev-hud] screen -> {name} (focus)");
+                self.target_output = Some(name);
+                self.recreate_surface()
+            }
+            Message::ShellEvent(mut event) => {
+                self.run_shell_hooks(&mut event);
+                if let shell::ShellEvent::Output { lines, .. } = &event
+                    && lines.is_empty()
+                {
+                    // Every line was dropped by a hook
+                    return Task::none();
+                }
+                if self.demo && matches!(event, shell::ShellEvent::ConfigReloaded(_)) {
+                    // shells.md was created; its widgets replace the demo
+                    self.demo = false;
+                    self.demo_loader = None;
+                    eprintln!("[dev-hud] demo mode: off (config file created)");
+                }
                 if let Some(shells) = &mut self.shells {
                     shells.apply_event(&event);
                 }
+                if !self.rules.is_empty() {
+                    self.check_shell_rules(&event);
+                }
+                if let shell::ShellEvent::Exited {
+                    label,
+                    exit_code,
+                    signal,
+                } = &event
+                    && (signal.is_some() || exit_code.is_some_and(|code| code != 0))
+                    && self.hud_config.digest.enabled()
+                {
+                    let status = rules::exit_text(*exit_code, signal.as_deref());
+                    self.digest.record(&format!("{label}: {status}"));
+                }
+                Task::none()
+            }
+            Message::DigestDue => {
+                if self.hud_config.digest.enabled() {
+                    self.send_digest();
+                } else {
+                    eprintln!("[dev-hud] digest: not configured");
+                }
+                Task::none()
+            }
+            Message::PluginClick { label, action } => {
+                eprintln!("[dev-hud] plugin '{label}': click {action}");
+                shell::send_to_plugin(&label, shell::plugin::click_event(&action));
                 Task::none()
             }
             Message::ShellToggle => {
@@ -343,6 +1040,203 @@ impl Hud {
                 }
                 Task::none()
             }
+            Message::SurfaceResized(size) => {
+                if self.surface_width != Some(size.width) {
+                    eprintln!("[dev-hud] surface: {}x{}", size.width, size.height);
+                    self.surface_width = Some(size.width);
+                }
+                Task::none()
+            }
+            Message::WatchdogPing => {
+                systemd::notify("WATCHDOG=1");
+                Task::none()
+            }
+            Message::TextScale(steps) => {
+                self.step_text_scale(steps);
+                Task::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.ctrl_held = modifiers.control();
+                Task::none()
+            }
+            Message::ZoomScroll(lines) => {
+                if !self.ctrl_held {
+                    return Task::none();
+                }
+                // Touchpads send fractional lines; step once per whole line
+                self.zoom_scroll += lines;
+                let steps = self.zoom_scroll.trunc();
+                self.zoom_scroll -= steps;
+                if steps != 0.0 {
+                    self.step_text_scale(steps as i32);
+                }
+                Task::none()
+            }
+            Message::MinimalToggle => {
+                self.minimal = !self.minimal;
+                eprintln!("[dev-hud] minimal -> {}", self.minimal);
+                Task::none()
+            }
+            Message::HudConfigReloaded(config) => {
+                // Only a changed startup mode overrides guides toggled via IPC
+                if config.guides.mode != self.hud_config.guides.mode {
+                    self.guides = config.guides.mode;
+                }
+                if config.calendar != self.hud_config.calendar {
+                    self.calendar = None;
+                }
+                if config.text != self.hud_config.text {
+                    self.apply_text_scale(config.text.scale);
+                }
+                if config.alerts != self.hud_config.alerts {
+                    self.alerts.clear();
+                }
+                self.hud_config = *config;
+                eprintln!("[dev-hud] hud.md reloaded");
+                Task::none()
+            }
+            Message::GuidesCycle => {
+                self.guides = self.guides.next();
+                eprintln!("[dev-hud] guides -> {}", self.guides.label());
+                Task::none()
+            }
+            Message::GuidesSet(guides) => {
+                self.guides = guides;
+                eprintln!("[dev-hud] guides -> {}", self.guides.label());
+                Task::none()
+            }
+            Message::Highlight(highlight) => {
+                if self.mode == HudMode::Hidden {
+                    eprintln!("[dev-hud] highlight: HUD is hidden, nothing drawn");
+                    return Task::none();
+                }
+                eprintln!(
+                    "[dev-hud] highlight {}x{} at ({}, {}) for {:.1}s",
+                    highlight.width,
+                    highlight.height,
+                    highlight.x,
+                    highlight.y,
+                    highlight.duration.as_secs_f32()
+                );
+                self.highlight = Some((highlight, Instant::now() + highlight.duration));
+                Task::none()
+            }
+            Message::HighlightClear => {
+                self.highlight = None;
+                Task::none()
+            }
+            Message::Snapshot(path) => {
+                let Some(id) = self.surface_id else {
+                    eprintln!("[dev-hud] snapshot: HUD is hidden, nothing to save");
+                    return Task::none();
+                };
+                iced::window::screenshot(id)
+                    .map(move |screenshot| Message::SnapshotTaken(path.clone(), screenshot))
+            }
+            Message::SnapshotTaken(path, screenshot) => {
+                // PNG encoding of a full output takes a moment; keep it off the UI thread
+                std::thread::spawn(move || match snapshot::save(&path, &screenshot) {
+                    Ok(()) => eprintln!(
+                        "[dev-hud] snapshot: {}x{} saved to {}",
+                        screenshot.size.width,
+                        screenshot.size.height,
+                        path.display()
+                    ),
+                    Err(e) => eprintln!("[dev-hud] snapshot: {e}"),
+                });
+                Task::none()
+            }
+            Message::UpdateCheck => spawn_blocking(update::latest_tag, Message::UpdateChecked),
+            Message::UpdateChecked(Ok(tag)) => {
+                self.update_available = update::newer(env!("DEV_HUD_VERSION"), &tag);
+                match &self.update_available {
+                    Some(tag) => eprintln!("[dev-hud] update: {tag} available"),
+                    None => eprintln!("[dev-hud] update: up to date (latest {tag})"),
+                }
+                Task::none()
+            }
+            Message::UpdateChecked(Err(e)) => {
+                eprintln!("[dev-hud] update: check failed: {e}");
+                Task::none()
+            }
+            Message::TourStart => {
+                eprintln!("[dev-hud] tour: started");
+                self.tour = Some(Tour::new(Instant::now()));
+                Task::none()
+            }
+            Message::TourSeen(seen) => {
+                if !seen && self.tour.is_none() {
+                    eprintln!("[dev-hud] tour: first start, showing the tour");
+                    self.tour = Some(Tour::new(Instant::now()));
+                }
+                Task::none()
+            }
+            Message::TourNext => {
+                if let Some(tour) = &mut self.tour
+                    && !tour.advance(Instant::now())
+                {
+                    self.end_tour();
+                }
+                Task::none()
+            }
+            Message::TourEnd => {
+                if self.tour.is_some() {
+                    self.end_tour();
+                }
+                Task::none()
+            }
+            Message::CalendarUpdated(next) => {
+                if next != self.calendar
+                    && let Some(up) = &next
+                {
+                    eprintln!("[dev-hud] calendar: next is {:?}", up.summary);
+                }
+                self.calendar = next;
+                Task::none()
+            }
+            Message::AlertsUpdated(alerts) => {
+                let raised: Vec<String> = alerts
+                    .iter()
+                    .filter(|a| !self.alerts.contains(a))
+                    .map(Alert::text)
+                    .collect();
+                for text in &raised {
+                    eprintln!("[dev-hud] alert: {text}");
+                    self.push(text, Instant::now());
+                    self.apply_rules(rules::Event {
+                        trigger: rules::Trigger::Alert,
+                        widget: None,
+                        text,
+                    });
+                    if let Some(hooks) = &mut self.hooks {
+                        hooks.on_alert(text);
+                        let actions = hooks.take_actions();
+                        self.apply_hook_actions(actions);
+                    }
+                }
+                if alerts != self.alerts {
+                    dbus::alerts_changed(alerts.iter().map(Alert::text).collect());
+                }
+                self.alerts = alerts;
+                Task::none()
+            }
+            Message::HooksChanged(source) => {
+                self.hooks = hooks::load(source.as_deref());
+                eprintln!(
+                    "[dev-hud] hooks: {}",
+                    if self.hooks.is_some() {
+                        "loaded"
+                    } else {
+                        "none"
+                    }
+                );
+                Task::none()
+            }
+            Message::RulesReloaded(rules) => {
+                eprintln!("[dev-hud] rules: {} loaded", rules.len());
+                self.rules.replace(rules);
+                Task::none()
+            }
             _ => Task::none(),
         }
     }
@@ -353,22 +1247,109 @@ impl Hud {
 
     fn subscription(state: &Self) -> Subscription<Message> {
         let socket = Subscription::run(ipc::socket_listener);
-        let needs_tick = state.demo_loader.is_some() && state.mode != HudMode::Hidden;
+        // Ticks also expire the highlight
+        let needs_tick = (state.demo_loader.is_some()
+            || state.highlight.is_some()
+            || state.tour.is_some()
+            || state.auto_focus.is_some()
+            || state.peek.is_some()
+            || !state.attention.is_empty())
+            && state.mode != HudMode::Hidden;
 
-        let mut subs = vec![socket];
+        let mut subs = vec![
+            socket,
+            Subscription::run(ipc::dbus_stream),
+            Subscription::run(ipc::hud_config_stream),
+            Subscription::run(ipc::rules_stream),
+            Subscription::run(ipc::hooks_stream),
+            iced::window::resize_events().map(|(_, size)| Message::SurfaceResized(size)),
+        ];
 
         if needs_tick {
             subs.push(Subscription::run_with(TICK_MS, ipc::tick_stream));
         }
 
+        // Turns the pages of crowded positions
+        if state.hud_config.layout.overflow == Overflow::Scroll
+            && state.shells.is_some()
+            && state.mode != HudMode::Hidden
+        {
+            subs.push(Subscription::run_with(PAGE_TICK_MS, ipc::tick_stream));
+        }
+
         if state.shells.is_some() {
-            subs.push(Subscription::run(ipc::shell_event_stream));
+            // A missing shells.md means the demo widgets while demo mode is on.
+            // Keyed on the running mode, not the `demo` setting, so saving the
+            // setting doesn't restart real widgets.
+            subs.push(Subscription::run_with(state.demo, ipc::shell_event_stream));
+        }
+
+        if let Some(interval) = state.watchdog {
+            subs.push(Subscription::run_with(interval, ipc::watchdog_stream));
+        }
+
+        // Ctrl+scroll text zoom (only the focused surface gets input)
+        if state.mode == HudMode::Focused {
+            subs.push(iced::event::listen_with(
+                |event, _status, _id| match event {
+                    iced::Event::Keyboard(iced::keyboard::Event::ModifiersChanged(modifiers)) => {
+                        Some(Message::ModifiersChanged(modifiers))
+                    }
+                    iced::Event::Mouse(iced::mouse::Event::WheelScrolled { delta }) => {
+                        Some(Message::ZoomScroll(match delta {
+                            iced::mouse::ScrollDelta::Lines { y, .. } => y,
+                            iced::mouse::ScrollDelta::Pixels { y, .. } => y / 40.0,
+                        }))
+                    }
+                    _ => None,
+                },
+            ));
+        }
+
+        if state.follow_focus {
+            subs.push(Subscription::run(ipc::focused_output_stream));
+        }
+
+        if state.hud_config.calendar.path.is_some() {
+            subs.push(Subscription::run_with(
+                state.hud_config.calendar.clone(),
+                ipc::calendar_stream,
+            ));
+        }
+
+        if state.hud_config.updates.check {
+            subs.push(Subscription::run(ipc::update_stream));
+        }
+
+        if state.hud_config.digest.enabled() {
+            subs.push(Subscription::run_with(
+                state.hud_config.digest.clone(),
+                ipc::digest_stream,
+            ));
+        }
+
+        if state.hud_config.alerts.enabled() {
+            subs.push(Subscription::run_with(
+                state.hud_config.alerts.clone(),
+                ipc::alerts_stream,
+            ));
+        }
+
+        if state.theme_transition.is_some() && state.mode != HudMode::Hidden {
+            subs.push(Subscription::run_with(
+                THEME_FRAME_MS,
+                ipc::theme_frame_stream,
+            ));
         }
 
         // Theme refresh for auto/adaptive modes (5s interval)
         if matches!(state.theme_mode, ThemeMode::Auto | ThemeMode::Adaptive) {
             subs.push(Subscription::run(ipc::theme_refresh_stream));
         }
+        // Immediate refresh on wallpaper change (adaptive only)
+        if state.theme_mode == ThemeMode::Adaptive {
+            subs.push(Subscription::run(ipc::wallpaper_stream));
+        }
 
         Subscription::batch(subs)
     }
@@ -1,15 +1,15 @@
 use std::io::Write;
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
 use std::process;
 
-fn socket_path() -> PathBuf {
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(runtime_dir).join("dev-hud.sock")
-}
+use dev_hud::instance;
 
 fn main() {
-    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = instance::init_from_args(&mut args) {
+        eprintln!("{e}");
+        process::exit(1);
+    }
     if args.is_empty() {
         usage();
         process::exit(1);
@@ -18,9 +18,15 @@ fn main() {
     let cmd = args.join(" ");
     match cmd.as_str() {
         "toggle" | "focus" | "demo loader-toggle" | "demo loader-change" | "demo font-change"
-        | "theme dark" | "theme light" | "theme auto" | "theme adaptive" | "theme-toggle"
-        | "bg-toggle" | "shell-toggle" | "screen" => {}
-        _ if cmd.starts_with("screen ") => {}
+        | "demo dismiss" | "theme dark" | "theme light" | "theme auto" | "theme adaptive"
+        | "theme-toggle" | "bg-toggle" | "shell-toggle" | "minimal" | "ui text bigger"
+        | "ui text smaller" | "ui text reset" | "screen" | "screen follow" | "guides"
+        | "guides off" | "guides crosshair" | "guides thirds" | "digest" | "update check"
+        | "tour" | "tour next" | "tour end" | "peek" => {}
+        _ if cmd.starts_with("screen ")
+            || cmd.starts_with("peek ")
+            || cmd.starts_with("highlight ")
+            || cmd.starts_with("snapshot ") => {}
         _ => {
             eprintln!("unknown command: {cmd}");
             usage();
@@ -28,7 +34,19 @@ fn main() {
         }
     }
 
-    let path = socket_path();
+    // The daemon runs in its own working directory
+    let cmd = match cmd.strip_prefix("snapshot ") {
+        Some(file) => match std::path::absolute(file.trim()) {
+            Ok(file) => format!("snapshot {}", file.display()),
+            Err(e) => {
+                eprintln!("snapshot: {e}");
+                process::exit(1);
+            }
+        },
+        None => cmd,
+    };
+
+    let path = instance::socket_path();
     let mut stream = match UnixStream::connect(&path) {
         Ok(s) => s,
         Err(e) => {
@@ -44,14 +62,18 @@ fn main() {
 }
 
 fn usage() {
-    eprintln!("usage: dev-hud-ctl <command>");
+    eprintln!("usage: dev-hud-ctl [--instance <name>] <command>");
+    eprintln!();
+    eprintln!("--instance (or $DEV_HUD_INSTANCE) talks to `dev-hud --instance <name>`.");
     eprintln!();
     eprintln!("commands:");
     eprintln!("  toggle              toggle HUD visibility");
     eprintln!("  focus               toggle HUD focus/interactivity");
+    eprintln!("  peek [secs]         show (or focus) the HUD, then switch back (default 5s)");
     eprintln!("  demo loader-toggle  toggle demo loader widget");
     eprintln!("  demo loader-change  cycle demo loader animation style");
     eprintln!("  demo font-change    cycle HUD font");
+    eprintln!("  demo dismiss        leave first-run demo mode for good");
     eprintln!("  theme dark          force dark theme");
     eprintln!("  theme light         force light theme");
     eprintln!("  theme auto          follow DE system theme (updates dynamically)");
@@ -59,6 +81,22 @@ fn usage() {
     eprintln!("  theme-toggle        cycle between dark and light themes");
     eprintln!("  bg-toggle           toggle semi-transparent backdrop behind widgets");
     eprintln!("  shell-toggle        toggle shell output widgets");
+    eprintln!("  minimal             toggle minimal density (shell widgets as status dots)");
+    eprintln!("  ui text bigger      scale HUD text up 10% (saved to hud.md)");
+    eprintln!("  ui text smaller     scale HUD text down 10%");
+    eprintln!("  ui text reset       restore the default text size");
     eprintln!("  screen              cycle HUD to next monitor");
     eprintln!("  screen <name>       move HUD to specific output (e.g. DP-1, HDMI-A-1)");
+    eprintln!("  screen follow       toggle following the keyboard-focused output");
+    eprintln!("  guides              cycle alignment guides (off, crosshair, thirds)");
+    eprintln!("  guides <mode>       set guides: off, crosshair, thirds");
+    eprintln!("  highlight x y w h [secs]");
+    eprintln!("                      outline a screen region (logical px, default 3s)");
+    eprintln!("  highlight off       remove the current highlight");
+    eprintln!("  digest              email the digest now (hud.md '# digest')");
+    eprintln!("  snapshot <file.png> save the HUD as rendered to a PNG");
+    eprintln!("  update check        look for a newer release (shown in the info line)");
+    eprintln!("  tour                show the onboarding tour again");
+    eprintln!("  tour next           skip to the next tour step");
+    eprintln!("  tour end            close the tour");
 }
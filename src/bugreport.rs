@@ -0,0 +1,321 @@
+//! `dev-hud bugreport`: a tarball with what an issue report needs: version,
+//! compositor, sanitized configs with their check results, recent logs and
+//! whether the running HUD answers.
+//!
+//! Values that look like credentials are always replaced; `--redact` also
+//! hides paths under the home directory, which tend to name projects.
+
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::cli;
+use crate::focus;
+use crate::hooks;
+use crate::hud_config::{self, SECRET_KEYS};
+use crate::instance;
+use crate::rules;
+use crate::shell::config::{self, Severity};
+use crate::systemd;
+use crate::util;
+
+/// Journal lines included in the report.
+const LOG_LINES: &str = "500";
+
+/// Environment-style names whose values are replaced (`GITHUB_TOKEN=...`).
+const SECRET_NAMES: &[&str] = &["TOKEN", "SECRET", "PASSWORD", "PASSWD", "KEY"];
+
+/// Handle `dev-hud bugreport [--redact] [output.tar.gz]`. Returns the
+/// process exit code.
+pub(crate) fn bugreport_command(args: &[String]) -> i32 {
+    let mut redact = false;
+    let mut output = None;
+    for arg in args {
+        match arg.as_str() {
+            "--redact" => redact = true,
+            _ if arg.starts_with('-') || output.is_some() => {
+                eprintln!("usage: dev-hud bugreport [--redact] [output.tar.gz]");
+                return 2;
+            }
+            _ => output = Some(PathBuf::from(arg)),
+        }
+    }
+    let output = output
+        .unwrap_or_else(|| PathBuf::from(format!("dev-hud-bugreport-{}.tar.gz", util::unix_now())));
+    let home = dirs::home_dir().map(|h| h.display().to_string());
+    let scrub = |text: &str| scrub(text, home.as_deref(), redact);
+
+    let staging = std::env::temp_dir().join(format!("dev-hud-bugreport-{}", std::process::id()));
+    let root = staging.join("dev-hud-bugreport");
+    let result = (|| {
+        std::fs::create_dir_all(root.join("config"))
+            .map_err(|e| format!("{}: {e}", root.display()))?;
+        let mut files = vec![("system.txt".to_string(), system_info())];
+        let mut health = daemon_health();
+        for path in [
+            config::config_file_path(),
+            hud_config::hud_config_path(),
+            rules::rules_path(),
+            hooks::hooks_path(),
+        ] {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            match std::fs::read_to_string(&path) {
+                Ok(content) => {
+                    health.push_str(&check_summary(&path, &content));
+                    let content = if name == "hud.md" {
+                        hide_secret_keys(&content)
+                    } else {
+                        content
+                    };
+                    files.push((format!("config/{name}"), content));
+                }
+                Err(e) => {
+                    let _ = writeln!(health, "{name}: {e}");
+                }
+            }
+        }
+        files.push(("health.txt".to_string(), health));
+        files.push(("log.txt".to_string(), journal()));
+        for (name, content) in &files {
+            let path = root.join(name);
+            std::fs::write(&path, scrub(content))
+                .map_err(|e| format!("{}: {e}", path.display()))?;
+        }
+        tar(&staging, &output)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    match result {
+        Ok(()) => {
+            println!("{}: bug report written", output.display());
+            println!("Credentials are redacted; review the contents before sharing.");
+            0
+        }
+        Err(e) => {
+            eprintln!("bugreport: {e}");
+            1
+        }
+    }
+}
+
+fn system_info() -> String {
+    let mut out = format!(
+        "dev-hud {} ({})\ninstance: {}\n",
+        env!("DEV_HUD_VERSION"),
+        env!("DEV_HUD_COMMIT"),
+        instance::name().unwrap_or("default")
+    );
+    if let Some(kernel) = command_output("uname", &["-srm"]) {
+        let _ = writeln!(out, "kernel: {}", kernel.trim());
+    }
+    for var in [
+        "XDG_CURRENT_DESKTOP",
+        "XDG_SESSION_TYPE",
+        "WAYLAND_DISPLAY",
+        "DEV_HUD_SCREEN",
+    ] {
+        let value = std::env::var(var).unwrap_or_else(|_| "(unset)".to_string());
+        let _ = writeln!(out, "{var}: {value}");
+    }
+    let _ = writeln!(
+        out,
+        "focus IPC: {}",
+        focus::compositor_name().unwrap_or("none (screen follow unavailable)")
+    );
+    out
+}
+
+/// Whether the HUD answers on its socket, plus helper tools it shells out to.
+fn daemon_health() -> String {
+    let socket = instance::socket_path();
+    let mut out = match std::os::unix::net::UnixStream::connect(&socket) {
+        Ok(_) => format!("daemon: running ({})\n", socket.display()),
+        Err(e) => format!("daemon: not reachable ({}): {e}\n", socket.display()),
+    };
+    for (tool, purpose) in [
+        ("curl", "push notifications"),
+        ("grim", "adaptive theme capture fallback"),
+        ("journalctl", "logs in this report"),
+    ] {
+        let found = command_output("sh", &["-c", &format!("command -v {tool}")]).is_some();
+        let _ = writeln!(
+            out,
+            "{tool}: {} ({purpose})",
+            if found { "found" } else { "missing" }
+        );
+    }
+    out
+}
+
+/// One line per config issue, like `dev-hud config check`.
+fn check_summary(path: &Path, content: &str) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let (issues, summary) = cli::check_content(path, content);
+    let mut out = format!("{name}: {summary}, {} issue(s)\n", issues.len());
+    for issue in issues {
+        let kind = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        let _ = writeln!(out, "  {name}:{}: {kind}: {}", issue.line, issue.message);
+    }
+    out
+}
+
+/// The service's recent journal, when it runs as a systemd user unit.
+fn journal() -> String {
+    let unit = systemd::unit_name();
+    match command_output(
+        "journalctl",
+        &[
+            "--user",
+            "-u",
+            &unit,
+            "-n",
+            LOG_LINES,
+            "--no-pager",
+            "-o",
+            "short-iso",
+        ],
+    ) {
+        Some(log) if !log.trim().is_empty() && !log.starts_with("-- No entries --") => log,
+        _ => format!(
+            "No journal entries for {unit}. When dev-hud isn't run as a service, \
+             attach its stderr output (dev-hud 2> dev-hud.log).\n"
+        ),
+    }
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn tar(staging: &Path, output: &Path) -> Result<(), String> {
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(output)
+        .arg("-C")
+        .arg(staging)
+        .arg("dev-hud-bugreport")
+        .status()
+        .map_err(|e| format!("tar: {e}"))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("tar failed ({status})"))
+    }
+}
+
+/// Replace the values of `- key: value` entries for `SECRET_KEYS`.
+fn hide_secret_keys(content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    for line in content.lines() {
+        let secret = line
+            .trim_start()
+            .strip_prefix("- ")
+            .and_then(|entry| entry.split_once(':'))
+            .filter(|(key, _)| SECRET_KEYS.contains(&key.trim()));
+        match secret {
+            Some((key, _)) => {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let _ = writeln!(out, "{indent}- {}: <redacted>", key.trim());
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Hide credential-looking `NAME=value` words and the home directory; with
+/// `redact`, also every path under it.
+fn scrub(text: &str, home: Option<&str>, redact: bool) -> String {
+    let mut text = text.to_string();
+    if let Some(home) = home.filter(|h| h.len() > 1) {
+        text = text.replace(home, "~");
+    }
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let words: Vec<String> = line
+            .split(' ')
+            .map(|word| {
+                let word = match word.split_once('=') {
+                    Some((name, _))
+                        if SECRET_NAMES.iter().any(|s| name.to_uppercase().contains(s)) =>
+                    {
+                        format!("{name}=<redacted>")
+                    }
+                    _ => word.to_string(),
+                };
+                if redact { redact_paths(&word) } else { word }
+            })
+            .collect();
+        out.push_str(&words.join(" "));
+    }
+    out
+}
+
+/// `~/src/my-project/x` → `~/…`, keeping anything before the path.
+fn redact_paths(word: &str) -> String {
+    let Some(start) = word.find("~/") else {
+        return word.to_string();
+    };
+    let end = word[start..]
+        .find(['"', '\'', ')', ',', ';', '`'])
+        .map_or(word.len(), |i| start + i);
+    format!("{}~/…{}", &word[..start], redact_paths(&word[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hides_credentials() {
+        let hud = "# push\n- ntfy: https://ntfy.sh/secret-topic\n- interval: 1m\n  - to: me@example.com\n";
+        assert_eq!(
+            hide_secret_keys(hud),
+            "# push\n- ntfy: <redacted>\n- interval: 1m\n  - to: <redacted>\n"
+        );
+        assert_eq!(
+            scrub("- command: GITHUB_TOKEN=abc gh run list", None, false),
+            "- command: GITHUB_TOKEN=<redacted> gh run list"
+        );
+        assert_eq!(scrub("a=b", None, false), "a=b");
+    }
+
+    #[test]
+    fn health_hides_secrets_in_issues() {
+        let hud = "# push\n- pushover: onlyonepart\n- slack: hooks.example.com/T0/B0/xyz\n";
+        let health = check_summary(Path::new("hud.md"), hud);
+        assert!(health.contains("2 issue(s)"), "{health}");
+        assert!(!health.contains("onlyonepart"), "{health}");
+        assert!(!health.contains("hooks.example.com"), "{health}");
+        assert!(
+            health.contains("invalid pushover '<redacted>' (expected <user key> <app token>)"),
+            "{health}"
+        );
+    }
+
+    #[test]
+    fn redacts_home_and_paths() {
+        let log = "spawn '/home/me/src/acme-api/run.sh'\n- path: ~/.cache/acme/events";
+        assert_eq!(
+            scrub(log, Some("/home/me"), false),
+            "spawn '~/src/acme-api/run.sh'\n- path: ~/.cache/acme/events"
+        );
+        assert_eq!(
+            scrub(log, Some("/home/me"), true),
+            "spawn '~/…'\n- path: ~/…"
+        );
+        assert_eq!(redact_paths("cd ~/a;~/b"), "cd ~/…;~/…");
+    }
+}
@@ -0,0 +1,660 @@
+//! Countdown to the next meeting, read from local iCalendar (`.ics`) files.
+//!
+//! Covers what calendar sync tools (vdirsyncer, Thunderbird exports, ...)
+//! produce for day-to-day meetings: UTC, floating and `TZID` start times
+//! (the latter two are read as local time), daily/weekly `RRULE`s with
+//! `INTERVAL`, `BYDAY`, `COUNT` and `UNTIL`, `EXDATE`, and moved instances
+//! (`RECURRENCE-ID`). All-day and cancelled events are skipped; other
+//! recurrence frequencies only yield their first occurrence.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How long after its start an event still shows as "now".
+pub const STARTED_GRACE_SECS: i64 = 5 * 60;
+
+/// Directory levels searched below the configured path
+/// (vdirsyncer uses `<root>/<calendar>/<uid>.ics`).
+const MAX_DEPTH: usize = 3;
+
+/// The next event to count down to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upcoming {
+    pub summary: String,
+    /// Unix timestamp of the start.
+    pub start: i64,
+}
+
+/// How close the event is, which drives its color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Urgency {
+    /// More than 15 minutes away.
+    Later,
+    /// Within 15 minutes.
+    Soon,
+    /// Within 5 minutes.
+    Imminent,
+    /// Already started.
+    Now,
+}
+
+impl Upcoming {
+    /// "Standup in 12m", "Planning in 1h 05m" or "Standup now".
+    pub fn countdown(&self, now: i64) -> (String, Urgency) {
+        let remaining = self.start - now;
+        if remaining <= 0 {
+            return (format!("{} now", self.summary), Urgency::Now);
+        }
+        let mins = (remaining + 59) / 60;
+        let text = if mins < 60 {
+            format!("{} in {mins}m", self.summary)
+        } else {
+            format!("{} in {}h {:02}m", self.summary, mins / 60, mins % 60)
+        };
+        let urgency = match mins {
+            ..=5 => Urgency::Imminent,
+            6..=15 => Urgency::Soon,
+            _ => Urgency::Later,
+        };
+        (text, urgency)
+    }
+}
+
+/// A date-time as written in the file: seconds since 1970-01-01 00:00 on
+/// the wall clock, plus whether that wall clock is UTC or local time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    civil: i64,
+    utc: bool,
+}
+
+impl Stamp {
+    fn to_unix(self) -> i64 {
+        if self.utc {
+            self.civil
+        } else {
+            local_to_unix(self.civil)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Freq {
+    Daily,
+    Weekly,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rule {
+    freq: Freq,
+    interval: i64,
+    /// Weekdays, Monday = 0. Empty means "any day" (daily) or the start's
+    /// weekday (weekly).
+    by_day: Vec<i64>,
+    count: Option<u32>,
+    until: Option<Stamp>,
+}
+
+/// A `VEVENT` with the fields needed to compute occurrences.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CalendarEvent {
+    uid: String,
+    summary: String,
+    start: Option<Stamp>,
+    all_day: bool,
+    cancelled: bool,
+    rule: Option<Rule>,
+    /// Unix timestamps of excluded occurrences.
+    exdates: Vec<i64>,
+    /// Set on an override of one instance of a recurring event.
+    recurrence_id: Option<i64>,
+}
+
+/// Read every `.ics` file at `path` (a file, or a directory searched
+/// recursively) and return their events.
+pub fn load(path: &Path) -> Vec<CalendarEvent> {
+    let mut files = Vec::new();
+    collect_files(path, 0, &mut files);
+    let mut events: Vec<CalendarEvent> = files
+        .iter()
+        .filter_map(|f| std::fs::read_to_string(f).ok())
+        .flat_map(|content| parse_ics(&content))
+        .collect();
+    exclude_overridden(&mut events);
+    events
+}
+
+fn collect_files(path: &Path, depth: usize, out: &mut Vec<PathBuf>) {
+    if path.is_file() {
+        out.push(path.to_path_buf());
+        return;
+    }
+    if depth > MAX_DEPTH {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let p = entry.path();
+        if p.is_dir() {
+            collect_files(&p, depth + 1, out);
+        } else if p.extension().is_some_and(|e| e.eq_ignore_ascii_case("ics")) {
+            out.push(p);
+        }
+    }
+}
+
+/// Parse the `VEVENT`s of one iCalendar document.
+pub fn parse_ics(content: &str) -> Vec<CalendarEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<CalendarEvent> = None;
+    // Nested components (VALARM) have their own properties; ignore those
+    let mut nested = 0usize;
+
+    for line in unfold(content) {
+        let Some((name, params, value)) = split_property(&line) else {
+            continue;
+        };
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") => current = Some(CalendarEvent::default()),
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take() {
+                    events.push(event);
+                }
+                nested = 0;
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested = nested.saturating_sub(1),
+            _ => {
+                if nested == 0
+                    && let Some(event) = current.as_mut()
+                {
+                    apply_property(event, &name, &params, value);
+                }
+            }
+        }
+    }
+    events
+}
+
+fn apply_property(event: &mut CalendarEvent, name: &str, params: &[(String, String)], value: &str) {
+    let is_date = params
+        .iter()
+        .any(|(k, v)| k == "VALUE" && v.eq_ignore_ascii_case("DATE"));
+    match name {
+        "UID" => event.uid = value.to_string(),
+        "SUMMARY" => event.summary = unescape(value),
+        "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+        "DTSTART" => {
+            event.start = parse_stamp(value);
+            event.all_day = is_date || value.len() == 8;
+        }
+        "RRULE" => event.rule = parse_rule(value),
+        "EXDATE" => event
+            .exdates
+            .extend(value.split(',').filter_map(parse_stamp).map(Stamp::to_unix)),
+        "RECURRENCE-ID" => event.recurrence_id = parse_stamp(value).map(Stamp::to_unix),
+        _ => {}
+    }
+}
+
+/// Moved or cancelled instances of a recurring event are separate
+/// `VEVENT`s with a `RECURRENCE-ID`; the series must skip those instances.
+fn exclude_overridden(events: &mut [CalendarEvent]) {
+    let mut overridden: HashMap<String, Vec<i64>> = HashMap::new();
+    for event in events.iter() {
+        if let Some(id) = event.recurrence_id {
+            overridden.entry(event.uid.clone()).or_default().push(id);
+        }
+    }
+    for event in events.iter_mut() {
+        if event.recurrence_id.is_none()
+            && let Some(ids) = overridden.get(&event.uid)
+        {
+            event.exdates.extend(ids);
+        }
+    }
+}
+
+/// The earliest event starting within `lookahead` seconds, or one that
+/// started less than `STARTED_GRACE_SECS` ago.
+pub fn next_event(events: &[CalendarEvent], now: i64, lookahead: i64) -> Option<Upcoming> {
+    let from = now - STARTED_GRACE_SECS;
+    let to = now + lookahead;
+    events
+        .iter()
+        .filter(|e| !e.all_day && !e.cancelled)
+        .filter_map(|e| first_occurrence(e, from, to).map(|start| (start, e)))
+        .min_by_key(|(start, _)| *start)
+        .map(|(start, e)| Upcoming {
+            summary: if e.summary.is_empty() {
+                "(untitled)".to_string()
+            } else {
+                e.summary.clone()
+            },
+            start,
+        })
+}
+
+/// First start of `event` within `from..=to` (Unix seconds).
+fn first_occurrence(event: &CalendarEvent, from: i64, to: i64) -> Option<i64> {
+    let start = event.start?;
+    let in_window = |t: i64| t >= from && t <= to && !event.exdates.contains(&t);
+    let Some(rule) = &event.rule else {
+        let t = start.to_unix();
+        return in_window(t).then_some(t);
+    };
+
+    let start_day = start.civil.div_euclid(DAY);
+    let time_of_day = start.civil.rem_euclid(DAY);
+    let until = rule.until.map(Stamp::to_unix);
+    // Wall-clock window, padded a day each side for the UTC offset
+    let last_day = civil_of(to, start.utc).div_euclid(DAY) + 1;
+    // COUNT needs every occurrence from the start; otherwise skip ahead
+    let first_day = if rule.count.is_some() {
+        start_day
+    } else {
+        start_day.max(civil_of(from, start.utc).div_euclid(DAY) - 1)
+    };
+
+    let mut seen = 0u32;
+    for day in first_day..=last_day {
+        if !rule.matches(day, start_day) {
+            continue;
+        }
+        let t = Stamp {
+            civil: day * DAY + time_of_day,
+            utc: start.utc,
+        }
+        .to_unix();
+        if t < start.to_unix() {
+            continue;
+        }
+        seen += 1;
+        if rule.count.is_some_and(|count| seen > count) || until.is_some_and(|u| t > u) {
+            return None;
+        }
+        if in_window(t) {
+            return Some(t);
+        }
+        if t > to {
+            return None;
+        }
+    }
+    None
+}
+
+impl Rule {
+    fn matches(&self, day: i64, start_day: i64) -> bool {
+        if day < start_day {
+            return false;
+        }
+        let wd = weekday(day);
+        match self.freq {
+            Freq::Daily => {
+                (day - start_day) % self.interval == 0
+                    && (self.by_day.is_empty() || self.by_day.contains(&wd))
+            }
+            Freq::Weekly => {
+                let week = (day - wd - (start_day - weekday(start_day))) / 7;
+                let days: &[i64] = if self.by_day.is_empty() {
+                    &[weekday(start_day)]
+                } else {
+                    &self.by_day
+                };
+                week % self.interval == 0 && days.contains(&wd)
+            }
+        }
+    }
+}
+
+/// `FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;UNTIL=20261231T000000Z`.
+/// Unsupported frequencies return `None` (the event then occurs once).
+fn parse_rule(value: &str) -> Option<Rule> {
+    let mut rule = Rule {
+        freq: Freq::Daily,
+        interval: 1,
+        by_day: Vec::new(),
+        count: None,
+        until: None,
+    };
+    let mut freq = None;
+    for part in value.split(';') {
+        let Some((key, val)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                freq = match val.to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Freq::Daily),
+                    "WEEKLY" => Some(Freq::Weekly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => rule.interval = val.parse().ok().filter(|n| *n > 0)?,
+            "COUNT" => rule.count = Some(val.parse().ok()?),
+            "UNTIL" => rule.until = parse_stamp(val),
+            "BYDAY" => {
+                rule.by_day = val
+                    .split(',')
+                    // Ordinals ("1MO", "-1FR") only apply to monthly rules
+                    .filter_map(|d| {
+                        let d = d.trim_start_matches(|c: char| !c.is_ascii_alphabetic());
+                        WEEKDAYS.iter().position(|w| w.eq_ignore_ascii_case(d))
+                    })
+                    .map(|i| i as i64)
+                    .collect()
+            }
+            _ => {}
+        }
+    }
+    rule.freq = freq?;
+    Some(rule)
+}
+
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+const DAY: i64 = 86_400;
+
+/// Parse `20261016`, `20261016T093000` or `20261016T093000Z`.
+fn parse_stamp(value: &str) -> Option<Stamp> {
+    let value = value.trim();
+    let num = |range: std::ops::Range<usize>| -> Option<i64> {
+        let s = value.get(range)?;
+        s.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| s.parse().ok())?
+    };
+    let days = days_from_civil(num(0..4)?, num(4..6)?, num(6..8)?);
+    if value.len() == 8 {
+        return Some(Stamp {
+            civil: days * DAY,
+            utc: false,
+        });
+    }
+    if value.as_bytes().get(8) != Some(&b'T') {
+        return None;
+    }
+    let secs = num(9..11)? * 3600 + num(11..13)? * 60 + num(13..15)?;
+    let utc = match value.get(15..) {
+        Some("Z") => true,
+        Some("") => false,
+        _ => return None,
+    };
+    Some(Stamp {
+        civil: days * DAY + secs,
+        utc,
+    })
+}
+
+/// Join folded lines (continuations start with a space or tab).
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.lines() {
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// Property parameters as upper-cased `(key, value)` pairs.
+type Params = Vec<(String, String)>;
+
+/// Split `NAME;KEY=VAL;KEY="V:AL":value` into upper-cased name, params and value.
+fn split_property(line: &str) -> Option<(String, Params, &str)> {
+    let mut in_quotes = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            in_quotes = !in_quotes;
+            None
+        }
+        ':' if !in_quotes => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    let params = parts
+        .filter_map(|p| p.split_once('='))
+        .map(|(k, v)| (k.to_ascii_uppercase(), v.trim_matches('"').to_string()))
+        .collect();
+    Some((name, params, value))
+}
+
+/// Undo iCalendar text escaping (`\,`, `\;`, `\\`, `\n`).
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => out.push(' '),
+            Some(other) => out.push(other),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Monday = 0.
+fn weekday(days: i64) -> i64 {
+    // 1970-01-01 was a Thursday
+    (days + 3).rem_euclid(7)
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`: (year, month, day).
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (
+        if m <= 2 {
+            yoe + era * 400 + 1
+        } else {
+            yoe + era * 400
+        },
+        m,
+        d,
+    )
+}
+
+/// Wall-clock seconds of Unix time `t`, in UTC or local time.
+fn civil_of(t: i64, utc: bool) -> i64 {
+    if utc {
+        return t;
+    }
+    // SAFETY: an all-zero tm is valid; localtime_r only writes to it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    let time = t as libc::time_t;
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return t;
+    }
+    t + tm.tm_gmtoff
+}
+
+/// Unix time of a local wall-clock time (DST resolved by the C library).
+fn local_to_unix(civil: i64) -> i64 {
+    let (y, m, d) = civil_from_days(civil.div_euclid(DAY));
+    let secs = civil.rem_euclid(DAY);
+    // SAFETY: an all-zero tm is valid; mktime only reads and normalizes it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    tm.tm_year = (y - 1900) as i32;
+    tm.tm_mon = (m - 1) as i32;
+    tm.tm_mday = d as i32;
+    tm.tm_hour = (secs / 3600) as i32;
+    tm.tm_min = (secs % 3600 / 60) as i32;
+    tm.tm_sec = (secs % 60) as i32;
+    tm.tm_isdst = -1;
+    // SAFETY: tm is a valid, initialized struct owned by this frame.
+    unsafe { libc::mktime(&mut tm) as i64 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i64, mo: i64, d: i64, h: i64, mi: i64) -> i64 {
+        days_from_civil(y, mo, d) * DAY + h * 3600 + mi * 60
+    }
+
+    const STANDUP: &str = "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+UID:standup-1\r
+SUMMARY:Standup\\, team\r
+DTSTART:20261005T090000Z\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR\r
+EXDATE:20261015T090000Z\r
+BEGIN:VALARM\r
+SUMMARY:reminder\r
+END:VALARM\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:standup-1\r
+RECURRENCE-ID:20261016T090000Z\r
+SUMMARY:Standup (moved)\r
+DTSTART:20261016T100000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:retro\r
+SUMMARY:Retro\r
+DTSTART:20261016T093000Z\r
+STATUS:CANCELLED\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:offsite\r
+SUMMARY:Offsite\r
+DTSTART;VALUE=DATE:20261016\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    fn standup_events() -> Vec<CalendarEvent> {
+        let mut events = parse_ics(STANDUP);
+        exclude_overridden(&mut events);
+        events
+    }
+
+    #[test]
+    fn parse_properties() {
+        let events = parse_ics(STANDUP);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0].summary, "Standup, team");
+        assert_eq!(events[0].exdates, vec![utc(2026, 10, 15, 9, 0)]);
+        assert!(events[2].cancelled);
+        assert!(events[3].all_day);
+    }
+
+    #[test]
+    fn weekly_rule_skips_weekends_and_exdates() {
+        let events = standup_events();
+        let hour = 3600;
+        // Wednesday 08:50 -> today's standup
+        let next = next_event(&events, utc(2026, 10, 14, 8, 50), hour).unwrap();
+        assert_eq!(next.start, utc(2026, 10, 14, 9, 0));
+        // Thursday is excluded
+        assert_eq!(next_event(&events, utc(2026, 10, 15, 8, 50), hour), None);
+        // Saturday 08:50 -> nothing within the hour
+        assert_eq!(next_event(&events, utc(2026, 10, 17, 8, 50), hour), None);
+        // Sunday evening, long lookahead -> Monday
+        let next = next_event(&events, utc(2026, 10, 18, 20, 0), 24 * hour).unwrap();
+        assert_eq!(next.start, utc(2026, 10, 19, 9, 0));
+    }
+
+    #[test]
+    fn moved_instance_replaces_series_occurrence() {
+        let events = standup_events();
+        let next = next_event(&events, utc(2026, 10, 16, 8, 50), 2 * 3600).unwrap();
+        assert_eq!(next.summary, "Standup (moved)");
+        assert_eq!(next.start, utc(2026, 10, 16, 10, 0));
+    }
+
+    #[test]
+    fn count_until_and_interval() {
+        let ics = "BEGIN:VEVENT\nUID:a\nSUMMARY:A\nDTSTART:20261001T120000Z\n\
+                   RRULE:FREQ=DAILY;INTERVAL=2;COUNT=3\nEND:VEVENT\n\
+                   BEGIN:VEVENT\nUID:b\nSUMMARY:B\nDTSTART:20261001T130000Z\n\
+                   RRULE:FREQ=DAILY;UNTIL=20261002T235959Z\nEND:VEVENT\n";
+        let events = parse_ics(ics);
+        let at = |d: i64| next_event(&events, utc(2026, 10, d, 11, 30), 3 * 3600);
+        // A on the 1st, 3rd, 5th; B on the 1st and 2nd
+        assert_eq!(at(1).unwrap().summary, "A");
+        assert_eq!(at(2).unwrap().summary, "B");
+        assert_eq!(at(3).unwrap().summary, "A");
+        assert_eq!(at(4), None);
+        assert_eq!(at(5).unwrap().summary, "A");
+        assert_eq!(at(7), None);
+    }
+
+    #[test]
+    fn started_events_stay_briefly() {
+        let events = parse_ics("BEGIN:VEVENT\nSUMMARY:Sync\nDTSTART:20261016T090000Z\nEND:VEVENT");
+        assert!(next_event(&events, utc(2026, 10, 16, 9, 4), 3600).is_some());
+        assert!(next_event(&events, utc(2026, 10, 16, 9, 6), 3600).is_none());
+    }
+
+    #[test]
+    fn folded_lines_and_quoted_params() {
+        let ics = "BEGIN:VEVENT\nSUMMARY:Quarterly\n  planning\n\
+                   DTSTART;TZID=\"GMT:+1\":20261016T090000\nEND:VEVENT";
+        let events = parse_ics(ics);
+        assert_eq!(events[0].summary, "Quarterly planning");
+        assert_eq!(
+            events[0].start,
+            Some(Stamp {
+                civil: utc(2026, 10, 16, 9, 0),
+                utc: false
+            })
+        );
+    }
+
+    #[test]
+    fn countdown_text_and_urgency() {
+        let up = Upcoming {
+            summary: "Standup".to_string(),
+            start: 10_000,
+        };
+        assert_eq!(
+            up.countdown(10_000 - 12 * 60),
+            ("Standup in 12m".to_string(), Urgency::Soon)
+        );
+        assert_eq!(up.countdown(10_000 - 30).1, Urgency::Imminent);
+        assert_eq!(up.countdown(10_000 - 30).0, "Standup in 1m");
+        assert_eq!(
+            up.countdown(10_000 - 65 * 60),
+            ("Standup in 1h 05m".to_string(), Urgency::Later)
+        );
+        assert_eq!(up.countdown(10_000 + 60).1, Urgency::Now);
+    }
+
+    #[test]
+    fn civil_round_trip() {
+        for days in [-1, 0, 59, 365 * 30 + 7, 20_742] {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+        assert_eq!(days_from_civil(2026, 10, 16), 20_742);
+        assert_eq!(WEEKDAYS[weekday(20_742) as usize], "FR");
+    }
+}
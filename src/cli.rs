@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+use crate::hooks;
+use crate::hud_config;
+use crate::rules;
+use crate::shell::config::{self, ConfigIssue, Severity};
+
+/// The config files dev-hud reads, told apart by file name.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ConfigKind {
+    /// `shells.md`: shell widgets.
+    Shells,
+    /// `hud.md`: markers, guides and other HUD-wide settings.
+    Hud,
+    /// `rules.md`: event rules and their actions.
+    Rules,
+    /// `hooks.rhai`: scripting hooks.
+    Hooks,
+}
+
+impl ConfigKind {
+    fn of(path: &Path) -> Self {
+        match path.file_name().and_then(|n| n.to_str()) {
+            Some("hud.md") => ConfigKind::Hud,
+            Some("rules.md") => ConfigKind::Rules,
+            Some("hooks.rhai") => ConfigKind::Hooks,
+            _ => ConfigKind::Shells,
+        }
+    }
+
+    fn example(self) -> &'static str {
+        match self {
+            ConfigKind::Shells => config::EXAMPLE_CONFIG,
+            ConfigKind::Hud => hud_config::EXAMPLE_HUD_CONFIG,
+            ConfigKind::Rules => rules::EXAMPLE_RULES,
+            ConfigKind::Hooks => hooks::EXAMPLE_HOOKS,
+        }
+    }
+}
+
+/// Handle `dev-hud config <subcommand>`. Returns the process exit code.
+/// Without a path, `shells.md`, `hud.md`, `rules.md` and `hooks.rhai` are
+/// handled.
+pub(crate) fn config_command(args: &[String]) -> i32 {
+    let explicit = args.get(1).map(PathBuf::from);
+    let paths = match &explicit {
+        Some(path) => vec![path.clone()],
+        None => vec![
+            config::config_file_path(),
+            hud_config::hud_config_path(),
+            rules::rules_path(),
+            hooks::hooks_path(),
+        ],
+    };
+    match args.first().map(String::as_str) {
+        Some("check") => paths
+            .iter()
+            // Only shells.md is required; by default check the others if they exist
+            .filter(|p| explicit.is_some() || ConfigKind::of(p) == ConfigKind::Shells || p.exists())
+            .map(|p| check(p))
+            .max()
+            .unwrap_or(0),
+        Some("init") => {
+            let results: Vec<i32> = paths.iter().map(|p| init(p)).collect();
+            // Fail only if nothing was written
+            if results.contains(&0) { 0 } else { 1 }
+        }
+        _ => {
+            config_usage();
+            2
+        }
+    }
+}
+
+fn config_usage() {
+    eprintln!("usage: dev-hud config <command>");
+    eprintln!();
+    eprintln!("commands:");
+    eprintln!(
+        "  check [path]  validate config files (default ~/.config/viz/shells.md, hud.md, rules.md, hooks.rhai)"
+    );
+    eprintln!("  init [path]   write example config files (never overwrites)");
+}
+
+/// Write the example config for `path`'s kind, creating parent directories.
+/// Refuses to touch an existing file.
+fn init(path: &Path) -> i32 {
+    if path.exists() {
+        eprintln!(
+            "{}: already exists, not overwriting (remove it first to regenerate)",
+            path.display()
+        );
+        return 1;
+    }
+    if let Some(parent) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!("{}: cannot create directory: {e}", parent.display());
+        return 1;
+    }
+    let example = ConfigKind::of(path).example();
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut f| std::io::Write::write_all(&mut f, example.as_bytes()));
+    match written {
+        Ok(()) => {
+            println!("{}: wrote example config", path.display());
+            0
+        }
+        Err(e) => {
+            eprintln!("{}: cannot write: {e}", path.display());
+            1
+        }
+    }
+}
+
+/// Validate a config file, printing every issue with the offending line.
+/// Exits 1 if there are errors, 0 if only warnings (or none).
+fn check(path: &Path) -> i32 {
+    let content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}: cannot read: {e}", path.display());
+            return 1;
+        }
+    };
+    let (issues, summary) = check_content(path, &content);
+    report(path, &content, &issues, &summary)
+}
+
+/// Issues in `content`, read from `path`, and a one-line summary.
+pub(crate) fn check_content(path: &Path, content: &str) -> (Vec<ConfigIssue>, String) {
+    match ConfigKind::of(path) {
+        ConfigKind::Shells => {
+            let parsed = config::check_config(content);
+            let summary = format!("{} widget(s)", parsed.configs.len());
+            (parsed.issues, summary)
+        }
+        ConfigKind::Hud => (
+            hud_config::check_hud_config(content).issues,
+            "hud settings".to_string(),
+        ),
+        ConfigKind::Rules => {
+            let parsed = rules::check_rules(content);
+            let summary = format!("{} rule(s)", parsed.rules.len());
+            (parsed.issues, summary)
+        }
+        ConfigKind::Hooks => match hooks::check_hooks(content) {
+            Ok(defined) => (Vec::new(), format!("hooks: {}", defined.join(", "))),
+            Err(issue) => (vec![issue], "hooks".to_string()),
+        },
+    }
+}
+
+/// Print issues with their source lines plus a summary; 1 if any are errors.
+fn report(path: &Path, content: &str, issues: &[ConfigIssue], summary: &str) -> i32 {
+    let source: Vec<&str> = content.lines().collect();
+
+    for issue in issues {
+        let kind = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        println!(
+            "{}:{}: {kind}: {}",
+            path.display(),
+            issue.line,
+            issue.message
+        );
+        if let Some(line) = source.get(issue.line - 1) {
+            println!("{:>5} | {line}", issue.line);
+        }
+    }
+
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    println!(
+        "{}: {summary}, {errors} error(s), {warnings} warning(s)",
+        path.display()
+    );
+    if errors > 0 { 1 } else { 0 }
+}
@@ -0,0 +1,150 @@
+//! D-Bus control interface on the session bus, alongside the unix socket.
+//!
+//! Service `org.fuabioo.DevHud` (`.<instance>` for named instances), object `/org/fuabioo/DevHud`, interface
+//! `org.fuabioo.DevHud1`. Methods map onto the socket commands (and go
+//! through the same `ipc::parse_command`); signals report HUD mode changes
+//! and battery/thermal alerts.
+
+use std::sync::OnceLock;
+
+use futures::channel::mpsc;
+use zbus::fdo;
+use zbus::object_server::SignalEmitter;
+
+use crate::app::Message;
+use crate::instance;
+use crate::ipc;
+
+const PATH: &str = "/org/fuabioo/DevHud";
+
+/// Set once the service is up; signals are dropped before that.
+static CONNECTION: OnceLock<zbus::blocking::Connection> = OnceLock::new();
+
+struct Control {
+    tx: mpsc::UnboundedSender<Message>,
+}
+
+impl Control {
+    fn send(&self, cmd: &str) -> fdo::Result<()> {
+        let msg = ipc::parse_command(cmd).map_err(fdo::Error::InvalidArgs)?;
+        self.tx
+            .unbounded_send(msg)
+            .map_err(|_| fdo::Error::Failed("HUD is shutting down".to_string()))
+    }
+}
+
+#[zbus::interface(name = "org.fuabioo.DevHud1")]
+impl Control {
+    /// Any `dev-hud-ctl` command line, e.g. "theme dark".
+    fn command(&self, command: &str) -> fdo::Result<()> {
+        self.send(command.trim())
+    }
+
+    fn toggle(&self) -> fdo::Result<()> {
+        self.send("toggle")
+    }
+
+    fn focus(&self) -> fdo::Result<()> {
+        self.send("focus")
+    }
+
+    /// Show (or focus) the HUD for `seconds`; <= 0 uses the default.
+    fn peek(&self, seconds: f64) -> fdo::Result<()> {
+        if seconds > 0.0 {
+            self.send(&format!("peek {seconds}"))
+        } else {
+            self.send("peek")
+        }
+    }
+
+    /// dark, light, auto, adaptive, or "toggle".
+    fn theme(&self, mode: &str) -> fdo::Result<()> {
+        match mode {
+            "toggle" => self.send("theme-toggle"),
+            mode => self.send(&format!("theme {mode}")),
+        }
+    }
+
+    fn backdrop_toggle(&self) -> fdo::Result<()> {
+        self.send("bg-toggle")
+    }
+
+    fn shell_toggle(&self) -> fdo::Result<()> {
+        self.send("shell-toggle")
+    }
+
+    fn minimal_toggle(&self) -> fdo::Result<()> {
+        self.send("minimal")
+    }
+
+    /// bigger, smaller or reset.
+    fn text(&self, step: &str) -> fdo::Result<()> {
+        self.send(&format!("ui text {step}"))
+    }
+
+    /// An output name, "follow", or "" to cycle.
+    fn screen(&self, name: &str) -> fdo::Result<()> {
+        self.send(format!("screen {name}").trim())
+    }
+
+    /// off, crosshair, thirds, or "" to cycle.
+    fn guides(&self, mode: &str) -> fdo::Result<()> {
+        self.send(format!("guides {mode}").trim())
+    }
+
+    /// Outline a region in logical px; `seconds` <= 0 uses the default.
+    fn highlight(&self, x: f64, y: f64, width: f64, height: f64, seconds: f64) -> fdo::Result<()> {
+        let duration = if seconds > 0.0 {
+            format!(" {seconds}")
+        } else {
+            String::new()
+        };
+        self.send(&format!("highlight {x} {y} {width} {height}{duration}"))
+    }
+
+    fn highlight_off(&self) -> fdo::Result<()> {
+        self.send("highlight off")
+    }
+
+    /// hidden, visible or focused.
+    #[zbus(signal)]
+    async fn mode_changed(emitter: &SignalEmitter<'_>, mode: &str) -> zbus::Result<()>;
+
+    /// The current alert texts (empty once everything has cleared).
+    #[zbus(signal)]
+    async fn alerts_changed(emitter: &SignalEmitter<'_>, alerts: Vec<String>) -> zbus::Result<()>;
+}
+
+/// Claim the bus name and serve the interface, forwarding calls to `tx`.
+/// zbus runs the object server on its own thread, so this returns once
+/// the name is acquired.
+pub(crate) fn serve(tx: mpsc::UnboundedSender<Message>) -> Result<(), String> {
+    let connection = zbus::blocking::connection::Builder::session()
+        .and_then(|b| b.name(instance::bus_name()))
+        .and_then(|b| b.serve_at(PATH, Control { tx }))
+        .and_then(|b| b.build())
+        .map_err(|e| e.to_string())?;
+    let _ = CONNECTION.set(connection);
+    Ok(())
+}
+
+fn emit(signal: impl FnOnce(&SignalEmitter<'static>) -> zbus::Result<()>) {
+    let Some(connection) = CONNECTION.get() else {
+        return;
+    };
+    let result = connection
+        .object_server()
+        .interface::<_, Control>(PATH)
+        .and_then(|iface| signal(iface.signal_emitter()));
+    if let Err(e) = result {
+        eprintln!("[dev-hud] dbus signal: {e}");
+    }
+}
+
+pub(crate) fn mode_changed(mode: &str) {
+    emit(|emitter| zbus::block_on(Control::mode_changed(emitter, mode)));
+}
+
+pub(crate) fn alerts_changed(alerts: Vec<String>) {
+    emit(|emitter| zbus::block_on(Control::alerts_changed(emitter, alerts)));
+}
@@ -0,0 +1,162 @@
+//! Daily email digest (`# digest` in `hud.md`): attention texts, alerts and
+//! widget failures collected during the day, mailed over SMTP at a set time
+//! for people who prefer one summary over push noise.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{SmtpTransport, Transport};
+
+use crate::hud_config::DigestConfig;
+use crate::util;
+
+/// Entries kept between digests; older ones are dropped and counted.
+const MAX_ENTRIES: usize = 500;
+
+/// Give up on the SMTP server after this long.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Events collected since the last digest.
+#[derive(Debug, Default)]
+pub struct Digest {
+    entries: VecDeque<(SystemTime, String)>,
+    dropped: usize,
+}
+
+impl Digest {
+    pub fn record(&mut self, text: &str) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+        self.entries
+            .push_back((SystemTime::now(), text.to_string()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The mail body for everything collected so far, emptying the digest.
+    /// None if nothing happened.
+    pub fn take_body(&mut self) -> Option<String> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let mut body = String::new();
+        if self.dropped > 0 {
+            body.push_str(&format!("({} earlier entries dropped)\n", self.dropped));
+        }
+        for (time, text) in self.entries.drain(..) {
+            body.push_str(&format!("{} {text}\n", util::clock_time(time)));
+        }
+        self.dropped = 0;
+        Some(body)
+    }
+}
+
+/// Whether the digest is due at local `(day, minute)`, given the day it was
+/// last sent.
+pub fn is_due(config: &DigestConfig, (day, minute): (i64, u32), last_day: i64) -> bool {
+    day > last_day && minute >= config.time_minutes
+}
+
+/// Mail `body` with `subject`. Blocks on the SMTP exchange, so call it off
+/// the UI thread.
+pub fn send(config: &DigestConfig, subject: &str, body: String) -> Result<(), String> {
+    let (Some(server), Some(to)) = (&config.smtp, &config.to) else {
+        return Err("digest needs 'smtp' and 'to'".to_string());
+    };
+    let from = config.from.as_ref().or(config.user.as_ref()).unwrap_or(to);
+    let mail = lettre::Message::builder()
+        .from(from.parse().map_err(|e| format!("from {from:?}: {e}"))?)
+        .to(to.parse().map_err(|e| format!("to {to:?}: {e}"))?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(body)
+        .map_err(|e| e.to_string())?;
+
+    let (host, port) = match server.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            Some(
+                port.parse::<u16>()
+                    .map_err(|_| format!("smtp port {port:?}"))?,
+            ),
+        ),
+        None => (server.as_str(), None),
+    };
+    let builder = match port {
+        _ if host == "localhost" || host == "127.0.0.1" => {
+            SmtpTransport::builder_dangerous(host).port(port.unwrap_or(25))
+        }
+        Some(587) => SmtpTransport::starttls_relay(host).map_err(|e| e.to_string())?,
+        Some(port) => SmtpTransport::relay(host)
+            .map_err(|e| e.to_string())?
+            .port(port),
+        None => SmtpTransport::relay(host).map_err(|e| e.to_string())?,
+    };
+    let mut builder = builder.timeout(Some(SMTP_TIMEOUT));
+    if let Some(user) = &config.user {
+        let password = match &config.password_command {
+            Some(command) => password(command)?,
+            None => String::new(),
+        };
+        builder = builder.credentials(Credentials::new(user.clone(), password));
+    }
+    builder
+        .build()
+        .send(&mail)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// First line printed by `command`.
+fn password(command: &str) -> Result<String, String> {
+    let output = std::process::Command::new("sh")
+        .args(["-c", command])
+        .stdin(std::process::Stdio::null())
+        .output()
+        .map_err(|e| format!("password_command: {e}"))?;
+    if !output.status.success() {
+        return Err(format!("password_command failed ({})", output.status));
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or("").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_and_drains() {
+        let mut digest = Digest::default();
+        assert_eq!(digest.take_body(), None);
+        for i in 0..MAX_ENTRIES + 2 {
+            digest.record(&format!("event {i}"));
+        }
+        let body = digest.take_body().unwrap();
+        assert!(body.starts_with("(2 earlier entries dropped)\n"));
+        assert!(body.ends_with(&format!("event {}\n", MAX_ENTRIES + 1)));
+        assert!(digest.is_empty());
+        assert_eq!(digest.take_body(), None);
+    }
+
+    #[test]
+    fn due_once_a_day() {
+        let config = DigestConfig::default(); // 18:00
+        assert!(!is_due(&config, (100, 17 * 60 + 59), 99));
+        assert!(is_due(&config, (100, 18 * 60), 99));
+        assert!(!is_due(&config, (100, 23 * 60), 100));
+        assert!(is_due(&config, (101, 18 * 60 + 5), 100));
+    }
+
+    #[test]
+    fn password_from_command() {
+        assert_eq!(password("printf 'hunter2\\nrest'").unwrap(), "hunter2");
+        assert!(password("exit 3").is_err());
+    }
+}
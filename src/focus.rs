@@ -0,0 +1,182 @@
+//! Keyboard-focused output from compositor IPC, for `screen follow`.
+//!
+//! Supported: Hyprland (event socket), sway (`swaymsg -t subscribe`) and
+//! niri (`niri msg event-stream`). Others have no focus events we can use.
+
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// Compositor IPC used to watch focus, detected from its environment.
+enum Compositor {
+    Hyprland(PathBuf),
+    Sway,
+    Niri,
+}
+
+impl Compositor {
+    fn detect() -> Option<Self> {
+        if let Ok(signature) = std::env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+            let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".into());
+            // Hyprland moved its sockets from /tmp/hypr to the runtime dir in 0.40
+            return [runtime_dir.as_str(), "/tmp"]
+                .iter()
+                .map(|dir| PathBuf::from(dir).join(format!("hypr/{signature}/.socket2.sock")))
+                .find(|p| p.exists())
+                .map(Compositor::Hyprland);
+        }
+        if std::env::var_os("SWAYSOCK").is_some() {
+            return Some(Compositor::Sway);
+        }
+        if std::env::var_os("NIRI_SOCKET").is_some() {
+            return Some(Compositor::Niri);
+        }
+        None
+    }
+}
+
+/// Name of the compositor whose focus events `screen follow` can use.
+pub(crate) fn compositor_name() -> Option<&'static str> {
+    Compositor::detect().map(|compositor| match compositor {
+        Compositor::Hyprland(_) => "Hyprland",
+        Compositor::Sway => "sway",
+        Compositor::Niri => "niri",
+    })
+}
+
+/// Call `send` with the newly focused output's name on every focus change,
+/// until it returns false or the compositor connection ends.
+pub(crate) fn watch(mut send: impl FnMut(String) -> bool) -> Result<(), String> {
+    let Some(compositor) = Compositor::detect() else {
+        return Err("no supported compositor IPC (Hyprland, sway, niri)".to_string());
+    };
+    match compositor {
+        Compositor::Hyprland(path) => {
+            let stream = std::os::unix::net::UnixStream::connect(&path)
+                .map_err(|e| format!("connect {path:?}: {e}"))?;
+            for line in std::io::BufReader::new(stream).lines() {
+                let line = line.map_err(|e| e.to_string())?;
+                if let Some(output) = parse_hyprland(&line)
+                    && !send(output.to_string())
+                {
+                    break;
+                }
+            }
+        }
+        Compositor::Sway => {
+            let mut child = spawn("swaymsg", &["-t", "subscribe", "-m", "[\"workspace\"]"])?;
+            let stdout = child.stdout.take().expect("piped stdout");
+            // swaymsg may pretty-print, so read whole JSON values, not lines
+            for value in serde_json::Deserializer::from_reader(stdout).into_iter() {
+                let value: serde_json::Value = value.map_err(|e| e.to_string())?;
+                if let Some(output) = parse_sway(&value)
+                    && !send(output)
+                {
+                    break;
+                }
+            }
+            let _ = child.kill();
+        }
+        Compositor::Niri => {
+            let mut child = spawn("niri", &["msg", "--json", "event-stream"])?;
+            let stdout = child.stdout.take().expect("piped stdout");
+            let mut niri = NiriWorkspaces::default();
+            for line in std::io::BufReader::new(stdout).lines() {
+                let line = line.map_err(|e| e.to_string())?;
+                let Ok(value) = serde_json::from_str(&line) else {
+                    continue;
+                };
+                if let Some(output) = niri.apply(&value)
+                    && !send(output)
+                {
+                    break;
+                }
+            }
+            let _ = child.kill();
+        }
+    }
+    Ok(())
+}
+
+fn spawn(cmd: &str, args: &[&str]) -> Result<std::process::Child, String> {
+    Command::new(cmd)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("{cmd}: {e}"))
+}
+
+/// `focusedmon>>DP-1,3` → `DP-1`.
+fn parse_hyprland(line: &str) -> Option<&str> {
+    let (output, _workspace) = line.strip_prefix("focusedmon>>")?.split_once(',')?;
+    Some(output)
+}
+
+/// A workspace event with `"change": "focus"` names its output.
+fn parse_sway(event: &serde_json::Value) -> Option<String> {
+    if event["change"] != "focus" {
+        return None;
+    }
+    event["current"]["output"].as_str().map(str::to_string)
+}
+
+/// niri reports focus by workspace id; outputs come from the workspace list.
+#[derive(Default)]
+struct NiriWorkspaces {
+    outputs: HashMap<u64, String>,
+}
+
+impl NiriWorkspaces {
+    fn apply(&mut self, event: &serde_json::Value) -> Option<String> {
+        if let Some(workspaces) = event["WorkspacesChanged"]["workspaces"].as_array() {
+            self.outputs = workspaces
+                .iter()
+                .filter_map(|ws| Some((ws["id"].as_u64()?, ws["output"].as_str()?.to_string())))
+                .collect();
+            return workspaces
+                .iter()
+                .find(|ws| ws["is_focused"] == true)
+                .and_then(|ws| ws["output"].as_str())
+                .map(str::to_string);
+        }
+        let activated = &event["WorkspaceActivated"];
+        if activated["focused"] == true {
+            return self.outputs.get(&activated["id"].as_u64()?).cloned();
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn hyprland_and_sway_events() {
+        assert_eq!(parse_hyprland("focusedmon>>DP-1,3"), Some("DP-1"));
+        assert_eq!(parse_hyprland("focusedmonv2>>DP-1,3"), None);
+        assert_eq!(parse_hyprland("workspace>>3"), None);
+
+        let focus = json!({"change": "focus", "current": {"name": "2", "output": "HDMI-A-1"}});
+        assert_eq!(parse_sway(&focus).as_deref(), Some("HDMI-A-1"));
+        let rename = json!({"change": "rename", "current": {"output": "HDMI-A-1"}});
+        assert_eq!(parse_sway(&rename), None);
+    }
+
+    #[test]
+    fn niri_tracks_workspace_outputs() {
+        let mut niri = NiriWorkspaces::default();
+        let changed = json!({"WorkspacesChanged": {"workspaces": [
+            {"id": 1, "output": "eDP-1", "is_focused": true},
+            {"id": 2, "output": "DP-2", "is_focused": false},
+        ]}});
+        assert_eq!(niri.apply(&changed).as_deref(), Some("eDP-1"));
+        let activated = json!({"WorkspaceActivated": {"id": 2, "focused": true}});
+        assert_eq!(niri.apply(&activated).as_deref(), Some("DP-2"));
+        let unfocused = json!({"WorkspaceActivated": {"id": 1, "focused": false}});
+        assert_eq!(niri.apply(&unfocused), None);
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::Duration;
+
+/// How long a highlight stays up when no duration is given.
+pub const DEFAULT_DURATION: Duration = Duration::from_secs(3);
+
+/// Upper bound for the duration, so a typo can't pin a box on screen.
+const MAX_SECS: f32 = 600.0;
+
+/// A screen region to outline, in logical pixels from the output's
+/// top-left corner.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Highlight {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub duration: Duration,
+}
+
+impl Highlight {
+    /// Parse the arguments of `highlight x y w h [secs]`.
+    pub fn parse(args: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = args.split_whitespace().collect();
+        if !(4..=5).contains(&parts.len()) {
+            return Err(format!("expected 'x y w h [secs]', got {:?}", args.trim()));
+        }
+        let number = |name: &str, value: &str| -> Result<f32, String> {
+            match value.parse::<f32>() {
+                Ok(v) if v.is_finite() && v >= 0.0 => Ok(v),
+                _ => Err(format!("invalid {name} '{value}' (expected a number >= 0)")),
+            }
+        };
+        let x = number("x", parts[0])?;
+        let y = number("y", parts[1])?;
+        let width = number("width", parts[2])?;
+        let height = number("height", parts[3])?;
+        if width == 0.0 || height == 0.0 {
+            return Err("width and height must be greater than 0".to_string());
+        }
+        let duration = match parts.get(4) {
+            Some(secs) => {
+                let secs = number("secs", secs)?;
+                if secs == 0.0 {
+                    return Err("secs must be greater than 0".to_string());
+                }
+                Duration::from_secs_f32(secs.min(MAX_SECS))
+            }
+            None => DEFAULT_DURATION,
+        };
+        Ok(Self {
+            x,
+            y,
+            width,
+            height,
+            duration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rect_with_and_without_duration() {
+        let h = Highlight::parse("10 20 300 150").unwrap();
+        assert_eq!((h.x, h.y, h.width, h.height), (10.0, 20.0, 300.0, 150.0));
+        assert_eq!(h.duration, DEFAULT_DURATION);
+
+        let h = Highlight::parse(" 0 0 50.5 40  1.5 ").unwrap();
+        assert_eq!(h.width, 50.5);
+        assert_eq!(h.duration, Duration::from_millis(1500));
+
+        let h = Highlight::parse("0 0 1 1 99999").unwrap();
+        assert_eq!(h.duration, Duration::from_secs(600));
+    }
+
+    #[test]
+    fn reject_bad_args() {
+        assert!(Highlight::parse("").is_err());
+        assert!(Highlight::parse("1 2 3").is_err());
+        assert!(Highlight::parse("1 2 3 4 5 6").is_err());
+        assert!(Highlight::parse("-1 2 3 4").is_err());
+        assert!(Highlight::parse("1 2 0 4").is_err());
+        assert!(Highlight::parse("1 2 3 4 0").is_err());
+        assert!(Highlight::parse("a 2 3 4").is_err());
+        assert!(Highlight::parse("1 2 3 NaN").is_err());
+    }
+}
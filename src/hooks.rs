@@ -0,0 +1,272 @@
+//! Rhai scripting hooks (`~/.config/viz/hooks.rhai`): rewrite or drop widget
+//! output lines and react to exits and alerts with the same actions rules
+//! have. The escape hatch for what `rules.md` can't express.
+//!
+//! A script defines any of:
+//!
+//! ```rhai
+//! fn on_shell_line(widget, text) { }  // a string replaces the line, false drops it
+//! fn on_shell_exit(widget, text) { }  // text: "exit 1" or "signal Killed"
+//! fn on_alert(text) { }
+//! ```
+//!
+//! and can call `notify(title, body)`, `run(command)`, `attention(text)`,
+//! `hide(widget)` and `show(widget)`.
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use rhai::{AST, CallFnOptions, Dynamic, Engine, FuncArgs, Scope};
+
+use crate::shell::config::{ConfigIssue, Severity};
+
+/// Operations a single hook call may run before it is aborted, so a runaway
+/// loop can't freeze the HUD.
+const MAX_OPERATIONS: u64 = 200_000;
+
+/// Something a hook asked for; carried out by the HUD.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookAction {
+    Notify { title: String, body: String },
+    Run(String),
+    Attention(String),
+    Hide(String),
+    Show(String),
+}
+
+/// A compiled hooks script.
+pub struct Hooks {
+    engine: Engine,
+    ast: AST,
+    actions: Rc<RefCell<Vec<HookAction>>>,
+    /// Last error logged, so a hook failing on every line logs once.
+    last_error: Option<String>,
+}
+
+/// Return the path to the hooks script.
+pub fn hooks_path() -> PathBuf {
+    crate::instance::config_dir().join("hooks.rhai")
+}
+
+/// Read the hooks script, or None if there is none.
+pub fn read_source() -> Option<String> {
+    std::fs::read_to_string(hooks_path()).ok()
+}
+
+/// Compile the hooks script read by `read_source`, or None if there is
+/// none or it doesn't compile (the error is logged).
+pub fn load(source: Option<&str>) -> Option<Hooks> {
+    match Hooks::compile(source?) {
+        Ok(hooks) => Some(hooks),
+        Err(issue) => {
+            eprintln!("[dev-hud] hooks.rhai: error: {issue}");
+            None
+        }
+    }
+}
+
+/// Compile-check a hooks script for `dev-hud config check`; returns the
+/// hooks it defines.
+pub fn check_hooks(source: &str) -> Result<Vec<String>, ConfigIssue> {
+    let hooks = Hooks::compile(source)?;
+    Ok(HOOKS
+        .iter()
+        .filter(|&&(name, arity)| hooks.defines(name, arity))
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+/// Hook names and their parameter counts.
+const HOOKS: &[(&str, usize)] = &[("on_shell_line", 2), ("on_shell_exit", 2), ("on_alert", 1)];
+
+/// Commented starter script written by `dev-hud config init`.
+pub const EXAMPLE_HOOKS: &str = r#"// Hooks for dev-hud (Rhai, https://rhai.rs; hot-reloaded).
+//
+// fn on_shell_line(widget, text)  return a string to replace the line,
+//                                 false to drop it, nothing to keep it
+// fn on_shell_exit(widget, text)  text is "exit <code>" or "signal <name>"
+// fn on_alert(text)               battery/thermal alerts from hud.md
+//
+// Actions: notify(title, body), run(command), attention(text),
+//          hide(widget), show(widget)
+// Run `dev-hud config check` after editing to validate this file.
+
+fn on_shell_line(widget, text) {
+    // Drop cargo's progress noise
+    if widget == "cargo" && text.starts_with("   Compiling ") {
+        return false;
+    }
+}
+
+fn on_shell_exit(widget, text) {
+    if text != "exit 0" {
+        attention(widget + " " + text);
+    }
+}
+"#;
+
+impl Hooks {
+    fn compile(source: &str) -> Result<Self, ConfigIssue> {
+        let actions: Rc<RefCell<Vec<HookAction>>> = Rc::default();
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.on_print(|s| eprintln!("[dev-hud] hooks: {s}"));
+        engine.on_debug(|s, _, pos| eprintln!("[dev-hud] hooks: {pos:?}: {s}"));
+
+        let queue = actions.clone();
+        engine.register_fn("notify", move |title: &str, body: &str| {
+            queue.borrow_mut().push(HookAction::Notify {
+                title: title.to_string(),
+                body: body.to_string(),
+            });
+        });
+        type Ctor = fn(String) -> HookAction;
+        let simple: [(&str, Ctor); 4] = [
+            ("run", HookAction::Run),
+            ("attention", HookAction::Attention),
+            ("hide", HookAction::Hide),
+            ("show", HookAction::Show),
+        ];
+        for (name, action) in simple {
+            let queue = actions.clone();
+            engine.register_fn(name, move |arg: &str| {
+                queue.borrow_mut().push(action(arg.to_string()));
+            });
+        }
+
+        let ast = engine.compile(source).map_err(|e| ConfigIssue {
+            line: e.1.line().unwrap_or(1),
+            label: None,
+            severity: Severity::Error,
+            message: e.0.to_string(),
+        })?;
+        Ok(Hooks {
+            engine,
+            ast,
+            actions,
+            last_error: None,
+        })
+    }
+
+    fn defines(&self, name: &str, arity: usize) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == arity)
+    }
+
+    /// Call `name` if the script defines it; errors are logged.
+    fn call(&mut self, name: &str, arity: usize, args: impl FuncArgs) -> Option<Dynamic> {
+        if !self.defines(name, arity) {
+            return None;
+        }
+        // Call the function only; the script's top-level statements never run
+        let options = CallFnOptions::new().eval_ast(false);
+        let result =
+            self.engine
+                .call_fn_with_options(options, &mut Scope::new(), &self.ast, name, args);
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                let error = format!("{name}: {e}");
+                if self.last_error.as_ref() != Some(&error) {
+                    eprintln!("[dev-hud] hooks: {error}");
+                    self.last_error = Some(error);
+                }
+                None
+            }
+        }
+    }
+
+    /// Run `on_shell_line`. Returns the text to show, or None to drop it.
+    pub fn on_shell_line(&mut self, widget: &str, text: String) -> Option<String> {
+        let args = (widget.to_string(), text.clone());
+        match self.call("on_shell_line", 2, args) {
+            Some(value) if value.is_string() => value.into_string().ok(),
+            Some(value) if value.as_bool() == Ok(false) => None,
+            _ => Some(text),
+        }
+    }
+
+    pub fn on_shell_exit(&mut self, widget: &str, text: &str) {
+        self.call("on_shell_exit", 2, (widget.to_string(), text.to_string()));
+    }
+
+    pub fn on_alert(&mut self, text: &str) {
+        self.call("on_alert", 1, (text.to_string(),));
+    }
+
+    /// Actions requested by hooks since the last call.
+    pub fn take_actions(&mut self) -> Vec<HookAction> {
+        std::mem::take(&mut self.actions.borrow_mut())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_hook_rewrites_and_drops() {
+        let mut hooks = Hooks::compile(
+            r#"
+            fn on_shell_line(widget, text) {
+                if text.contains("noise") { return false; }
+                if widget == "ci" { return "[ci] " + text; }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            hooks.on_shell_line("ci", "ok".into()).as_deref(),
+            Some("[ci] ok")
+        );
+        assert_eq!(
+            hooks.on_shell_line("top", "ok".into()).as_deref(),
+            Some("ok")
+        );
+        assert_eq!(hooks.on_shell_line("ci", "noise".into()), None);
+        // Undefined hooks are no-ops
+        hooks.on_alert("battery 5%");
+        assert!(hooks.take_actions().is_empty());
+    }
+
+    #[test]
+    fn actions_and_errors() {
+        let mut hooks = Hooks::compile(
+            r#"
+            fn on_shell_exit(widget, text) {
+                if text != "exit 0" {
+                    attention(widget + " " + text);
+                    notify("dev-hud", widget);
+                    hide(widget);
+                }
+            }
+            fn on_shell_line(widget, text) { loop {} }
+            "#,
+        )
+        .unwrap();
+        hooks.on_shell_exit("build", "exit 0");
+        hooks.on_shell_exit("build", "exit 2");
+        assert_eq!(
+            hooks.take_actions(),
+            vec![
+                HookAction::Attention("build exit 2".into()),
+                HookAction::Notify {
+                    title: "dev-hud".into(),
+                    body: "build".into()
+                },
+                HookAction::Hide("build".into()),
+            ]
+        );
+        // A runaway hook is stopped and the line kept
+        assert_eq!(hooks.on_shell_line("x", "y".into()).as_deref(), Some("y"));
+
+        let issue = check_hooks("fn on_alert(text) {\n  let = 1;\n}\n").unwrap_err();
+        assert_eq!(issue.line, 2);
+        assert_eq!(
+            check_hooks(EXAMPLE_HOOKS).unwrap(),
+            vec!["on_shell_line", "on_shell_exit"]
+        );
+    }
+}
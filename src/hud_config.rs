@@ -0,0 +1,1258 @@
+use std::path::PathBuf;
+
+use crate::shell::Position;
+use crate::shell::config::{
+    ConfigIssue, Problem, Severity, clamped, expand_home, invalid, parse_duration_secs,
+};
+
+/// Which corner/edge markers are drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkerCount {
+    /// No markers.
+    None,
+    /// Top-left and bottom-right only.
+    Diagonal,
+    /// All four corners.
+    #[default]
+    Corners,
+    /// Corners plus the midpoint of each edge.
+    Edges,
+}
+
+impl MarkerCount {
+    pub fn corners(self) -> bool {
+        matches!(self, MarkerCount::Corners | MarkerCount::Edges)
+    }
+}
+
+/// Alignment guides drawn across the whole output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Guides {
+    #[default]
+    Off,
+    /// One vertical and one horizontal line through the center.
+    Crosshair,
+    /// Rule-of-thirds grid.
+    Thirds,
+}
+
+impl Guides {
+    pub fn next(self) -> Self {
+        match self {
+            Guides::Off => Guides::Crosshair,
+            Guides::Crosshair => Guides::Thirds,
+            Guides::Thirds => Guides::Off,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "off" | "none" => Some(Guides::Off),
+            "crosshair" | "center" => Some(Guides::Crosshair),
+            "thirds" | "grid" => Some(Guides::Thirds),
+            _ => None,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Guides::Off => "off",
+            Guides::Crosshair => "crosshair",
+            Guides::Thirds => "thirds",
+        }
+    }
+}
+
+/// Corner marker appearance. `None` fields fall back to the theme.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MarkerConfig {
+    pub glyph: String,
+    pub size: Option<f32>,
+    /// RGBA, 0.0–1.0.
+    pub color: Option<[f32; 4]>,
+    pub count: MarkerCount,
+}
+
+impl Default for MarkerConfig {
+    fn default() -> Self {
+        Self {
+            glyph: "+".to_string(),
+            size: None,
+            color: None,
+            count: MarkerCount::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GuidesConfig {
+    /// Guides shown at startup; `dev-hud-ctl guides` changes it at runtime.
+    pub mode: Guides,
+    pub color: Option<[f32; 4]>,
+}
+
+/// How widgets are arranged on the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LayoutMode {
+    /// Grouped into the four corners by `position`.
+    #[default]
+    Corners,
+    /// One slim column along the left edge (ultrawide displays).
+    Left,
+    /// One slim column along the right edge.
+    Right,
+}
+
+/// What happens when a position's widgets exceed `max_lines`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    /// Draw everything (widgets may run into each other).
+    #[default]
+    Off,
+    /// Trim the tallest widgets' output first.
+    Shrink,
+    /// Hide the lowest-priority widgets.
+    Hide,
+    /// Show the widgets a page at a time, turning pages every
+    /// `LayoutConfig::PAGE_SECS`.
+    Scroll,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutConfig {
+    pub mode: LayoutMode,
+    /// Side panel width in logical pixels.
+    pub width: f32,
+    pub overflow: Overflow,
+    /// Rows one position may use, labels included.
+    pub max_lines: usize,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            mode: LayoutMode::default(),
+            width: 420.0,
+            overflow: Overflow::default(),
+            max_lines: 40,
+        }
+    }
+}
+
+impl LayoutConfig {
+    /// Seconds each page is shown with `overflow: scroll`.
+    pub const PAGE_SECS: i64 = 5;
+
+    /// Text columns for a widget: its own `cols`, further limited to what
+    /// fits its pixel budget (minus the 2-column indent). The budget is the
+    /// side panel width, or `corner_width` (if known) in the corners layout.
+    /// `cell_width` is the measured advance of one monospace cell.
+    pub fn fit_cols(&self, cols: usize, cell_width: f32, corner_width: Option<f32>) -> usize {
+        let budget = match self.mode {
+            LayoutMode::Corners => corner_width,
+            LayoutMode::Left | LayoutMode::Right => Some(self.width),
+        };
+        let Some(budget) = budget.filter(|_| cell_width > 0.0) else {
+            return cols;
+        };
+        let fits = (budget / cell_width) as usize;
+        cols.min(fits.saturating_sub(2).max(1))
+    }
+
+    /// Fit one position's widgets, highest priority first, into
+    /// `max_lines`. `wanted` holds each widget's output rows (its label
+    /// costs one more); returns the rows to show, or None to hide it.
+    /// `page` picks the page shown with `Overflow::Scroll` (wrapping).
+    pub fn fit_lines(&self, wanted: &[usize], page: u64) -> Vec<Option<usize>> {
+        let total: usize = wanted.iter().map(|w| w + 1).sum();
+        if self.overflow == Overflow::Off || total <= self.max_lines {
+            return wanted.iter().map(|&w| Some(w)).collect();
+        }
+        match self.overflow {
+            Overflow::Off => unreachable!(),
+            Overflow::Hide => {
+                // Keep a prefix; the first widget always stays (trimmed if needed)
+                let mut used = 0;
+                let mut fitted = Vec::with_capacity(wanted.len());
+                for (i, &w) in wanted.iter().enumerate() {
+                    if i == 0 {
+                        let rows = w.min(self.max_lines.saturating_sub(1)).max(1);
+                        used = rows + 1;
+                        fitted.push(Some(rows));
+                    } else if fitted.last() == Some(&None) || used + w + 1 > self.max_lines {
+                        fitted.push(None);
+                    } else {
+                        used += w + 1;
+                        fitted.push(Some(w));
+                    }
+                }
+                fitted
+            }
+            Overflow::Shrink => {
+                // Largest cap (at least one row each) that fits the output budget
+                let budget = self.max_lines.saturating_sub(wanted.len());
+                let max = wanted.iter().copied().max().unwrap_or(0);
+                let cap = (1..=max)
+                    .rev()
+                    .find(|&cap| wanted.iter().map(|&w| w.min(cap)).sum::<usize>() <= budget)
+                    .unwrap_or(1);
+                wanted.iter().map(|&w| Some(w.min(cap))).collect()
+            }
+            Overflow::Scroll => {
+                // Split into pages that fit (a widget alone on one is trimmed)
+                let mut pages = 1;
+                let mut used = 0;
+                let mut placed = Vec::with_capacity(wanted.len());
+                for &w in wanted {
+                    let rows = w.min(self.max_lines.saturating_sub(1)).max(1);
+                    if used > 0 && used + rows + 1 > self.max_lines {
+                        pages += 1;
+                        used = 0;
+                    }
+                    used += rows + 1;
+                    placed.push((pages - 1, rows));
+                }
+                let shown = (page % pages as u64) as usize;
+                placed
+                    .into_iter()
+                    .map(|(p, rows)| (p == shown).then_some(rows))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Meeting countdown from local `.ics` files. Disabled without a path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CalendarConfig {
+    /// An `.ics` file or a directory searched for them.
+    pub path: Option<PathBuf>,
+    pub position: Position,
+    /// Only events starting within this many seconds are shown.
+    pub lookahead_secs: u64,
+}
+
+impl Default for CalendarConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            position: Position::TopRight,
+            lookahead_secs: 3600,
+        }
+    }
+}
+
+/// Battery/thermal warning band thresholds. `None` disables a check.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlertsConfig {
+    /// Warn at or below this charge while discharging.
+    pub battery_percent: Option<u8>,
+    /// Warn when any thermal zone reaches this many °C.
+    pub temperature_c: Option<u32>,
+}
+
+impl AlertsConfig {
+    pub fn enabled(&self) -> bool {
+        self.battery_percent.is_some() || self.temperature_c.is_some()
+    }
+}
+
+impl Default for AlertsConfig {
+    fn default() -> Self {
+        Self {
+            battery_percent: Some(10),
+            temperature_c: Some(90),
+        }
+    }
+}
+
+/// Phone push backends for attention events. All are off by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PushConfig {
+    /// ntfy topic URL, e.g. `https://ntfy.sh/my-topic`.
+    pub ntfy: Option<String>,
+    /// Pushover `(user key, app token)`.
+    pub pushover: Option<(String, String)>,
+    /// Telegram `(bot token, chat id)`.
+    pub telegram: Option<(String, String)>,
+    /// Slack incoming webhook URL.
+    pub slack: Option<String>,
+    /// Discord webhook URL.
+    pub discord: Option<String>,
+    /// Minimum seconds between two pushes to the same backend.
+    pub interval_secs: u64,
+}
+
+impl Default for PushConfig {
+    fn default() -> Self {
+        Self {
+            ntfy: None,
+            pushover: None,
+            telegram: None,
+            slack: None,
+            discord: None,
+            interval_secs: 60,
+        }
+    }
+}
+
+/// Daily email digest of attention texts, alerts and widget failures.
+/// Disabled without `smtp` and `to`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DigestConfig {
+    /// SMTP server as `host` or `host:port` (465 implicit TLS by default,
+    /// 587 STARTTLS, plain for localhost).
+    pub smtp: Option<String>,
+    pub user: Option<String>,
+    /// Shell command printing the SMTP password (e.g. `pass show smtp`).
+    pub password_command: Option<String>,
+    /// Sender address; defaults to `user`, then `to`.
+    pub from: Option<String>,
+    pub to: Option<String>,
+    /// Local time the digest is sent, in minutes after midnight.
+    pub time_minutes: u32,
+}
+
+impl DigestConfig {
+    pub fn enabled(&self) -> bool {
+        self.smtp.is_some() && self.to.is_some()
+    }
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            smtp: None,
+            user: None,
+            password_command: None,
+            from: None,
+            to: None,
+            time_minutes: 18 * 60,
+        }
+    }
+}
+
+/// Release check on startup and once a day. Off by default; the HUD only
+/// shows that a newer version exists.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UpdatesConfig {
+    pub check: bool,
+}
+
+/// Switching to focused mode when an attention text is raised, for
+/// clicking through without toggling first. Off by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttentionConfig {
+    pub focus: bool,
+    /// Back to visible after this long even if attention is still raised.
+    pub timeout_secs: u64,
+}
+
+impl Default for AttentionConfig {
+    fn default() -> Self {
+        Self {
+            focus: false,
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// First-run demo mode, started when no `shells.md` exists.
+/// `dev-hud-ctl demo dismiss` turns it off for good.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemoConfig {
+    pub auto: bool,
+}
+
+impl Default for DemoConfig {
+    fn default() -> Self {
+        Self { auto: true }
+    }
+}
+
+/// Text size multiplier; `dev-hud-ctl ui text bigger|smaller` saves it here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextConfig {
+    pub scale: f32,
+}
+
+impl TextConfig {
+    pub const MIN_SCALE: f32 = 0.5;
+    pub const MAX_SCALE: f32 = 3.0;
+}
+
+impl Default for TextConfig {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+/// HUD-wide settings from `~/.config/viz/hud.md`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HudConfig {
+    pub markers: MarkerConfig,
+    pub guides: GuidesConfig,
+    pub calendar: CalendarConfig,
+    pub alerts: AlertsConfig,
+    pub layout: LayoutConfig,
+    pub text: TextConfig,
+    pub push: PushConfig,
+    pub digest: DigestConfig,
+    pub demo: DemoConfig,
+    pub updates: UpdatesConfig,
+    pub attention: AttentionConfig,
+}
+
+pub struct ParsedHudConfig {
+    pub config: HudConfig,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Return the path to the HUD config file.
+pub fn hud_config_path() -> PathBuf {
+    crate::instance::config_dir().join("hud.md")
+}
+
+/// Commented starter config written by `dev-hud config init`.
+pub const EXAMPLE_HUD_CONFIG: &str = r#"<!--
+HUD settings for dev-hud (hot-reloaded; no restart needed).
+
+Each `# heading` is a section; entries are `- key: value`.
+Colors are #rrggbb or #rrggbbaa; leave them out to follow the theme.
+Run `dev-hud config check` after editing to validate this file.
+-->
+
+# markers
+- glyph: +
+- size: 16
+- count: 4
+
+# guides
+- mode: off
+- color: #ffffff40
+
+# layout
+- mode: corners
+- width: 420
+- overflow: off
+- max_lines: 40
+
+<!--
+# calendar
+- path: ~/.calendars
+- position: top-right
+- lookahead: 1h
+-->
+
+# alerts
+- battery: 10%
+- temperature: 90
+
+<!--
+# push
+- ntfy: https://ntfy.sh/my-topic
+- pushover: <user key> <app token>
+- telegram: <bot token> <chat id>
+- slack: https://hooks.slack.com/services/...
+- discord: https://discord.com/api/webhooks/...
+- interval: 1m
+-->
+
+<!--
+# digest
+- smtp: smtp.example.com
+- user: me@example.com
+- password_command: pass show smtp
+- to: me@example.com
+- time: 18:00
+-->
+
+<!--
+# updates
+- check: on
+-->
+
+<!--
+# attention
+- focus: on
+- timeout: 30s
+-->
+"#;
+
+/// Set `key` in `section` of `hud.md`, creating the file, section or entry
+/// as needed. Used to persist settings changed at runtime.
+pub fn save_entry(section: &str, key: &str, value: &str) -> Result<(), String> {
+    let path = hud_config_path();
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("{}: {e}", path.display())),
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+    }
+    std::fs::write(&path, set_entry(&content, section, key, value))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// `content` with `- key: value` set in `# section`. An existing entry is
+/// replaced in place; otherwise it is added after the section's last entry,
+/// or in a new section at the end. Commented-out blocks are left alone.
+pub fn set_entry(content: &str, section: &str, key: &str, value: &str) -> String {
+    let entry = format!("- {key}: {value}");
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let mut current: Option<String> = None;
+    let mut in_comment = false;
+    // Where to insert if the key is missing: after the heading or last entry
+    let mut insert_at = None;
+
+    for (idx, line) in lines.iter_mut().enumerate() {
+        let trimmed = line.trim();
+        if !in_comment && trimmed.contains("<!--") {
+            in_comment = true;
+        }
+        if in_comment {
+            if trimmed.contains("-->") {
+                in_comment = false;
+            }
+            continue;
+        }
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            current = Some(heading.trim().to_lowercase());
+            if current.as_deref() == Some(section) {
+                insert_at = Some(idx + 1);
+            }
+            continue;
+        }
+        if current.as_deref() != Some(section) {
+            continue;
+        }
+        let Some(item) = trimmed.strip_prefix("- ") else {
+            continue;
+        };
+        if item.split_once(':').is_some_and(|(k, _)| k.trim() == key) {
+            *line = entry;
+            return join_lines(&lines);
+        }
+        insert_at = Some(idx + 1);
+    }
+
+    match insert_at {
+        Some(idx) => lines.insert(idx, entry),
+        None => {
+            if lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                lines.push(String::new());
+            }
+            lines.push(format!("# {section}"));
+            lines.push(entry);
+        }
+    }
+    join_lines(&lines)
+}
+
+fn join_lines(lines: &[String]) -> String {
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// Keys holding tokens, webhook URLs or mail accounts. Issues never quote
+/// their values, and bug reports redact them.
+pub const SECRET_KEYS: &[&str] = &[
+    "ntfy",
+    "pushover",
+    "telegram",
+    "slack",
+    "discord",
+    "smtp",
+    "user",
+    "password_command",
+    "from",
+    "to",
+];
+
+/// Load the HUD config, falling back to defaults if the file is missing.
+/// Problems are logged; the UI uses whatever parsed cleanly.
+pub fn load() -> HudConfig {
+    let path = hud_config_path();
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return HudConfig::default();
+    };
+    let parsed = check_hud_config(&content);
+    for issue in &parsed.issues {
+        let kind = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        eprintln!("[dev-hud] hud.md: {kind}: {issue}");
+    }
+    parsed.config
+}
+
+/// Parse `hud.md`, reporting unknown sections/keys and bad values by line.
+///
+/// Format (values are taken verbatim, so no trailing `# notes`; see the
+/// README for what each key accepts):
+/// ```markdown
+/// # markers
+/// - glyph: ⌜
+/// - size: 20
+/// - color: #ff8800cc
+/// - count: 8
+///
+/// # guides
+/// - mode: thirds
+/// - color: #ffffff40
+///
+/// # layout
+/// - mode: right
+/// - width: 360
+/// - overflow: hide
+/// - max_lines: 30
+///
+/// # text
+/// - scale: 1.2
+///
+/// # calendar
+/// - path: ~/.calendars
+/// - position: top-right
+/// - lookahead: 2h
+///
+/// # alerts
+/// - battery: 15%
+/// - temperature: 85
+///
+/// # push
+/// - ntfy: https://ntfy.sh/my-topic
+/// - pushover: <user key> <app token>
+/// - telegram: <bot token> <chat id>
+/// - slack: https://hooks.slack.com/services/...
+/// - discord: https://discord.com/api/webhooks/...
+/// - interval: 1m
+///
+/// # digest
+/// - smtp: smtp.example.com:587
+/// - user: me@example.com
+/// - password_command: pass show smtp
+/// - to: me@example.com
+/// - time: 18:00
+/// ```
+pub fn check_hud_config(content: &str) -> ParsedHudConfig {
+    let mut config = HudConfig::default();
+    let mut issues = Vec::new();
+    let mut section: Option<String> = None;
+    let mut in_comment = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let trimmed = line.trim();
+
+        // Skip HTML comment blocks: <!-- ... -->
+        if !in_comment && trimmed.contains("<!--") {
+            in_comment = true;
+        }
+        if in_comment {
+            if trimmed.contains("-->") {
+                in_comment = false;
+            }
+            continue;
+        }
+
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            let name = heading.trim().to_lowercase();
+            if !SECTIONS.contains(&name.as_str()) {
+                issues.push(ConfigIssue {
+                    line: line_no,
+                    label: Some(name.clone()),
+                    severity: Severity::Error,
+                    message: format!(
+                        "unknown section '{name}' (expected one of: {})",
+                        SECTIONS.join(", ")
+                    ),
+                });
+            }
+            section = Some(name);
+            continue;
+        }
+
+        let Some(item) = trimmed.strip_prefix("- ") else {
+            continue;
+        };
+        let problem = match (section.as_deref(), item.split_once(':')) {
+            (None, _) => Some((
+                Severity::Warning,
+                format!("'{trimmed}' is not under a '# section' heading (ignored)"),
+            )),
+            (Some(_), None) => Some((
+                Severity::Error,
+                format!("expected '- key: value', found '{trimmed}'"),
+            )),
+            (Some(section), Some((key, value))) => {
+                set(&mut config, section, key.trim(), value.trim())
+            }
+        };
+        if let Some((severity, mut message)) = problem {
+            // Issues end up in the journal and bug reports
+            if let Some((key, value)) = item.split_once(':')
+                && SECRET_KEYS.contains(&key.trim())
+                && !value.trim().is_empty()
+            {
+                message = message.replace(&format!("'{}'", value.trim()), "'<redacted>'");
+            }
+            issues.push(ConfigIssue {
+                line: line_no,
+                label: section.clone(),
+                severity,
+                message,
+            });
+        }
+    }
+
+    ParsedHudConfig { config, issues }
+}
+
+const SECTIONS: &[&str] = &[
+    "markers",
+    "guides",
+    "layout",
+    "text",
+    "calendar",
+    "alerts",
+    "push",
+    "digest",
+    "demo",
+    "updates",
+    "attention",
+];
+
+/// Apply one entry of `section`. Entries of unknown sections are ignored
+/// (the heading already produced an error).
+fn set(config: &mut HudConfig, section: &str, key: &str, value: &str) -> Problem {
+    let known: &[&str] = match section {
+        "markers" => &["glyph", "size", "color", "count"],
+        "guides" => &["mode", "color"],
+        "layout" => &["mode", "width", "overflow", "max_lines"],
+        "text" => &["scale"],
+        "calendar" => &["path", "position", "lookahead"],
+        "alerts" => &["battery", "temperature"],
+        "push" => &[
+            "ntfy", "pushover", "telegram", "slack", "discord", "interval",
+        ],
+        "digest" => &["smtp", "user", "password_command", "from", "to", "time"],
+        "demo" => &["auto"],
+        "updates" => &["check"],
+        "attention" => &["focus", "timeout"],
+        _ => return None,
+    };
+    if !known.contains(&key) {
+        return Some((
+            Severity::Error,
+            format!(
+                "unknown key '{key}' in '{section}' (expected one of: {})",
+                known.join(", ")
+            ),
+        ));
+    }
+    if value.is_empty() {
+        return Some((Severity::Error, format!("'{key}' has no value")));
+    }
+    let lower = value.to_lowercase();
+    match (section, key) {
+        ("markers", "glyph") => config.markers.glyph = value.to_string(),
+        ("markers", "size") => {
+            let mut size = 0.0;
+            let problem = clamped(key, value, 4.0, 96.0, &mut size);
+            if !matches!(problem, Some((Severity::Error, _))) {
+                config.markers.size = Some(size);
+            }
+            return problem;
+        }
+        ("markers", "count") => {
+            config.markers.count = match lower.as_str() {
+                "0" | "none" => MarkerCount::None,
+                "2" => MarkerCount::Diagonal,
+                "4" => MarkerCount::Corners,
+                "8" => MarkerCount::Edges,
+                _ => return invalid(key, value, "0, 2, 4, 8"),
+            }
+        }
+        ("markers", "color") | ("guides", "color") => {
+            let Some(rgba) = parse_color(value) else {
+                return invalid(key, value, "#rrggbb or #rrggbbaa");
+            };
+            if section == "markers" {
+                config.markers.color = Some(rgba);
+            } else {
+                config.guides.color = Some(rgba);
+            }
+        }
+        ("guides", "mode") => {
+            config.guides.mode = match Guides::parse(&lower) {
+                Some(mode) => mode,
+                None => return invalid(key, value, "off, crosshair, thirds"),
+            }
+        }
+        ("layout", "mode") => {
+            config.layout.mode = match lower.as_str() {
+                "corners" => LayoutMode::Corners,
+                "left" => LayoutMode::Left,
+                "right" => LayoutMode::Right,
+                _ => return invalid(key, value, "corners, left, right"),
+            }
+        }
+        ("layout", "width") => {
+            return clamped(
+                key,
+                lower.trim_end_matches("px"),
+                160.0,
+                1600.0,
+                &mut config.layout.width,
+            );
+        }
+        ("layout", "overflow") => {
+            config.layout.overflow = match lower.as_str() {
+                "off" | "none" => Overflow::Off,
+                "shrink" => Overflow::Shrink,
+                "hide" => Overflow::Hide,
+                "scroll" => Overflow::Scroll,
+                _ => return invalid(key, value, "off, shrink, hide, scroll"),
+            }
+        }
+        ("layout", "max_lines") => {
+            return clamped(key, value, 4, 400, &mut config.layout.max_lines);
+        }
+        ("text", "scale") => {
+            return clamped(
+                key,
+                value,
+                TextConfig::MIN_SCALE,
+                TextConfig::MAX_SCALE,
+                &mut config.text.scale,
+            );
+        }
+        ("calendar", "path") => config.calendar.path = Some(expand_home(value)),
+        ("calendar", "position") => {
+            config.calendar.position = match Position::parse(&lower) {
+                Some(position) => position,
+                None => return invalid(key, value, Position::EXPECTED),
+            }
+        }
+        ("calendar", "lookahead") => {
+            let Some(secs) = parse_duration_secs(&lower) else {
+                return invalid(key, value, "a duration like 30m or 2h");
+            };
+            let limited = secs.clamp(60, 24 * 3600);
+            config.calendar.lookahead_secs = limited;
+            if limited != secs {
+                return Some((
+                    Severity::Warning,
+                    format!("{key} {secs}s out of range 60..=86400, using {limited}s"),
+                ));
+            }
+        }
+        ("alerts", "battery") => {
+            if lower == "off" {
+                config.alerts.battery_percent = None;
+                return None;
+            }
+            let mut percent = 0;
+            let problem = clamped(key, lower.trim_end_matches('%'), 1, 99, &mut percent);
+            if !matches!(problem, Some((Severity::Error, _))) {
+                config.alerts.battery_percent = Some(percent);
+            }
+            return problem;
+        }
+        ("alerts", "temperature") => {
+            if lower == "off" {
+                config.alerts.temperature_c = None;
+                return None;
+            }
+            let mut celsius = 0;
+            let problem = clamped(key, lower.trim_end_matches("°c"), 40, 120, &mut celsius);
+            if !matches!(problem, Some((Severity::Error, _))) {
+                config.alerts.temperature_c = Some(celsius);
+            }
+            return problem;
+        }
+        ("push", "ntfy") => {
+            let topic = value
+                .strip_prefix("https://")
+                .or_else(|| value.strip_prefix("http://"))
+                .and_then(|rest| rest.split_once('/'))
+                .map(|(_, topic)| topic);
+            if !topic.is_some_and(|t| !t.is_empty() && !t.contains('/')) {
+                return invalid(key, value, "a topic URL like https://ntfy.sh/my-topic");
+            }
+            config.push.ntfy = Some(value.to_string());
+        }
+        ("push", "slack") => {
+            if !value.starts_with("https://") {
+                return invalid(key, value, "an https:// webhook URL");
+            }
+            config.push.slack = Some(value.to_string());
+        }
+        ("push", "discord") => {
+            if !value.starts_with("https://") {
+                return invalid(key, value, "an https:// webhook URL");
+            }
+            config.push.discord = Some(value.to_string());
+        }
+        ("push", "pushover") | ("push", "telegram") => {
+            let parts: Vec<&str> = value.split_whitespace().collect();
+            let [first, second] = parts[..] else {
+                return invalid(
+                    key,
+                    value,
+                    if key == "pushover" {
+                        "<user key> <app token>"
+                    } else {
+                        "<bot token> <chat id>"
+                    },
+                );
+            };
+            let pair = Some((first.to_string(), second.to_string()));
+            if key == "pushover" {
+                config.push.pushover = pair;
+            } else {
+                config.push.telegram = pair;
+            }
+        }
+        ("push", "interval") => {
+            let Some(secs) = parse_duration_secs(&lower) else {
+                return invalid(key, value, "a duration like 30s or 5m");
+            };
+            config.push.interval_secs = secs;
+        }
+        ("digest", "smtp") => config.digest.smtp = Some(value.to_string()),
+        ("digest", "user") => config.digest.user = Some(value.to_string()),
+        ("digest", "password_command") => {
+            config.digest.password_command = Some(value.to_string());
+        }
+        ("digest", "from") | ("digest", "to") => {
+            if !value.contains('@') {
+                return invalid(key, value, "an email address");
+            }
+            if key == "from" {
+                config.digest.from = Some(value.to_string());
+            } else {
+                config.digest.to = Some(value.to_string());
+            }
+        }
+        ("digest", "time") => {
+            let minutes = value.split_once(':').and_then(|(h, m)| {
+                let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+                (h < 24 && m < 60).then_some(h * 60 + m)
+            });
+            let Some(minutes) = minutes else {
+                return invalid(key, value, "a time like 18:00");
+            };
+            config.digest.time_minutes = minutes;
+        }
+        ("demo", "auto") | ("updates", "check") | ("attention", "focus") => {
+            let on = match lower.as_str() {
+                "on" => true,
+                "off" => false,
+                _ => return invalid(key, value, "on, off"),
+            };
+            match section {
+                "demo" => config.demo.auto = on,
+                "updates" => config.updates.check = on,
+                _ => config.attention.focus = on,
+            }
+        }
+        ("attention", "timeout") => {
+            let Some(secs) = parse_duration_secs(&lower) else {
+                return invalid(key, value, "a duration like 30s or 2m");
+            };
+            let limited = secs.clamp(5, 3600);
+            config.attention.timeout_secs = limited;
+            if limited != secs {
+                return Some((
+                    Severity::Warning,
+                    format!("{key} {secs}s out of range 5..=3600, using {limited}s"),
+                ));
+            }
+        }
+        _ => unreachable!("key checked against the section's keys"),
+    }
+    None
+}
+
+/// Parse `#rrggbb` or `#rrggbbaa` into RGBA components (0.0–1.0).
+pub fn parse_color(value: &str) -> Option<[f32; 4]> {
+    let hex = value.strip_prefix('#')?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|h| u8::from_str_radix(h, 16).ok())
+            .map(|v| v as f32 / 255.0)
+    };
+    Some([
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        if hex.len() == 8 { channel(6)? } else { 1.0 },
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_markers_and_guides() {
+        let input = r#"
+# markers
+- glyph: ⌜
+- size: 20
+- color: #ff8800
+- count: 8
+
+# guides
+- mode: thirds
+- color: #ffffff40
+"#;
+        let parsed = check_hud_config(input);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        let cfg = parsed.config;
+        assert_eq!(cfg.markers.glyph, "⌜");
+        assert_eq!(cfg.markers.size, Some(20.0));
+        assert_eq!(cfg.markers.color, Some([1.0, 136.0 / 255.0, 0.0, 1.0]));
+        assert_eq!(cfg.markers.count, MarkerCount::Edges);
+        assert_eq!(cfg.guides.mode, Guides::Thirds);
+        assert_eq!(cfg.guides.color.map(|c| c[3]), Some(64.0 / 255.0));
+    }
+
+    #[test]
+    fn report_bad_entries() {
+        let input = "# markers\n- count: 3\n- colour: red\n- size: 200\n# panels\n- x: 1\n";
+        let parsed = check_hud_config(input);
+        let lines: Vec<(usize, Severity)> =
+            parsed.issues.iter().map(|i| (i.line, i.severity)).collect();
+        assert_eq!(
+            lines,
+            vec![
+                (2, Severity::Error),
+                (3, Severity::Error),
+                (4, Severity::Warning),
+                (5, Severity::Error),
+            ]
+        );
+        assert_eq!(parsed.config.markers.size, Some(96.0));
+        assert_eq!(parsed.config.markers.count, MarkerCount::Corners);
+    }
+
+    #[test]
+    fn parse_calendar_section() {
+        let input = "# calendar\n- path: /tmp/cal\n- position: bottom-left\n- lookahead: 2h\n";
+        let parsed = check_hud_config(input);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(
+            parsed.config.calendar,
+            CalendarConfig {
+                path: Some(PathBuf::from("/tmp/cal")),
+                position: Position::BottomLeft,
+                lookahead_secs: 7200,
+            }
+        );
+
+        let parsed = check_hud_config("# calendar\n- lookahead: 3d\n- position: middle\n");
+        assert_eq!(parsed.issues.len(), 2);
+        assert_eq!(parsed.config.calendar, CalendarConfig::default());
+    }
+
+    #[test]
+    fn side_layout_caps_columns() {
+        let parsed = check_hud_config("# layout\n- mode: right\n- width: 300px\n");
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        let layout = parsed.config.layout;
+        assert_eq!(layout.mode, LayoutMode::Right);
+        // 300 / 6 = 50 cells, minus the indent
+        assert_eq!(layout.fit_cols(80, 6.0, None), 48);
+        assert_eq!(layout.fit_cols(30, 6.0, Some(1000.0)), 30);
+
+        let corners = LayoutConfig::default();
+        assert_eq!(corners.fit_cols(80, 6.0, None), 80);
+        assert_eq!(corners.fit_cols(80, 6.0, Some(600.0)), 80);
+        assert_eq!(corners.fit_cols(80, 6.0, Some(240.0)), 38);
+
+        let parsed = check_hud_config("# layout\n- mode: top\n- width: 20\n");
+        assert_eq!(parsed.issues.len(), 2);
+        assert_eq!(parsed.config.layout.width, 160.0);
+    }
+
+    #[test]
+    fn overflow_strategies() {
+        let mut layout = LayoutConfig {
+            max_lines: 12,
+            ..LayoutConfig::default()
+        };
+        // 3 labels + 14 rows = 17 lines
+        let wanted = [8, 4, 2];
+        assert_eq!(
+            layout.fit_lines(&wanted, 0),
+            vec![Some(8), Some(4), Some(2)]
+        );
+
+        layout.overflow = Overflow::Hide;
+        assert_eq!(layout.fit_lines(&wanted, 0), vec![Some(8), None, None]);
+        assert_eq!(layout.fit_lines(&[20, 1], 0), vec![Some(11), None]);
+
+        layout.overflow = Overflow::Shrink;
+        assert_eq!(
+            layout.fit_lines(&wanted, 0),
+            vec![Some(3), Some(3), Some(2)]
+        );
+        assert_eq!(layout.fit_lines(&[3, 3], 0), vec![Some(3), Some(3)]);
+
+        layout.overflow = Overflow::Scroll;
+        assert_eq!(layout.fit_lines(&wanted, 0), vec![Some(8), None, None]);
+        assert_eq!(layout.fit_lines(&wanted, 1), vec![None, Some(4), Some(2)]);
+        assert_eq!(layout.fit_lines(&wanted, 2), vec![Some(8), None, None]);
+        assert_eq!(layout.fit_lines(&[20, 1], 0), vec![Some(11), None]);
+        assert_eq!(layout.fit_lines(&[20, 1], 1), vec![None, Some(1)]);
+        assert_eq!(layout.fit_lines(&[3, 3], 1), vec![Some(3), Some(3)]);
+
+        let parsed = check_hud_config("# layout\n- overflow: wrap\n- max_lines: 1\n");
+        assert_eq!(parsed.issues.len(), 2);
+        assert_eq!(parsed.config.layout.overflow, Overflow::Off);
+        assert_eq!(parsed.config.layout.max_lines, 4);
+    }
+
+    #[test]
+    fn set_entry_replaces_or_adds() {
+        let content = "# markers\n- size: 20\n\n# text\n- scale: 1.0\n\n# guides\n- mode: off\n";
+        let updated = set_entry(content, "text", "scale", "1.2");
+        assert_eq!(updated, content.replace("scale: 1.0", "scale: 1.2"));
+        assert_eq!(check_hud_config(&updated).config.text.scale, 1.2);
+
+        // Missing key goes after the section's last entry
+        let updated = set_entry(
+            "# markers\n- size: 20\n\n# guides\n",
+            "markers",
+            "glyph",
+            "+",
+        );
+        assert_eq!(updated, "# markers\n- size: 20\n- glyph: +\n\n# guides\n");
+
+        // Commented-out sections don't count
+        let updated = set_entry("<!--\n# text\n- scale: 2\n-->\n", "text", "scale", "0.8");
+        assert_eq!(
+            updated,
+            "<!--\n# text\n- scale: 2\n-->\n\n# text\n- scale: 0.8\n"
+        );
+        assert_eq!(
+            set_entry("", "text", "scale", "1.1"),
+            "# text\n- scale: 1.1\n"
+        );
+    }
+
+    #[test]
+    fn parse_alerts_section() {
+        let parsed = check_hud_config("# alerts\n- battery: 20%\n- temperature: off\n");
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(
+            parsed.config.alerts,
+            AlertsConfig {
+                battery_percent: Some(20),
+                temperature_c: None,
+            }
+        );
+
+        let parsed = check_hud_config("# alerts\n- battery: low\n- temperature: 300\n");
+        let severities: Vec<Severity> = parsed.issues.iter().map(|i| i.severity).collect();
+        assert_eq!(severities, vec![Severity::Error, Severity::Warning]);
+        assert_eq!(parsed.config.alerts.battery_percent, Some(10));
+        assert_eq!(parsed.config.alerts.temperature_c, Some(120));
+    }
+
+    #[test]
+    fn parse_push_section() {
+        let parsed = check_hud_config(
+            "# push\n- ntfy: https://ntfy.sh/dev\n- telegram: 123:abc 42\n- interval: 5m\n",
+        );
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(
+            parsed.config.push,
+            PushConfig {
+                ntfy: Some("https://ntfy.sh/dev".to_string()),
+                pushover: None,
+                telegram: Some(("123:abc".to_string(), "42".to_string())),
+                interval_secs: 300,
+                ..PushConfig::default()
+            }
+        );
+
+        let parsed = check_hud_config(
+            "# push\n- ntfy: ntfy.sh/dev\n- pushover: only-a-user-key\n- slack: hooks.slack.com/x\n",
+        );
+        assert_eq!(parsed.issues.len(), 3);
+        assert_eq!(parsed.config.push, PushConfig::default());
+    }
+
+    #[test]
+    fn parse_digest_section() {
+        let parsed = check_hud_config(
+            "# digest\n- smtp: mail.example.com:587\n- to: me@example.com\n- time: 7:30\n",
+        );
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert!(parsed.config.digest.enabled());
+        assert_eq!(parsed.config.digest.time_minutes, 7 * 60 + 30);
+
+        let parsed = check_hud_config("# digest\n- to: me\n- time: 25:00\n");
+        assert_eq!(parsed.issues.len(), 2);
+        assert_eq!(parsed.config.digest, DigestConfig::default());
+    }
+
+    #[test]
+    fn parse_demo_updates_and_attention_sections() {
+        assert!(check_hud_config("").config.demo.auto);
+        let parsed = check_hud_config("# demo\n- auto: off\n");
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert!(!parsed.config.demo.auto);
+        assert_eq!(check_hud_config("# demo\n- auto: maybe\n").issues.len(), 1);
+        assert!(!check_hud_config("").config.updates.check);
+        assert!(
+            check_hud_config("# updates\n- check: on\n")
+                .config
+                .updates
+                .check
+        );
+        let parsed = check_hud_config("# attention\n- focus: on\n- timeout: 2m\n");
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(
+            parsed.config.attention,
+            AttentionConfig {
+                focus: true,
+                timeout_secs: 120
+            }
+        );
+        let parsed = check_hud_config("# attention\n- timeout: 1s\n");
+        assert_eq!(parsed.issues[0].severity, Severity::Warning);
+        assert_eq!(parsed.config.attention.timeout_secs, 5);
+    }
+
+    #[test]
+    fn parse_color_forms() {
+        assert_eq!(parse_color("#000000"), Some([0.0, 0.0, 0.0, 1.0]));
+        assert_eq!(parse_color("#ffffff00"), Some([1.0, 1.0, 1.0, 0.0]));
+        assert_eq!(parse_color("ffffff"), None);
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let parsed = check_hud_config(EXAMPLE_HUD_CONFIG);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(
+            parsed.config.markers,
+            MarkerConfig {
+                size: Some(16.0),
+                ..MarkerConfig::default()
+            }
+        );
+    }
+}
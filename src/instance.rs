@@ -0,0 +1,152 @@
+//! Named instances (`dev-hud --instance work`), so a second HUD can run
+//! next to the default one without sharing its socket, config or state.
+//!
+//! The name is set once at startup; everything below falls back to the
+//! default paths when it isn't.
+
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+static NAME: OnceLock<String> = OnceLock::new();
+
+/// Environment fallback for `--instance`, e.g. in a unit file.
+pub const ENV_VAR: &str = "DEV_HUD_INSTANCE";
+
+/// Check an instance name: it becomes part of file and D-Bus names, so
+/// only ASCII letters, digits, `-` and `_`, starting with a letter.
+pub fn validate(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("instance {name:?}: must start with a letter"));
+    }
+    if name.len() > 32 {
+        return Err(format!("instance {name:?}: longer than 32 characters"));
+    }
+    if let Some(c) = chars.find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_') {
+        return Err(format!("instance {name:?}: invalid character {c:?}"));
+    }
+    Ok(())
+}
+
+/// Set this process's instance. Only the first call has any effect.
+pub fn set(name: &str) -> Result<(), String> {
+    validate(name)?;
+    let _ = NAME.set(name.to_string());
+    Ok(())
+}
+
+/// Take `--instance <name>` / `--instance=<name>` out of `args`, falling
+/// back to `$DEV_HUD_INSTANCE`, and set it.
+pub fn init_from_args(args: &mut Vec<String>) -> Result<(), String> {
+    let name = match take_flag(args)? {
+        Some(name) => Some(name),
+        None => std::env::var(ENV_VAR).ok().filter(|s| !s.is_empty()),
+    };
+    match name {
+        Some(name) => set(&name),
+        None => Ok(()),
+    }
+}
+
+fn take_flag(args: &mut Vec<String>) -> Result<Option<String>, String> {
+    let Some(i) = args
+        .iter()
+        .position(|a| a == "--instance" || a.starts_with("--instance="))
+    else {
+        return Ok(None);
+    };
+    let flag = args.remove(i);
+    match flag.strip_prefix("--instance=") {
+        Some(name) => Ok(Some(name.to_string())),
+        None if i < args.len() => Ok(Some(args.remove(i))),
+        None => Err("--instance needs a name".to_string()),
+    }
+}
+
+/// The instance name, or None for the default instance.
+pub fn name() -> Option<&'static str> {
+    NAME.get().map(String::as_str)
+}
+
+/// `base` for the default instance, `base-<name>` otherwise.
+pub fn suffixed(base: &str) -> String {
+    suffixed_for(name(), base, "-")
+}
+
+fn suffixed_for(name: Option<&str>, base: &str, sep: &str) -> String {
+    match name {
+        Some(name) => format!("{base}{sep}{name}"),
+        None => base.to_string(),
+    }
+}
+
+/// IPC socket: `$XDG_RUNTIME_DIR/dev-hud.sock` or `dev-hud-<name>.sock`.
+pub fn socket_path() -> PathBuf {
+    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(runtime_dir).join(format!("{}.sock", suffixed("dev-hud")))
+}
+
+/// D-Bus well-known name: `org.fuabioo.DevHud` or `org.fuabioo.DevHud.<name>`.
+pub fn bus_name() -> String {
+    suffixed_for(name(), "org.fuabioo.DevHud", ".")
+}
+
+/// Where `shells.md` and `hud.md` live: `~/.config/viz`, or a `<name>`
+/// subdirectory of it.
+pub fn config_dir() -> PathBuf {
+    home_join(name(), ".config/viz")
+}
+
+/// Runtime state such as the default FIFO: `~/.cache/dev-hud[/<name>]`.
+pub fn state_dir() -> PathBuf {
+    home_join(name(), ".cache/dev-hud")
+}
+
+fn home_join(name: Option<&str>, dir: &str) -> PathBuf {
+    let dir = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join(dir);
+    match name {
+        Some(name) => dir.join(name),
+        None => dir,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn names_and_flags() {
+        assert!(validate("work").is_ok());
+        assert!(validate("test_2-b").is_ok());
+        for bad in ["", "2nd", "a.b", "a/b", "ünï", &"x".repeat(33)] {
+            assert!(validate(bad).is_err(), "{bad:?}");
+        }
+
+        let mut a = args(&["--instance", "work", "config", "check"]);
+        assert_eq!(take_flag(&mut a), Ok(Some("work".to_string())));
+        assert_eq!(a, args(&["config", "check"]));
+        let mut a = args(&["toggle", "--instance=work"]);
+        assert_eq!(take_flag(&mut a), Ok(Some("work".to_string())));
+        assert_eq!(a, args(&["toggle"]));
+        assert!(take_flag(&mut args(&["--instance"])).is_err());
+        assert_eq!(take_flag(&mut args(&["toggle"])), Ok(None));
+    }
+
+    #[test]
+    fn namespaced_paths() {
+        assert_eq!(suffixed_for(None, "dev-hud", "-"), "dev-hud");
+        assert_eq!(suffixed_for(Some("work"), "dev-hud", "-"), "dev-hud-work");
+        assert_eq!(
+            suffixed_for(Some("work"), "org.fuabioo.DevHud", "."),
+            "org.fuabioo.DevHud.work"
+        );
+        assert!(home_join(None, ".config/viz").ends_with(".config/viz"));
+        assert!(home_join(Some("work"), ".config/viz").ends_with(".config/viz/work"));
+    }
+}
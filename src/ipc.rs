@@ -1,23 +1,45 @@
 use std::io::BufRead;
 use std::os::unix::net::UnixListener;
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use futures::channel::mpsc;
 
 use crate::app::Message;
+use crate::calendar;
+use crate::dbus;
+use crate::digest;
+use crate::focus;
+use crate::highlight::Highlight;
+use crate::hooks;
+use crate::hud_config::{self, AlertsConfig, CalendarConfig, DigestConfig, Guides};
+use crate::instance;
+use crate::power;
+use crate::rules;
 use crate::shell;
-use crate::theme::ThemeMode;
+use crate::theme::{self, ThemeMode};
+use crate::util;
 
-pub(crate) fn socket_path() -> PathBuf {
-    let runtime_dir = std::env::var("XDG_RUNTIME_DIR").unwrap_or_else(|_| "/tmp".to_string());
-    PathBuf::from(runtime_dir).join("dev-hud.sock")
-}
+/// Wallpaper daemons animate the switch (swww's default transition is
+/// about a second); sample once the new image is fully shown.
+const WALLPAPER_SETTLE: Duration = Duration::from_millis(1500);
+
+/// How often wallpaper daemons without change events are queried.
+const WALLPAPER_POLL: Duration = Duration::from_secs(3);
+
+/// Releases are rare; one check a day is plenty.
+const UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How long `peek` lasts without a duration.
+const PEEK_DURATION: Duration = Duration::from_secs(5);
+
+/// Longest `peek <secs>`.
+const MAX_PEEK_SECS: f32 = 600.0;
 
 pub(crate) fn socket_listener() -> impl futures::Stream<Item = Message> {
     let (tx, rx) = mpsc::unbounded();
     std::thread::spawn(move || {
-        let path = socket_path();
+        let path = instance::socket_path();
         let _ = std::fs::remove_file(&path);
         let listener = match UnixListener::bind(&path) {
             Ok(l) => l,
@@ -30,32 +52,13 @@ pub(crate) fn socket_listener() -> impl futures::Stream<Item = Message> {
         for stream in listener.incoming().flatten() {
             let mut buf = String::new();
             if std::io::BufReader::new(stream).read_line(&mut buf).is_ok() {
-                let msg = match buf.trim() {
-                    "toggle" => Some(Message::ToggleVisibility),
-                    "focus" => Some(Message::ToggleFocus),
-                    "demo loader-toggle" => Some(Message::DemoLoaderToggle),
-                    "demo loader-change" => Some(Message::DemoLoaderChange),
-                    "demo font-change" => Some(Message::FontChange),
-                    "theme dark" => Some(Message::ThemeSet(ThemeMode::Dark)),
-                    "theme light" => Some(Message::ThemeSet(ThemeMode::Light)),
-                    "theme auto" => Some(Message::ThemeSet(ThemeMode::Auto)),
-                    "theme adaptive" => Some(Message::ThemeSet(ThemeMode::Adaptive)),
-                    "theme-toggle" => Some(Message::ThemeToggle),
-                    "bg-toggle" => Some(Message::BackdropToggle),
-                    "shell-toggle" => Some(Message::ShellToggle),
-                    "screen" => Some(Message::ScreenCycle),
-                    cmd if cmd.starts_with("screen ") => {
-                        Some(Message::ScreenSet(cmd[7..].trim().to_string()))
+                match parse_command(buf.trim()) {
+                    Ok(msg) => {
+                        if tx.unbounded_send(msg).is_err() {
+                            break;
+                        }
                     }
-                    other => {
-                        eprintln!("[dev-hud] unknown command: {other:?}");
-                        None
-                    }
-                };
-                if let Some(msg) = msg
-                    && tx.unbounded_send(msg).is_err()
-                {
-                    break;
+                    Err(e) => eprintln!("[dev-hud] {e}"),
                 }
             }
         }
@@ -63,6 +66,72 @@ pub(crate) fn socket_listener() -> impl futures::Stream<Item = Message> {
     rx
 }
 
+/// Serve the D-Bus interface; calls arrive as the same messages as socket
+/// commands. Logs and yields nothing when there is no session bus.
+pub(crate) fn dbus_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || match dbus::serve(tx) {
+        Ok(()) => eprintln!("[dev-hud] dbus: serving {}", instance::bus_name()),
+        Err(e) => eprintln!("[dev-hud] dbus: {e}"),
+    });
+    rx
+}
+
+/// Parse one IPC command line (shared by the socket and D-Bus `Command`).
+pub(crate) fn parse_command(cmd: &str) -> Result<Message, String> {
+    match cmd {
+        "toggle" => Ok(Message::ToggleVisibility),
+        "focus" => Ok(Message::ToggleFocus),
+        "peek" => Ok(Message::Peek(PEEK_DURATION)),
+        cmd if cmd.starts_with("peek ") => peek_duration(&cmd[5..]).map(Message::Peek),
+        "demo loader-toggle" => Ok(Message::DemoLoaderToggle),
+        "demo loader-change" => Ok(Message::DemoLoaderChange),
+        "demo font-change" => Ok(Message::FontChange),
+        "demo dismiss" => Ok(Message::DemoDismiss),
+        "theme dark" => Ok(Message::ThemeSet(ThemeMode::Dark)),
+        "theme light" => Ok(Message::ThemeSet(ThemeMode::Light)),
+        "theme auto" => Ok(Message::ThemeSet(ThemeMode::Auto)),
+        "theme adaptive" => Ok(Message::ThemeSet(ThemeMode::Adaptive)),
+        "theme-toggle" => Ok(Message::ThemeToggle),
+        "bg-toggle" => Ok(Message::BackdropToggle),
+        "shell-toggle" => Ok(Message::ShellToggle),
+        "minimal" => Ok(Message::MinimalToggle),
+        "ui text bigger" => Ok(Message::TextScale(1)),
+        "ui text smaller" => Ok(Message::TextScale(-1)),
+        "ui text reset" => Ok(Message::TextScale(0)),
+        "screen" => Ok(Message::ScreenCycle),
+        "screen follow" => Ok(Message::ScreenFollowToggle),
+        cmd if cmd.starts_with("screen ") => Ok(Message::ScreenSet(cmd[7..].trim().to_string())),
+        "guides" => Ok(Message::GuidesCycle),
+        cmd if cmd.starts_with("guides ") => Guides::parse(cmd[7..].trim())
+            .map(Message::GuidesSet)
+            .ok_or_else(|| format!("unknown guides mode: {:?}", &cmd[7..])),
+        "highlight off" => Ok(Message::HighlightClear),
+        "digest" => Ok(Message::DigestDue),
+        "update check" => Ok(Message::UpdateCheck),
+        "tour" => Ok(Message::TourStart),
+        cmd if cmd.starts_with("snapshot ") => {
+            Ok(Message::Snapshot(PathBuf::from(cmd[9..].trim())))
+        }
+        "tour next" => Ok(Message::TourNext),
+        "tour end" => Ok(Message::TourEnd),
+        cmd if cmd.starts_with("highlight ") => Highlight::parse(&cmd[10..])
+            .map(Message::Highlight)
+            .map_err(|e| format!("highlight: {e}")),
+        other => Err(format!("unknown command: {other:?}")),
+    }
+}
+
+fn peek_duration(secs: &str) -> Result<Duration, String> {
+    match secs.trim().parse::<f32>() {
+        Ok(s) if s.is_finite() && s > 0.0 => Ok(Duration::from_secs_f32(s.min(MAX_PEEK_SECS))),
+        _ => Err(format!(
+            "peek: invalid secs {:?} (expected a number > 0)",
+            secs.trim()
+        )),
+    }
+}
+
 pub(crate) fn tick_stream(ms: &u64) -> mpsc::UnboundedReceiver<Message> {
     let ms = *ms;
     let (tx, rx) = mpsc::unbounded();
@@ -77,6 +146,34 @@ pub(crate) fn tick_stream(ms: &u64) -> mpsc::UnboundedReceiver<Message> {
     rx
 }
 
+pub(crate) fn theme_frame_stream(ms: &u64) -> mpsc::UnboundedReceiver<Message> {
+    let ms = *ms;
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(Duration::from_millis(ms));
+            if tx.unbounded_send(Message::ThemeFrame).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+pub(crate) fn watchdog_stream(interval: &Duration) -> mpsc::UnboundedReceiver<Message> {
+    let interval = *interval;
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        loop {
+            std::thread::sleep(interval);
+            if tx.unbounded_send(Message::WatchdogPing).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
 pub(crate) fn theme_refresh_stream() -> impl futures::Stream<Item = Message> {
     let (tx, rx) = mpsc::unbounded();
     std::thread::spawn(move || {
@@ -90,9 +187,250 @@ pub(crate) fn theme_refresh_stream() -> impl futures::Stream<Item = Message> {
     rx
 }
 
+/// Send a `ThemeRefresh` once a wallpaper change has settled, so adaptive
+/// mode reacts without waiting for the 5s refresh. Sources with change
+/// events are watched; the rest are polled every `WALLPAPER_POLL`. Exits
+/// quietly when no source is available, and once the subscription ends.
+pub(crate) fn wallpaper_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let sources = theme::WallpaperSource::detect();
+        if sources.is_empty() {
+            return;
+        }
+        let (changed_tx, changed_rx) = std::sync::mpsc::channel();
+        let mut watches = Vec::new();
+        let mut polled = Vec::new();
+        for source in sources {
+            match source.watch(changed_tx.clone()) {
+                Some(watch) => watches.push((source.name(), watch)),
+                None => polled.push(source),
+            }
+        }
+        let mut names: Vec<String> = watches.iter().map(|(name, _)| name.clone()).collect();
+        names.extend(polled.iter().map(|s| format!("{} (polled)", s.name())));
+        eprintln!(
+            "[dev-hud] adaptive: watching wallpaper via {}",
+            names.join(", ")
+        );
+
+        let state = || -> Vec<Option<String>> { polled.iter().map(|s| s.query()).collect() };
+        let mut last = state();
+        let mut next_poll = Instant::now() + WALLPAPER_POLL;
+        // Dropping `watches` on the way out stops the watchers
+        while !tx.is_closed() {
+            let mut changed = changed_rx.recv_timeout(Duration::from_secs(1)).is_ok();
+            if !polled.is_empty() && Instant::now() >= next_poll {
+                next_poll = Instant::now() + WALLPAPER_POLL;
+                let current = state();
+                changed |= current != last;
+                last = current;
+            }
+            if !changed {
+                continue;
+            }
+            // Let the daemon's transition animation finish before sampling
+            std::thread::sleep(WALLPAPER_SETTLE);
+            while changed_rx.try_recv().is_ok() {}
+            if tx.unbounded_send(Message::ThemeRefresh).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Poll `hud.md` for changes (same 2s cadence as the shell config) and
+/// send the re-parsed config. The initial load happens in `Hud::new`.
+pub(crate) fn hud_config_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let path = hud_config::hud_config_path();
+        let mtime = || std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let mut last_mtime = mtime();
+        loop {
+            std::thread::sleep(Duration::from_secs(2));
+            let current = mtime();
+            if current == last_mtime {
+                continue;
+            }
+            last_mtime = current;
+            if tx
+                .unbounded_send(Message::HudConfigReloaded(Box::new(hud_config::load())))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Poll `rules.md` like `hud.md` and send the re-parsed rules.
+pub(crate) fn rules_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let path = rules::rules_path();
+        let mtime = || std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let mut last_mtime = mtime();
+        loop {
+            std::thread::sleep(Duration::from_secs(2));
+            let current = mtime();
+            if current == last_mtime {
+                continue;
+            }
+            last_mtime = current;
+            if tx
+                .unbounded_send(Message::RulesReloaded(rules::load()))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Poll `hooks.rhai` and send its source when it changes.
+pub(crate) fn hooks_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let path = hooks::hooks_path();
+        let mtime = || std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+        let mut last_mtime = mtime();
+        loop {
+            std::thread::sleep(Duration::from_secs(2));
+            let current = mtime();
+            if current == last_mtime {
+                continue;
+            }
+            last_mtime = current;
+            if tx
+                .unbounded_send(Message::HooksChanged(hooks::read_source()))
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+/// Send the next calendar event every 15s so the countdown stays current.
+/// Files are re-read every minute to pick up synced changes.
+pub(crate) fn calendar_stream(config: &CalendarConfig) -> mpsc::UnboundedReceiver<Message> {
+    let config = config.clone();
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let Some(path) = config.path else {
+            return;
+        };
+        let mut events = Vec::new();
+        let mut last_load: Option<std::time::Instant> = None;
+        loop {
+            if last_load.is_none_or(|t| t.elapsed() >= Duration::from_secs(60)) {
+                let count = events.len();
+                events = calendar::load(&path);
+                if last_load.is_none() || events.len() != count {
+                    eprintln!(
+                        "[dev-hud] calendar: {} event(s) from {path:?}",
+                        events.len()
+                    );
+                }
+                last_load = Some(std::time::Instant::now());
+            }
+            let next =
+                calendar::next_event(&events, util::unix_now(), config.lookahead_secs as i64);
+            if tx.unbounded_send(Message::CalendarUpdated(next)).is_err() {
+                break;
+            }
+            std::thread::sleep(Duration::from_secs(15));
+        }
+    });
+    rx
+}
+
+/// Check battery and thermal zones every 10s, sending the crossed
+/// thresholds whenever they change.
+pub(crate) fn alerts_stream(config: &AlertsConfig) -> mpsc::UnboundedReceiver<Message> {
+    let config = config.clone();
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut last = Vec::new();
+        loop {
+            let alerts = power::check(&config, &power::read());
+            if alerts != last {
+                last = alerts.clone();
+                if tx.unbounded_send(Message::AlertsUpdated(alerts)).is_err() {
+                    break;
+                }
+            }
+            std::thread::sleep(Duration::from_secs(10));
+        }
+    });
+    rx
+}
+
+/// Send `DigestDue` once a day at the digest's configured local time.
+/// Starting after that time skips today.
+pub(crate) fn digest_stream(config: &DigestConfig) -> mpsc::UnboundedReceiver<Message> {
+    let config = config.clone();
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let (today, minute) = util::local_day_minute(std::time::SystemTime::now());
+        let mut last_day = if minute >= config.time_minutes {
+            today
+        } else {
+            today - 1
+        };
+        loop {
+            let now = util::local_day_minute(std::time::SystemTime::now());
+            if digest::is_due(&config, now, last_day) {
+                last_day = now.0;
+                if tx.unbounded_send(Message::DigestDue).is_err() {
+                    break;
+                }
+            }
+            std::thread::sleep(Duration::from_secs(30));
+        }
+    });
+    rx
+}
+
+/// Send the output that has keyboard focus whenever it changes.
+pub(crate) fn focused_output_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        let mut last: Option<String> = None;
+        let result = focus::watch(|output| {
+            if last.as_ref() == Some(&output) {
+                return true;
+            }
+            last = Some(output.clone());
+            tx.unbounded_send(Message::FocusedOutput(output)).is_ok()
+        });
+        match result {
+            Ok(()) => eprintln!("[dev-hud] screen follow: compositor stopped sending events"),
+            Err(e) => eprintln!("[dev-hud] screen follow: {e}"),
+        }
+    });
+    rx
+}
+
+/// Send `UpdateCheck` on startup and then once a day (`# updates`).
+pub(crate) fn update_stream() -> impl futures::Stream<Item = Message> {
+    let (tx, rx) = mpsc::unbounded();
+    std::thread::spawn(move || {
+        while tx.unbounded_send(Message::UpdateCheck).is_ok() {
+            std::thread::sleep(UPDATE_CHECK_INTERVAL);
+        }
+    });
+    rx
+}
+
 // --- Shell subscription bridge ---
 
-pub(crate) fn shell_event_stream() -> impl futures::Stream<Item = Message> {
+pub(crate) fn shell_event_stream(demo: &bool) -> impl futures::Stream<Item = Message> + use<> {
     use futures::StreamExt;
-    shell::shell_stream().map(Message::ShellEvent)
+    shell::shell_stream(*demo).map(Message::ShellEvent)
 }
@@ -0,0 +1,45 @@
+//! GUI-free building blocks of dev-hud, usable from other tools (TUI
+//! viewers, bots, status bars) without pulling in the overlay.
+//!
+//! - [`shell`]: shell widget config (`~/.config/viz/shells.md`) parsing and
+//!   validation, and the process supervisor that runs widgets and streams
+//!   their output as [`shell::ShellEvent`]s.
+//! - [`shell::jsonl`]: JSONL line selection and formatting used by
+//!   `format: jsonl` widgets.
+//! - [`hud_config`]: HUD settings (`~/.config/viz/hud.md`).
+//! - [`instance`]: `--instance` names and the socket/config/state paths
+//!   they namespace.
+//! - [`hooks`]: Rhai scripting hooks on widget output, exits and alerts.
+//! - [`digest`]: daily email digest of attention texts, alerts and widget
+//!   failures over SMTP.
+//! - [`calendar`]: `.ics` parsing and next-meeting countdown.
+//! - [`power`]: battery and thermal readings from sysfs, checked against
+//!   alert thresholds.
+//! - [`push`]: phone push notifications (ntfy, Pushover, Telegram) and
+//!   Slack/Discord webhooks, with per-backend rate limiting.
+//! - [`rules`]: `rules.md` parsing and matching of widget output, exits and
+//!   alerts against user-defined rules.
+//! - [`update`]: GitHub release check and version comparison.
+//! - [`util`]: display-width aware truncation, ANSI stripping, time helpers.
+//!
+//! ```
+//! use dev_hud::shell::config;
+//!
+//! let parsed = config::check_config("# uptime\n- command: uptime\n- mode: oneshot\n");
+//! assert!(parsed.issues.is_empty());
+//! assert_eq!(parsed.configs[0].label, "uptime");
+//! ```
+//!
+//! The API follows the binary's needs and is not yet semver-stable.
+
+pub mod calendar;
+pub mod digest;
+pub mod hooks;
+pub mod hud_config;
+pub mod instance;
+pub mod power;
+pub mod push;
+pub mod rules;
+pub mod shell;
+pub mod update;
+pub mod util;
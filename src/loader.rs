@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use iced::Font;
 use iced::widget::{image as iced_image, svg};
 use image::AnimationDecoder;
@@ -79,37 +81,68 @@ impl LoaderStyle {
     }
 }
 
+/// Decoded GIF frames and generated SVG frames for the image styles.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LoaderAssets {
+    pub(crate) gif_frames: Vec<iced_image::Handle>,
+    pub(crate) svg_frames: Vec<svg::Handle>,
+}
+
+/// Prepared once per process; handles are cheap to clone.
+static ASSETS: OnceLock<LoaderAssets> = OnceLock::new();
+
+/// The loader assets, preparing them on first use. Decoding the GIF takes
+/// long enough to drop frames, so call this off the UI thread.
+pub(crate) fn loader_assets() -> LoaderAssets {
+    ASSETS
+        .get_or_init(|| {
+            let assets = LoaderAssets {
+                gif_frames: decode_gif_frames(),
+                svg_frames: generate_svg_frames(SVG_FRAME_COUNT),
+            };
+            eprintln!(
+                "[dev-hud] loader assets: {} gif frames, {} svg frames",
+                assets.gif_frames.len(),
+                assets.svg_frames.len()
+            );
+            assets
+        })
+        .clone()
+}
+
 pub(crate) struct DemoLoader {
     pub(crate) style: LoaderStyle,
     pub(crate) frame: usize,
-    pub(crate) gif_frames: Vec<iced_image::Handle>,
-    pub(crate) svg_frames: Vec<svg::Handle>,
+    /// None until `loader_assets` is done; the image styles spin as text
+    /// meanwhile.
+    pub(crate) assets: Option<LoaderAssets>,
 }
 
 impl DemoLoader {
     pub(crate) fn new() -> Self {
-        let gif_frames = decode_gif_frames();
-        let svg_frames = generate_svg_frames(SVG_FRAME_COUNT);
-        eprintln!(
-            "[dev-hud] loader assets: {} gif frames, {} svg frames",
-            gif_frames.len(),
-            svg_frames.len()
-        );
         Self {
             style: LoaderStyle::Braille,
             frame: 0,
-            gif_frames,
-            svg_frames,
+            assets: ASSETS.get().cloned(),
         }
     }
 
-    fn frame_count(&self) -> usize {
+    /// Text frames for the current style, or the braille spinner while an
+    /// image style's assets are loading.
+    pub(crate) fn text_frames(&self) -> &'static [&'static str] {
         match self.style {
-            LoaderStyle::Braille | LoaderStyle::Bounce | LoaderStyle::Pipe => {
-                self.style.text_frames().len()
+            LoaderStyle::Gif | LoaderStyle::Svg if self.assets.is_none() => {
+                LoaderStyle::Braille.text_frames()
             }
-            LoaderStyle::Gif => self.gif_frames.len().max(1),
-            LoaderStyle::Svg => self.svg_frames.len().max(1),
+            style => style.text_frames(),
+        }
+    }
+
+    fn frame_count(&self) -> usize {
+        match (self.style, &self.assets) {
+            (LoaderStyle::Gif, Some(assets)) => assets.gif_frames.len().max(1),
+            (LoaderStyle::Svg, Some(assets)) => assets.svg_frames.len().max(1),
+            _ => self.text_frames().len(),
         }
     }
 
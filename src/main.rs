@@ -1,12 +1,36 @@
 mod app;
+mod bugreport;
+mod cli;
+mod dbus;
+mod focus;
+mod highlight;
 mod ipc;
 mod loader;
-mod shell;
+mod measure;
+mod screencopy;
+mod snapshot;
 mod surface;
+mod systemd;
 mod theme;
-mod util;
+mod tour;
 mod views;
 
+// GUI-free subsystems live in the library crate (src/lib.rs)
+use dev_hud::{
+    calendar, digest, hooks, hud_config, instance, power, push, rules, shell, update, util,
+};
+
 fn main() -> Result<(), iced_layershell::Error> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = instance::init_from_args(&mut args) {
+        eprintln!("{e}");
+        std::process::exit(2);
+    }
+    match args.first().map(String::as_str) {
+        Some("config") => std::process::exit(cli::config_command(&args[1..])),
+        Some("bugreport") => std::process::exit(bugreport::bugreport_command(&args[1..])),
+        Some("--install-service") => std::process::exit(systemd::install_service()),
+        _ => {}
+    }
     app::run()
 }
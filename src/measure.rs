@@ -0,0 +1,38 @@
+//! Text measurement with the renderer's font system, so widths can be
+//! capped in pixels rather than guessed from character counts.
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+use iced::advanced::graphics::text::Paragraph;
+use iced::advanced::text::{self, Paragraph as _};
+use iced::{Font, Pixels, Size};
+
+/// Measured over several glyphs to average out rounding.
+const SAMPLE: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+static CELL_WIDTHS: LazyLock<Mutex<HashMap<(Font, u32), f32>>> = LazyLock::new(Default::default);
+
+/// Advance of one terminal cell in `font` at `size`, measured once per
+/// font and size. Widths of whole lines follow from their display width
+/// (wide characters take two cells).
+pub(crate) fn cell_width(font: Font, size: f32) -> f32 {
+    let key = (font, size.to_bits());
+    let mut cache = CELL_WIDTHS.lock().unwrap_or_else(|e| e.into_inner());
+    *cache.entry(key).or_insert_with(|| {
+        let paragraph = Paragraph::with_text(text::Text {
+            content: SAMPLE,
+            bounds: Size::INFINITE,
+            size: Pixels(size),
+            line_height: text::LineHeight::default(),
+            font,
+            align_x: text::Alignment::Default,
+            align_y: iced::alignment::Vertical::Top,
+            shaping: text::Shaping::Advanced,
+            wrapping: text::Wrapping::None,
+        });
+        let measured = paragraph.min_width() / SAMPLE.len() as f32;
+        // Typical monospace advance if the font system returned nothing
+        if measured > 0.0 { measured } else { size * 0.6 }
+    })
+}
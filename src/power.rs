@@ -0,0 +1,172 @@
+//! Battery and thermal readings from sysfs, checked against the `# alerts`
+//! thresholds in `hud.md`.
+
+use std::path::Path;
+
+use crate::hud_config::AlertsConfig;
+
+/// Zones reporting above this are sensor glitches, not heat.
+const MAX_PLAUSIBLE_CELSIUS: f32 = 150.0;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Readings {
+    /// Lowest charge among discharging system batteries, in percent.
+    pub battery: Option<u8>,
+    /// Hottest thermal zone: its `type` and temperature in °C.
+    pub hottest: Option<(String, f32)>,
+}
+
+/// A crossed threshold, shown in the HUD's warning band.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Alert {
+    LowBattery { percent: u8 },
+    Hot { zone: String, celsius: i32 },
+}
+
+impl Alert {
+    pub fn text(&self) -> String {
+        match self {
+            Alert::LowBattery { percent } => format!("battery {percent}%"),
+            Alert::Hot { zone, celsius } => format!("{zone} {celsius}°C"),
+        }
+    }
+}
+
+/// Read the live system state.
+pub fn read() -> Readings {
+    read_from(Path::new("/sys/class"))
+}
+
+/// Read `power_supply` and `thermal` under a sysfs `class` directory.
+/// Missing or unreadable entries are skipped.
+pub fn read_from(sys_class: &Path) -> Readings {
+    let read = |path: &Path| std::fs::read_to_string(path).ok();
+    let entries = |dir: &str| -> Vec<std::path::PathBuf> {
+        let mut paths: Vec<_> = std::fs::read_dir(sys_class.join(dir))
+            .map(|d| d.flatten().map(|e| e.path()).collect())
+            .unwrap_or_default();
+        paths.sort();
+        paths
+    };
+
+    let battery = entries("power_supply")
+        .iter()
+        .filter(|p| read(&p.join("type")).is_some_and(|t| t.trim() == "Battery"))
+        // Wireless mice and keyboards report scope "Device"
+        .filter(|p| read(&p.join("scope")).is_none_or(|s| s.trim() != "Device"))
+        .filter(|p| read(&p.join("status")).is_some_and(|s| s.trim() == "Discharging"))
+        .filter_map(|p| read(&p.join("capacity"))?.trim().parse::<u8>().ok())
+        .min();
+
+    let hottest = entries("thermal")
+        .iter()
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with("thermal_zone"))
+        })
+        .filter_map(|p| {
+            let millis: i64 = read(&p.join("temp"))?.trim().parse().ok()?;
+            let celsius = millis as f32 / 1000.0;
+            if celsius <= 0.0 || celsius > MAX_PLAUSIBLE_CELSIUS {
+                return None;
+            }
+            let zone = read(&p.join("type"))
+                .map(|t| t.trim().to_string())
+                .unwrap_or_else(|| "thermal".to_string());
+            Some((zone, celsius))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+
+    Readings { battery, hottest }
+}
+
+/// Alerts for the readings that cross `config`'s thresholds.
+pub fn check(config: &AlertsConfig, readings: &Readings) -> Vec<Alert> {
+    let mut alerts = Vec::new();
+    if let (Some(threshold), Some(percent)) = (config.battery_percent, readings.battery)
+        && percent <= threshold
+    {
+        alerts.push(Alert::LowBattery { percent });
+    }
+    if let (Some(threshold), Some((zone, celsius))) = (config.temperature_c, &readings.hottest)
+        && *celsius >= threshold as f32
+    {
+        alerts.push(Alert::Hot {
+            zone: zone.clone(),
+            celsius: celsius.round() as i32,
+        });
+    }
+    alerts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, file: &str, contents: &str) {
+        let path = root.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn read_sysfs_tree() {
+        let root = std::env::temp_dir().join(format!("dev-hud-power-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for (dir, kind, scope, status, capacity) in [
+            ("BAT0", "Battery", "", "Discharging", "42"),
+            ("BAT1", "Battery", "", "Discharging", "17"),
+            ("hidpp_battery_0", "Battery", "Device", "Discharging", "3"),
+            ("AC", "Mains", "", "", ""),
+        ] {
+            let base = format!("power_supply/{dir}");
+            write(&root, &format!("{base}/type"), kind);
+            write(&root, &format!("{base}/status"), status);
+            write(&root, &format!("{base}/capacity"), capacity);
+            if !scope.is_empty() {
+                write(&root, &format!("{base}/scope"), scope);
+            }
+        }
+        write(&root, "thermal/thermal_zone0/type", "acpitz\n");
+        write(&root, "thermal/thermal_zone0/temp", "48000\n");
+        write(&root, "thermal/thermal_zone1/type", "x86_pkg_temp\n");
+        write(&root, "thermal/thermal_zone1/temp", "91500\n");
+        write(&root, "thermal/thermal_zone2/temp", "255000\n");
+        write(&root, "thermal/cooling_device0/temp", "99000\n");
+
+        let readings = read_from(&root);
+        assert_eq!(readings.battery, Some(17));
+        assert_eq!(readings.hottest, Some(("x86_pkg_temp".to_string(), 91.5)));
+
+        write(&root, "power_supply/BAT1/status", "Charging");
+        assert_eq!(read_from(&root).battery, Some(42));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn thresholds() {
+        let readings = Readings {
+            battery: Some(15),
+            hottest: Some(("acpitz".to_string(), 89.6)),
+        };
+        let config = AlertsConfig {
+            battery_percent: Some(15),
+            temperature_c: Some(90),
+        };
+        assert_eq!(
+            check(&config, &readings),
+            vec![Alert::LowBattery { percent: 15 }]
+        );
+        let config = AlertsConfig {
+            battery_percent: None,
+            temperature_c: Some(85),
+        };
+        let alerts = check(&config, &readings);
+        assert_eq!(
+            alerts.iter().map(Alert::text).collect::<Vec<_>>(),
+            vec!["acpitz 90°C"]
+        );
+        assert!(check(&config, &Readings::default()).is_empty());
+    }
+}
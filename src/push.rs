@@ -0,0 +1,258 @@
+//! Push notifications for attention events to a phone (ntfy, Pushover,
+//! Telegram) or a team chat (Slack and Discord incoming webhooks),
+//! configured in the `# push` section of `hud.md`.
+//!
+//! Requests are made by `curl` on a background thread. The request,
+//! tokens included, is passed on curl's stdin (`--config -`) so it never
+//! shows up in `ps`. Each backend sends at most one push per `interval`;
+//! pushes in between are dropped.
+
+use std::collections::HashMap;
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde_json::json;
+
+use crate::hud_config::PushConfig;
+
+/// Give up on a request after this long.
+const TIMEOUT_SECS: u32 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Ntfy,
+    Pushover,
+    Telegram,
+    Slack,
+    Discord,
+}
+
+impl Backend {
+    pub fn name(self) -> &'static str {
+        match self {
+            Backend::Ntfy => "ntfy",
+            Backend::Pushover => "pushover",
+            Backend::Telegram => "telegram",
+            Backend::Slack => "slack",
+            Backend::Discord => "discord",
+        }
+    }
+}
+
+/// Backends that have credentials in `config`.
+pub fn backends(config: &PushConfig) -> Vec<Backend> {
+    let mut out = Vec::new();
+    if config.ntfy.is_some() {
+        out.push(Backend::Ntfy);
+    }
+    if config.pushover.is_some() {
+        out.push(Backend::Pushover);
+    }
+    if config.telegram.is_some() {
+        out.push(Backend::Telegram);
+    }
+    if config.slack.is_some() {
+        out.push(Backend::Slack);
+    }
+    if config.discord.is_some() {
+        out.push(Backend::Discord);
+    }
+    out
+}
+
+/// Rate-limits and sends pushes.
+#[derive(Debug, Default)]
+pub struct Pusher {
+    last_sent: HashMap<Backend, Instant>,
+}
+
+impl Pusher {
+    /// Push `title`/`body` to every configured backend that isn't inside
+    /// its rate-limit interval.
+    pub fn push(&mut self, config: &PushConfig, title: &str, body: &str, now: Instant) {
+        for backend in self.due(config, now) {
+            if let Some(request) = curl_config(config, backend, title, body) {
+                send(backend, request);
+            }
+        }
+    }
+
+    /// Backends to push to at `now`, marking them as sent.
+    fn due(&mut self, config: &PushConfig, now: Instant) -> Vec<Backend> {
+        let interval = Duration::from_secs(config.interval_secs);
+        let mut due = Vec::new();
+        for backend in backends(config) {
+            match self.last_sent.get(&backend) {
+                Some(&last) if now.saturating_duration_since(last) < interval => {
+                    eprintln!("[dev-hud] push {}: rate-limited, dropped", backend.name());
+                }
+                _ => {
+                    self.last_sent.insert(backend, now);
+                    due.push(backend);
+                }
+            }
+        }
+        due
+    }
+}
+
+/// The curl config (`--config` syntax) for one push, or None if `backend`
+/// isn't configured.
+fn curl_config(config: &PushConfig, backend: Backend, title: &str, body: &str) -> Option<String> {
+    let mut options: Vec<(&str, String)> = Vec::new();
+    match backend {
+        Backend::Ntfy => {
+            // JSON publishing goes to the server root, with the topic inside
+            let (server, topic) = config.ntfy.as_deref()?.rsplit_once('/')?;
+            options.push(("url", server.to_string()));
+            options.push(("header", "Content-Type: application/json".to_string()));
+            let payload = json!({"topic": topic, "title": title, "message": body});
+            options.push(("data-raw", payload.to_string()));
+        }
+        Backend::Pushover => {
+            let (user, token) = config.pushover.as_ref()?;
+            options.push((
+                "url",
+                "https://api.pushover.net/1/messages.json".to_string(),
+            ));
+            options.push(("data-urlencode", format!("token={token}")));
+            options.push(("data-urlencode", format!("user={user}")));
+            options.push(("data-urlencode", format!("title={title}")));
+            options.push(("data-urlencode", format!("message={body}")));
+        }
+        Backend::Telegram => {
+            let (token, chat) = config.telegram.as_ref()?;
+            options.push((
+                "url",
+                format!("https://api.telegram.org/bot{token}/sendMessage"),
+            ));
+            options.push(("header", "Content-Type: application/json".to_string()));
+            let payload = json!({"chat_id": chat, "text": format!("{title}\n{body}")});
+            options.push(("data-raw", payload.to_string()));
+        }
+        Backend::Slack => {
+            options.push(("url", config.slack.clone()?));
+            options.push(("header", "Content-Type: application/json".to_string()));
+            let payload = json!({"text": format!("*{title}* {body}")});
+            options.push(("data-raw", payload.to_string()));
+        }
+        Backend::Discord => {
+            options.push(("url", config.discord.clone()?));
+            options.push(("header", "Content-Type: application/json".to_string()));
+            let payload = json!({"content": format!("**{title}** {body}"), "username": title});
+            options.push(("data-raw", payload.to_string()));
+        }
+    }
+    Some(
+        options
+            .iter()
+            .map(|(key, value)| format!("{key} = \"{}\"\n", quote(value)))
+            .collect(),
+    )
+}
+
+/// Escape a value for a double-quoted curl config string.
+fn quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Run curl with `request` on its stdin, off the calling thread.
+fn send(backend: Backend, request: String) {
+    std::thread::spawn(move || {
+        let name = backend.name();
+        let child = Command::new("curl")
+            .args([
+                "--silent",
+                "--show-error",
+                "--fail",
+                "--output",
+                "/dev/null",
+            ])
+            .args(["--max-time", &TIMEOUT_SECS.to_string(), "--config", "-"])
+            .stdin(Stdio::piped())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("[dev-hud] push {name}: curl: {e}");
+                return;
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take()
+            && let Err(e) = stdin.write_all(request.as_bytes())
+        {
+            eprintln!("[dev-hud] push {name}: {e}");
+        }
+        match child.wait() {
+            Ok(status) if status.success() => eprintln!("[dev-hud] push {name}: sent"),
+            Ok(status) => eprintln!("[dev-hud] push {name}: failed ({status})"),
+            Err(e) => eprintln!("[dev-hud] push {name}: {e}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> PushConfig {
+        PushConfig {
+            ntfy: Some("https://ntfy.sh/dev".to_string()),
+            pushover: None,
+            telegram: Some(("123:abc".to_string(), "42".to_string())),
+            slack: Some("https://hooks.slack.com/services/T/B/x".to_string()),
+            ..PushConfig::default()
+        }
+    }
+
+    #[test]
+    fn requests() {
+        let config = config();
+        let ntfy = curl_config(&config, Backend::Ntfy, "dev-hud", "build \"failed\"").unwrap();
+        let lines: Vec<&str> = ntfy.lines().collect();
+        assert_eq!(lines[0], "url = \"https://ntfy.sh\"");
+        assert_eq!(lines[1], "header = \"Content-Type: application/json\"");
+        assert!(lines[2].starts_with("data-raw = \"{"));
+        assert!(lines[2].contains(r#"\"topic\":\"dev\""#));
+        assert!(lines[2].contains(r#"\"message\":\"build \\\"failed\\\"\""#));
+        let telegram = curl_config(&config, Backend::Telegram, "t", "b").unwrap();
+        assert!(telegram.starts_with("url = \"https://api.telegram.org/bot123:abc/sendMessage\""));
+        assert!(curl_config(&config, Backend::Pushover, "t", "b").is_none());
+        let slack = curl_config(&config, Backend::Slack, "dev-hud", "ci: exit 1").unwrap();
+        assert!(slack.ends_with("data-raw = \"{\\\"text\\\":\\\"*dev-hud* ci: exit 1\\\"}\"\n"));
+        assert_eq!(quote("a\nb\\"), "a\\nb\\\\");
+    }
+
+    #[test]
+    fn rate_limited_per_backend() {
+        let config = config();
+        let mut pusher = Pusher::default();
+        let start = Instant::now();
+        assert_eq!(
+            pusher.due(&config, start),
+            vec![Backend::Ntfy, Backend::Telegram, Backend::Slack]
+        );
+        assert!(
+            pusher
+                .due(&config, start + Duration::from_secs(59))
+                .is_empty()
+        );
+        assert_eq!(
+            pusher.due(&config, start + Duration::from_secs(60)).len(),
+            3
+        );
+        assert!(pusher.due(&PushConfig::default(), start).is_empty());
+    }
+}
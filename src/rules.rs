@@ -0,0 +1,506 @@
+//! User-defined rules (`~/.config/viz/rules.md`): match widget output,
+//! exits, spawn errors and alerts, then notify, run a command, flag the
+//! HUD for attention or hide/show widgets.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::shell::config::{ConfigIssue, Problem, Severity, invalid, parse_duration_secs};
+
+/// Minimum time between two firings of a rule, unless it sets `cooldown`.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// What happened; a rule's `on:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Trigger {
+    /// A line of widget output.
+    #[default]
+    Line,
+    /// A widget process exited (text: `exit 1`, `signal Killed`).
+    Exit,
+    /// A widget failed to spawn.
+    Error,
+    /// A battery/thermal alert was raised.
+    Alert,
+}
+
+impl Trigger {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "line" | "output" => Some(Trigger::Line),
+            "exit" => Some(Trigger::Exit),
+            "error" => Some(Trigger::Error),
+            "alert" => Some(Trigger::Alert),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Desktop notification via `notify-send`.
+    Notify,
+    /// `sh -c` the rule's `run` command.
+    Run,
+    /// Show the text in the HUD's warning band for a while.
+    Attention,
+    /// Hide the target widget.
+    Hide,
+    /// Show a widget hidden by a rule again.
+    Show,
+}
+
+impl Action {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "notify" => Some(Action::Notify),
+            "run" => Some(Action::Run),
+            "attention" => Some(Action::Attention),
+            "hide" => Some(Action::Hide),
+            "show" => Some(Action::Show),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    /// The `# heading`.
+    pub name: String,
+    pub on: Trigger,
+    /// Only events from this widget label (any widget if unset).
+    pub widget: Option<String>,
+    /// Glob (`*`, `?`) matched anywhere in the event text.
+    pub pattern: Option<String>,
+    pub actions: Vec<Action>,
+    /// Command for `Action::Run`.
+    pub run: Option<String>,
+    /// Widget for hide/show; defaults to the event's widget.
+    pub target: Option<String>,
+    pub cooldown: Duration,
+}
+
+impl Rule {
+    fn new(name: &str) -> Self {
+        Rule {
+            name: name.to_string(),
+            on: Trigger::default(),
+            widget: None,
+            pattern: None,
+            actions: Vec::new(),
+            run: None,
+            target: None,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    pub fn matches(&self, event: &Event) -> bool {
+        self.on == event.trigger
+            && self
+                .widget
+                .as_deref()
+                .is_none_or(|w| event.widget == Some(w))
+            && self
+                .pattern
+                .as_deref()
+                .is_none_or(|p| glob_find(p, event.text))
+    }
+
+    /// The widget hide/show act on for `event`.
+    pub fn target<'a>(&'a self, event: &Event<'a>) -> Option<&'a str> {
+        self.target.as_deref().or(event.widget)
+    }
+}
+
+/// Something rules can match.
+#[derive(Debug, Clone, Copy)]
+pub struct Event<'a> {
+    pub trigger: Trigger,
+    pub widget: Option<&'a str>,
+    pub text: &'a str,
+}
+
+/// Loaded rules plus when each last fired, for cooldowns.
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+    last_fired: HashMap<String, Instant>,
+}
+
+impl Rules {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Rules {
+            rules,
+            last_fired: HashMap::new(),
+        }
+    }
+
+    /// Replace the rules, keeping cooldowns of rules that still exist.
+    pub fn replace(&mut self, rules: Vec<Rule>) {
+        self.last_fired
+            .retain(|name, _| rules.iter().any(|r| r.name == *name));
+        self.rules = rules;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Rules matching `event` that are out of their cooldown; marks them fired.
+    pub fn fire(&mut self, event: &Event, now: Instant) -> Vec<Rule> {
+        let mut fired = Vec::new();
+        for rule in self.rules.iter().filter(|r| r.matches(event)) {
+            let cooled = self
+                .last_fired
+                .get(&rule.name)
+                .is_none_or(|&at| now.duration_since(at) >= rule.cooldown);
+            if cooled {
+                self.last_fired.insert(rule.name.clone(), now);
+                fired.push(rule.clone());
+            }
+        }
+        fired
+    }
+}
+
+/// Text of an `exit` event: `exit <code>`, or `signal <name>` when killed.
+pub fn exit_text(exit_code: Option<i32>, signal: Option<&str>) -> String {
+    match (exit_code, signal) {
+        (_, Some(signal)) => format!("signal {signal}"),
+        (Some(code), None) => format!("exit {code}"),
+        (None, None) => "exit".to_string(),
+    }
+}
+
+/// Return the path to the rules file.
+pub fn rules_path() -> PathBuf {
+    crate::instance::config_dir().join("rules.md")
+}
+
+pub struct ParsedRules {
+    pub rules: Vec<Rule>,
+    pub issues: Vec<ConfigIssue>,
+}
+
+/// Load the rules, or none if the file is missing. Problems are logged.
+pub fn load() -> Vec<Rule> {
+    let Ok(content) = std::fs::read_to_string(rules_path()) else {
+        return Vec::new();
+    };
+    let parsed = check_rules(&content);
+    for issue in &parsed.issues {
+        let kind = match issue.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        eprintln!("[dev-hud] rules.md: {kind}: {issue}");
+    }
+    parsed.rules
+}
+
+/// Commented starter config written by `dev-hud config init`.
+pub const EXAMPLE_RULES: &str = r#"<!--
+Rules for dev-hud (hot-reloaded; no restart needed).
+
+Each `# heading` is a rule: when an event matches, its actions run.
+  on:       line (widget output) | exit | error (failed to spawn) | alert
+  widget:   only events from this widget label
+  match:    glob on the text, anywhere in it (* and ? wildcards)
+  action:   notify, run, attention, hide, show (comma separated)
+  run:      command for `run`; gets $DEV_HUD_RULE, $DEV_HUD_WIDGET, $DEV_HUD_TEXT
+  target:   widget for hide/show (default: the event's widget)
+  cooldown: minimum time between firings (default 10s)
+Run `dev-hud config check` after editing to validate this file.
+-->
+
+# build-errors
+- on: line
+- match: error[E*]
+- action: attention, notify
+- cooldown: 30s
+
+<!--
+# deploy-done
+- on: exit
+- widget: deploy
+- match: exit 0
+- action: run, hide
+- run: paplay /usr/share/sounds/freedesktop/stereo/complete.oga
+-->
+"#;
+
+/// Parse `rules.md`, reporting unknown keys, bad values and rules without
+/// actions by line.
+pub fn check_rules(content: &str) -> ParsedRules {
+    let mut rules: Vec<Rule> = Vec::new();
+    // Heading line of each rule, for errors about the rule as a whole
+    let mut heading_lines: Vec<usize> = Vec::new();
+    let mut issues = Vec::new();
+    let mut in_comment = false;
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
+        let trimmed = line.trim();
+
+        // Skip HTML comment blocks: <!-- ... -->
+        if !in_comment && trimmed.contains("<!--") {
+            in_comment = true;
+        }
+        if in_comment {
+            if trimmed.contains("-->") {
+                in_comment = false;
+            }
+            continue;
+        }
+
+        if let Some(heading) = trimmed.strip_prefix("# ") {
+            let name = heading.trim().to_string();
+            if rules.iter().any(|r| r.name == name) {
+                issues.push(ConfigIssue {
+                    line: line_no,
+                    label: Some(name.clone()),
+                    severity: Severity::Warning,
+                    message: format!("duplicate rule '{name}' (cooldowns are shared)"),
+                });
+            }
+            rules.push(Rule::new(&name));
+            heading_lines.push(line_no);
+            continue;
+        }
+
+        let Some(item) = trimmed.strip_prefix("- ") else {
+            continue;
+        };
+        let problem = match (rules.last_mut(), item.split_once(':')) {
+            (None, _) => Some((
+                Severity::Warning,
+                format!("'{trimmed}' is not under a '# rule' heading (ignored)"),
+            )),
+            (Some(_), None) => Some((
+                Severity::Error,
+                format!("expected '- key: value', found '{trimmed}'"),
+            )),
+            (Some(rule), Some((key, value))) => set(rule, key.trim(), value.trim()),
+        };
+        if let Some((severity, message)) = problem {
+            issues.push(ConfigIssue {
+                line: line_no,
+                label: rules.last().map(|r| r.name.clone()),
+                severity,
+                message,
+            });
+        }
+    }
+
+    // Drop rules that can't do anything
+    let mut kept = Vec::new();
+    for (rule, line) in rules.into_iter().zip(heading_lines) {
+        let problem = if rule.actions.is_empty() {
+            Some("has no action (ignored)")
+        } else if rule.actions.contains(&Action::Run) && rule.run.is_none() {
+            Some("uses 'run' without a 'run' command (ignored)")
+        } else {
+            None
+        };
+        match problem {
+            Some(message) => issues.push(ConfigIssue {
+                line,
+                label: Some(rule.name.clone()),
+                severity: Severity::Error,
+                message: format!("rule '{}' {message}", rule.name),
+            }),
+            None => kept.push(rule),
+        }
+    }
+    issues.sort_by_key(|i| i.line);
+
+    ParsedRules {
+        rules: kept,
+        issues,
+    }
+}
+
+const KEYS: &[&str] = &[
+    "on", "widget", "match", "action", "run", "target", "cooldown",
+];
+
+fn set(rule: &mut Rule, key: &str, value: &str) -> Problem {
+    if !KEYS.contains(&key) {
+        return Some((
+            Severity::Error,
+            format!("unknown key '{key}' (expected one of: {})", KEYS.join(", ")),
+        ));
+    }
+    if value.is_empty() {
+        return Some((Severity::Error, format!("'{key}' has no value")));
+    }
+    match key {
+        "on" => match Trigger::parse(&value.to_lowercase()) {
+            Some(on) => rule.on = on,
+            None => return invalid(key, value, "line, exit, error, alert"),
+        },
+        "widget" => rule.widget = Some(value.to_string()),
+        "match" => rule.pattern = Some(value.to_string()),
+        "action" => {
+            for name in value.split(',').map(|a| a.trim().to_lowercase()) {
+                match Action::parse(&name) {
+                    Some(action) if !rule.actions.contains(&action) => rule.actions.push(action),
+                    Some(_) => {}
+                    None => {
+                        return invalid(key, &name, "notify, run, attention, hide, show");
+                    }
+                }
+            }
+        }
+        "run" => rule.run = Some(value.to_string()),
+        "target" => rule.target = Some(value.to_string()),
+        "cooldown" => match parse_duration_secs(value) {
+            Some(secs) => rule.cooldown = Duration::from_secs(secs),
+            None => return invalid(key, value, "a duration like 30s or 5m"),
+        },
+        _ => unreachable!("key checked against KEYS"),
+    }
+    None
+}
+
+/// True if glob `pattern` (`*` any run, `?` one character) matches some
+/// part of `text`.
+pub fn glob_find(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // Unanchored: allow anything before and after
+    (0..=text.len()).any(|start| glob_prefix(&pattern, &text[start..]))
+}
+
+/// True if `pattern` matches a prefix of `text`.
+fn glob_prefix(pattern: &[char], text: &[char]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some(('*', rest)) => (0..=text.len()).any(|skip| glob_prefix(rest, &text[skip..])),
+        Some((&p, rest)) => match text.split_first() {
+            Some((&t, text)) if p == '?' || p == t => glob_prefix(rest, text),
+            _ => false,
+        },
+    }
+}
+
+/// Run `command` with `sh -c` in the background with extra environment
+/// variables (for rules: the rule, widget and text). The child is reaped on
+/// its own thread.
+pub fn spawn(command: &str, env: &[(&str, &str)]) {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.args(["-c", command])
+        .envs(env.iter().copied())
+        .stdin(std::process::Stdio::null());
+    reap(cmd, command);
+}
+
+/// Desktop notification via `notify-send`.
+pub fn notify(title: &str, body: &str) {
+    let mut cmd = std::process::Command::new("notify-send");
+    cmd.args(["--app-name=dev-hud", title, body]);
+    reap(cmd, "notify-send");
+}
+
+fn reap(mut cmd: std::process::Command, what: &str) {
+    match cmd.spawn() {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => eprintln!("[dev-hud] {what}: {e}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_matches_anywhere() {
+        assert!(glob_find(
+            "error[E*]",
+            "src/main.rs: error[E0308]: mismatched"
+        ));
+        assert!(glob_find("exit ?", "exit 1"));
+        assert!(!glob_find("exit ?", "exit "));
+        assert!(glob_find("", "anything"));
+        assert!(glob_find("a*c", "xxabbbcxx"));
+        assert!(!glob_find("a*c", "xxabbbxx"));
+        assert!(glob_find("→*✓", "build → ok ✓"));
+    }
+
+    #[test]
+    fn parse_rules() {
+        let input = "\
+# build-errors
+- on: line
+- widget: cargo
+- match: error*
+- action: attention, notify
+- cooldown: 30s
+
+# bell
+- on: alert
+- action: run
+- run: paplay bell.oga
+
+# broken
+- on: tuesday
+- action: run
+- colour: red
+";
+        let parsed = check_rules(input);
+        assert_eq!(parsed.rules.len(), 2);
+        let build = &parsed.rules[0];
+        assert_eq!(build.on, Trigger::Line);
+        assert_eq!(build.widget.as_deref(), Some("cargo"));
+        assert_eq!(build.actions, vec![Action::Attention, Action::Notify]);
+        assert_eq!(build.cooldown, Duration::from_secs(30));
+        assert_eq!(parsed.rules[1].run.as_deref(), Some("paplay bell.oga"));
+
+        let lines: Vec<usize> = parsed.issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec![13, 14, 16]);
+        assert!(parsed.issues[0].message.contains("without a 'run' command"));
+        assert!(parsed.issues[1].message.contains("'tuesday'"));
+        assert!(parsed.issues[2].message.contains("'colour'"));
+        assert!(check_rules(EXAMPLE_RULES).issues.is_empty());
+    }
+
+    #[test]
+    fn fire_respects_widget_and_cooldown() {
+        let mut rules = Rules::new(
+            check_rules(
+                "# err\n- widget: cargo\n- match: error\n- action: attention\n- cooldown: 5s\n",
+            )
+            .rules,
+        );
+        let event = Event {
+            trigger: Trigger::Line,
+            widget: Some("cargo"),
+            text: "error: oops",
+        };
+        let other = Event {
+            widget: Some("tests"),
+            ..event
+        };
+        let start = Instant::now();
+        assert!(rules.fire(&other, start).is_empty());
+        assert_eq!(rules.fire(&event, start).len(), 1);
+        assert!(
+            rules
+                .fire(&event, start + Duration::from_secs(4))
+                .is_empty()
+        );
+        assert_eq!(rules.fire(&event, start + Duration::from_secs(5)).len(), 1);
+        let exit = Event {
+            trigger: Trigger::Exit,
+            ..event
+        };
+        assert!(
+            rules
+                .fire(&exit, start + Duration::from_secs(60))
+                .is_empty()
+        );
+    }
+}
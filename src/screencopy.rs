@@ -0,0 +1,322 @@
+//! Screen sampling through the wlr-screencopy protocol.
+//!
+//! Adaptive theme mode needs the brightness behind the HUD. Instead of
+//! spawning `grim` for a full screenshot, this copies a handful of small
+//! patches from the target output into shared memory and averages them.
+//! Works on wlroots compositors (sway, Hyprland, river, wayfire, ...);
+//! returns `None` elsewhere so the caller can fall back to external tools.
+
+use std::fs::File;
+use std::os::fd::{AsFd, FromRawFd, OwnedFd};
+use std::os::unix::fs::FileExt;
+
+use wayland_client::protocol::{wl_buffer, wl_output, wl_registry, wl_shm, wl_shm_pool};
+use wayland_client::{Connection, Dispatch, EventQueue, QueueHandle, WEnum, delegate_noop};
+use wayland_protocols_wlr::screencopy::v1::client::{
+    zwlr_screencopy_frame_v1::{self, ZwlrScreencopyFrameV1},
+    zwlr_screencopy_manager_v1::ZwlrScreencopyManagerV1,
+};
+
+/// Side of each sampled square, in logical pixels.
+const PATCH: i32 = 48;
+
+/// Patches per axis across the sampled quadrant (GRID × GRID captures).
+const GRID: i32 = 3;
+
+/// Only every Nth pixel of a patch is read, in both directions.
+const PIXEL_STRIDE: usize = 4;
+
+struct OutputInfo {
+    output: wl_output::WlOutput,
+    name: Option<String>,
+    width: i32,
+    height: i32,
+    scale: i32,
+}
+
+/// Buffer parameters announced for the current frame, and its outcome.
+#[derive(Default)]
+struct FrameState {
+    format: Option<wl_shm::Format>,
+    width: u32,
+    height: u32,
+    stride: u32,
+    ready: bool,
+    failed: bool,
+}
+
+#[derive(Default)]
+struct State {
+    shm: Option<wl_shm::WlShm>,
+    manager: Option<ZwlrScreencopyManagerV1>,
+    outputs: Vec<OutputInfo>,
+    frame: FrameState,
+}
+
+/// Average perceptual luminance (0.0–1.0) of the bottom-left quadrant of
+/// `output` (or the first output), where HUD widgets usually render.
+pub(crate) fn sample_luminance(output: Option<&str>) -> Option<f32> {
+    let conn = Connection::connect_to_env().ok()?;
+    let mut queue = conn.new_event_queue();
+    let qh = queue.handle();
+    let mut state = State::default();
+
+    conn.display().get_registry(&qh, ());
+    // First roundtrip binds globals, the second delivers wl_output properties
+    queue.roundtrip(&mut state).ok()?;
+    queue.roundtrip(&mut state).ok()?;
+
+    let manager = state.manager.clone()?;
+    let shm = state.shm.clone()?;
+    let target = output
+        .and_then(|name| {
+            state
+                .outputs
+                .iter()
+                .position(|o| o.name.as_deref() == Some(name))
+        })
+        .unwrap_or(0);
+    let info = state.outputs.get(target)?;
+    let (wl_output, scale) = (info.output.clone(), info.scale.max(1));
+    let (width, height) = (info.width / scale, info.height / scale);
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    // Patches centered in a GRID × GRID layout over the bottom-left quadrant
+    let (cell_w, cell_h) = (width / 2 / GRID, height / 2 / GRID);
+    let mut total = 0.0f64;
+    let mut count = 0usize;
+    for row in 0..GRID {
+        for col in 0..GRID {
+            let x = col * cell_w + (cell_w - PATCH).max(0) / 2;
+            let y = height / 2 + row * cell_h + (cell_h - PATCH).max(0) / 2;
+            let frame = manager.capture_output_region(
+                0,
+                &wl_output,
+                x,
+                y,
+                PATCH.min(cell_w),
+                PATCH.min(cell_h),
+                &qh,
+                (),
+            );
+            let patch = capture(&mut queue, &mut state, &shm, &frame, &qh);
+            frame.destroy();
+            let (sum, n) = patch?;
+            total += sum;
+            count += n;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+    let lum = (total / count as f64) as f32;
+    eprintln!(
+        "[dev-hud] adaptive: luminance = {lum:.3} ({count} samples via screencopy, {} patches)",
+        GRID * GRID
+    );
+    Some(lum)
+}
+
+/// Copy one frame into a shared-memory buffer and return the summed
+/// luminance of its sampled pixels with their count.
+fn capture(
+    queue: &mut EventQueue<State>,
+    state: &mut State,
+    shm: &wl_shm::WlShm,
+    frame: &ZwlrScreencopyFrameV1,
+    qh: &QueueHandle<State>,
+) -> Option<(f64, usize)> {
+    state.frame = FrameState::default();
+    // Buffer parameters are sent as soon as the frame is created
+    queue.roundtrip(state).ok()?;
+    let format = state.frame.format?;
+    let (width, height, stride) = (state.frame.width, state.frame.height, state.frame.stride);
+    let size = stride as usize * height as usize;
+    if size == 0 {
+        return None;
+    }
+
+    let file = shm_file(size)?;
+    let pool = shm.create_pool(file.as_fd(), size as i32, qh, ());
+    let buffer = pool.create_buffer(
+        0,
+        width as i32,
+        height as i32,
+        stride as i32,
+        format,
+        qh,
+        (),
+    );
+    frame.copy(&buffer);
+    while !state.frame.ready && !state.frame.failed {
+        if queue.blocking_dispatch(state).is_err() {
+            break;
+        }
+    }
+    buffer.destroy();
+    pool.destroy();
+    if !state.frame.ready {
+        return None;
+    }
+
+    let mut pixels = vec![0u8; size];
+    file.read_exact_at(&mut pixels, 0).ok()?;
+    Some(patch_luminance(&pixels, format, width, height, stride))
+}
+
+/// Create an anonymous shared-memory file of `size` bytes.
+fn shm_file(size: usize) -> Option<File> {
+    // SAFETY: the name is a valid C string; the returned fd is checked and
+    // immediately owned.
+    let fd = unsafe { libc::memfd_create(c"dev-hud-screencopy".as_ptr(), libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return None;
+    }
+    // SAFETY: fd is a freshly created descriptor nobody else owns.
+    let file = File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+    file.set_len(size as u64).ok()?;
+    Some(file)
+}
+
+/// Sum perceptual luminance over a strided subset of the pixels.
+fn patch_luminance(
+    pixels: &[u8],
+    format: wl_shm::Format,
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> (f64, usize) {
+    // Byte offsets of R, G, B within a little-endian 32-bit pixel
+    let (r, g, b) = match format {
+        wl_shm::Format::Argb8888 | wl_shm::Format::Xrgb8888 => (2, 1, 0),
+        _ => (0, 1, 2),
+    };
+    let mut total = 0.0f64;
+    let mut count = 0usize;
+    for y in (0..height as usize).step_by(PIXEL_STRIDE) {
+        for x in (0..width as usize).step_by(PIXEL_STRIDE) {
+            let idx = y * stride as usize + x * 4;
+            let Some(px) = pixels.get(idx..idx + 4) else {
+                continue;
+            };
+            let (pr, pg, pb) = (
+                px[r] as f64 / 255.0,
+                px[g] as f64 / 255.0,
+                px[b] as f64 / 255.0,
+            );
+            total += 0.2126 * pr + 0.7152 * pg + 0.0722 * pb;
+            count += 1;
+        }
+    }
+    (total, count)
+}
+
+impl Dispatch<wl_registry::WlRegistry, ()> for State {
+    fn event(
+        state: &mut Self,
+        registry: &wl_registry::WlRegistry,
+        event: wl_registry::Event,
+        _: &(),
+        _: &Connection,
+        qh: &QueueHandle<Self>,
+    ) {
+        let wl_registry::Event::Global {
+            name,
+            interface,
+            version,
+        } = event
+        else {
+            return;
+        };
+        match interface.as_str() {
+            "wl_shm" => state.shm = Some(registry.bind(name, 1, qh, ())),
+            "zwlr_screencopy_manager_v1" => {
+                state.manager = Some(registry.bind(name, version.min(3), qh, ()))
+            }
+            "wl_output" => {
+                let idx = state.outputs.len();
+                state.outputs.push(OutputInfo {
+                    output: registry.bind(name, version.min(4), qh, idx),
+                    name: None,
+                    width: 0,
+                    height: 0,
+                    scale: 1,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<wl_output::WlOutput, usize> for State {
+    fn event(
+        state: &mut Self,
+        _: &wl_output::WlOutput,
+        event: wl_output::Event,
+        idx: &usize,
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        let Some(info) = state.outputs.get_mut(*idx) else {
+            return;
+        };
+        match event {
+            wl_output::Event::Mode {
+                flags: WEnum::Value(flags),
+                width,
+                height,
+                ..
+            } if flags.contains(wl_output::Mode::Current) => {
+                info.width = width;
+                info.height = height;
+            }
+            wl_output::Event::Scale { factor } => info.scale = factor,
+            wl_output::Event::Name { name } => info.name = Some(name),
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<ZwlrScreencopyFrameV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _: &ZwlrScreencopyFrameV1,
+        event: zwlr_screencopy_frame_v1::Event,
+        _: &(),
+        _: &Connection,
+        _: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_screencopy_frame_v1::Event::Buffer {
+                format: WEnum::Value(format),
+                width,
+                height,
+                stride,
+            } if state.frame.format.is_none()
+                && matches!(
+                    format,
+                    wl_shm::Format::Argb8888
+                        | wl_shm::Format::Xrgb8888
+                        | wl_shm::Format::Abgr8888
+                        | wl_shm::Format::Xbgr8888
+                ) =>
+            {
+                state.frame.format = Some(format);
+                state.frame.width = width;
+                state.frame.height = height;
+                state.frame.stride = stride;
+            }
+            zwlr_screencopy_frame_v1::Event::Ready { .. } => state.frame.ready = true,
+            zwlr_screencopy_frame_v1::Event::Failed => state.frame.failed = true,
+            _ => {}
+        }
+    }
+}
+
+delegate_noop!(State: ignore wl_shm::WlShm);
+delegate_noop!(State: ignore wl_shm_pool::WlShmPool);
+delegate_noop!(State: ignore wl_buffer::WlBuffer);
+delegate_noop!(State: ZwlrScreencopyManagerV1);
@@ -0,0 +1,138 @@
+//! asciicast v2 recording of TUI widget output, replayable with
+//! `asciinema play`.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Writes a cast header followed by one `[elapsed, "o", data]` event per
+/// chunk of terminal output.
+pub struct CastWriter<W: Write> {
+    out: W,
+    started: Instant,
+    /// Trailing bytes of an incomplete UTF-8 sequence, completed by the
+    /// next chunk (events must be valid JSON strings).
+    pending: Vec<u8>,
+}
+
+impl CastWriter<std::io::BufWriter<std::fs::File>> {
+    /// Create `<dir>/<label>-<unix time>.cast` (and `dir` if needed).
+    pub fn create(
+        dir: &Path,
+        label: &str,
+        cols: usize,
+        rows: usize,
+    ) -> std::io::Result<(Self, PathBuf)> {
+        std::fs::create_dir_all(dir)?;
+        let timestamp = crate::util::unix_now();
+        let name: String = label
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '-'
+                }
+            })
+            .collect();
+        let path = dir.join(format!("{name}-{timestamp}.cast"));
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        let writer = Self::new(std::io::BufWriter::new(file), label, cols, rows, timestamp)?;
+        Ok((writer, path))
+    }
+}
+
+impl<W: Write> CastWriter<W> {
+    pub fn new(
+        mut out: W,
+        title: &str,
+        cols: usize,
+        rows: usize,
+        timestamp: i64,
+    ) -> std::io::Result<Self> {
+        let header = serde_json::json!({
+            "version": 2,
+            "width": cols,
+            "height": rows,
+            "timestamp": timestamp,
+            "title": title,
+            "env": { "TERM": "xterm-256color" },
+        });
+        writeln!(out, "{header}")?;
+        Ok(Self {
+            out,
+            started: Instant::now(),
+            pending: Vec::new(),
+        })
+    }
+
+    /// Record a chunk of raw PTY output.
+    pub fn output(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        self.write_event(elapsed, bytes)
+    }
+
+    fn write_event(&mut self, elapsed: f64, bytes: &[u8]) -> std::io::Result<()> {
+        self.pending.extend_from_slice(bytes);
+        // Hold back an incomplete sequence at the end; replace invalid bytes
+        let complete = match std::str::from_utf8(&self.pending) {
+            Ok(_) => self.pending.len(),
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(_) => self.pending.len(),
+        };
+        if complete == 0 {
+            return Ok(());
+        }
+        let data = String::from_utf8_lossy(&self.pending[..complete]).into_owned();
+        self.pending.drain(..complete);
+        let event = serde_json::json!([(elapsed * 1e6).round() / 1e6, "o", data]);
+        writeln!(self.out, "{event}")?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(buf: &[u8]) -> Vec<serde_json::Value> {
+        String::from_utf8_lossy(buf)
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn header_and_events() {
+        let mut buf = Vec::new();
+        let mut cast = CastWriter::new(&mut buf, "top", 80, 24, 1_700_000_000).unwrap();
+        cast.write_event(0.5, b"\x1b[2Jhello").unwrap();
+        drop(cast);
+        let lines = lines(&buf);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert_eq!(lines[0]["title"], "top");
+        assert_eq!(lines[1], serde_json::json!([0.5, "o", "\u{1b}[2Jhello"]));
+    }
+
+    #[test]
+    fn split_utf8_sequences_are_joined() {
+        let mut buf = Vec::new();
+        let mut cast = CastWriter::new(&mut buf, "t", 80, 24, 0).unwrap();
+        let bytes = "a→b".as_bytes();
+        cast.write_event(0.1, &bytes[..2]).unwrap();
+        cast.write_event(0.2, &bytes[2..3]).unwrap();
+        cast.write_event(0.3, &bytes[3..]).unwrap();
+        cast.write_event(0.4, b"\xffz").unwrap();
+        drop(cast);
+        let data: Vec<String> = lines(&buf)[1..]
+            .iter()
+            .map(|e| e[2].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(data, vec!["a", "→b", "\u{fffd}z"]);
+    }
+}
@@ -9,6 +9,10 @@ pub enum ShellMode {
     Oneshot,
     /// TUI program (e.g. `top`, `htop`). Runs in a PTY with terminal emulation.
     Tui,
+    /// Named pipe (`path`) that any script can write lines to. No process.
+    Fifo,
+    /// Executable speaking the JSON-lines plugin protocol (see `plugin`).
+    Plugin,
 }
 
 /// When a shell widget is visible.
@@ -22,7 +26,7 @@ pub enum Visibility {
 }
 
 /// Screen position for a shell widget.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum Position {
     TopLeft,
     TopRight,
@@ -31,6 +35,91 @@ pub enum Position {
     BottomRight,
 }
 
+impl Position {
+    pub const EXPECTED: &str = "top-left, top-right, bottom-left, bottom-right";
+
+    /// Parse a lowercase `top-left`-style name.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "top-left" => Some(Position::TopLeft),
+            "top-right" => Some(Position::TopRight),
+            "bottom-left" => Some(Position::BottomLeft),
+            "bottom-right" => Some(Position::BottomRight),
+            _ => None,
+        }
+    }
+}
+
+/// How a widget's output lines are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Plain text, one display line per output line.
+    #[default]
+    Text,
+    /// One JSON object per line, rendered through `JsonlFields` selectors.
+    Jsonl,
+}
+
+/// What happens to a regular command's stderr.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StderrMode {
+    /// Interleaved with stdout, rendered the same way.
+    #[default]
+    Merge,
+    /// Captured on its own and rendered in the error color.
+    Separate,
+    /// Discarded.
+    Hide,
+}
+
+/// Sandboxing applied to a widget's process so a runaway command can't
+/// starve the machine. Limits are per process (rlimits), inherited by children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ResourceLimits {
+    /// Start from an empty environment plus `CLEAN_ENV_KEEP`.
+    pub clean_env: bool,
+    /// Scheduling niceness (0..=19).
+    pub nice: Option<i32>,
+    /// CPU time in seconds (RLIMIT_CPU); the process is killed once it exceeds it.
+    pub cpu_secs: Option<u64>,
+    /// Address-space size in bytes (RLIMIT_AS); allocations beyond it fail.
+    pub memory_bytes: Option<u64>,
+}
+
+/// Variables passed through when `clean_env: true`.
+pub const CLEAN_ENV_KEEP: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LOGNAME",
+    "LANG",
+    "LC_ALL",
+    "TZ",
+    "XDG_RUNTIME_DIR",
+];
+
+/// jq-like selectors (`.type`, `.payload.msg`, `.items[0].name`) that pick
+/// the fields of a JSONL event to display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonlFields {
+    /// Event kind, shown as a prefix (`deploy: ...`).
+    pub type_field: String,
+    /// Main description text.
+    pub text_field: String,
+    /// Error marker; when present and not null/false the line renders as an error.
+    pub error_field: String,
+}
+
+impl Default for JsonlFields {
+    fn default() -> Self {
+        Self {
+            type_field: ".type".to_string(),
+            text_field: ".description".to_string(),
+            error_field: ".error".to_string(),
+        }
+    }
+}
+
 /// Parsed configuration for a single shell widget.
 #[derive(Debug, Clone)]
 pub struct ShellConfig {
@@ -43,6 +132,21 @@ pub struct ShellConfig {
     pub rows: usize,
     pub font_size: Option<f32>,
     pub position: Position,
+    pub format: OutputFormat,
+    pub fields: JsonlFields,
+    /// File source: the FIFO for `mode: fifo`, the followed file for `- path:`.
+    pub path: Option<PathBuf>,
+    /// Run `command` through `sh -c`. When false it is split into argv
+    /// (see `split_args`) and executed directly.
+    pub shell: bool,
+    pub stderr: StderrMode,
+    pub limits: ResourceLimits,
+    /// Directory for asciicast v2 recordings of a `mode: tui` widget,
+    /// one file per run.
+    pub record: Option<PathBuf>,
+    /// Order within a position: higher renders first and is hidden last
+    /// when the HUD's `overflow` strategy needs room.
+    pub priority: i32,
 }
 
 impl ShellConfig {
@@ -77,11 +181,242 @@ pub struct ConfigDiff {
     pub changed: Vec<ShellConfig>,
 }
 
+/// How serious a config problem is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The value was adjusted (e.g. clamped); the widget still runs.
+    Warning,
+    /// The line was ignored or the widget was dropped.
+    Error,
+}
+
+/// A problem found while parsing the config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    /// 1-based line number in the config file.
+    pub line: usize,
+    /// Widget the line belongs to, if it's under a `# heading`.
+    pub label: Option<String>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Result of parsing the config file: the usable widgets plus every problem found.
+pub struct ParsedConfig {
+    pub configs: Vec<ShellConfig>,
+    pub issues: Vec<ConfigIssue>,
+}
+
 /// Return the path to the shells config file.
 pub fn config_file_path() -> PathBuf {
-    dirs::home_dir()
-        .unwrap_or_else(|| PathBuf::from("/tmp"))
-        .join(".config/viz/shells.md")
+    crate::instance::config_dir().join("shells.md")
+}
+
+/// Commented starter config written by `dev-hud config init`.
+/// One example per widget mode; the noisier ones are inside HTML comments.
+pub const EXAMPLE_CONFIG: &str = r#"<!--
+Shell widgets for dev-hud (hot-reloaded; no restart needed).
+
+Each `# heading` starts a widget; the heading text is its label.
+Options: command, mode (oneshot | stream | tui | fifo | plugin), visible (focus | always),
+position (top-left | top-right | bottom-left | bottom-right), lines, cols,
+rows, font_size, path, format (text | jsonl), type_field, text_field, error_field,
+shell (true | false: run without `sh -c`), stderr (merge | separate | hide),
+clean_env (true | false), nice (0-19), cpu_limit (e.g. 5m), memory_limit (e.g. 512M),
+record (directory for asciicast recordings of tui widgets),
+priority (higher renders first within a position; -100 to 100).
+With `mode: plugin` the command prints JSON frames such as
+{"blocks": [{"text": "build ok", "color": "muted"}]} and reads clicks on stdin.
+Wrap an entry in an HTML comment to disable it, like the examples at the end.
+Run `dev-hud config check` after editing to validate this file.
+-->
+
+# uptime
+- command: uptime
+- mode: oneshot
+- visible: always
+- position: bottom-left
+
+# feed
+- mode: fifo
+- path: ~/.cache/dev-hud/feed
+- lines: 6
+- visible: always
+- position: bottom-right
+
+<!--
+# syslog
+- command: journalctl --user -f --no-pager -n 20
+- mode: stream
+- lines: 8
+- cols: 160
+
+# system-monitor
+- command: top -d 2
+- mode: tui
+- rows: 17
+- cols: 120
+- font_size: 6.5
+- position: top-right
+
+# agent-events
+- path: ~/.cache/my-agent/events.jsonl
+- type_field: .event
+- text_field: .payload.message
+- error_field: .payload.error
+-->
+"#;
+
+/// Widgets shown in demo mode, while no config file exists.
+pub const DEMO_CONFIG: &str = r#"# welcome
+- command: printf '%s\n' 'dev-hud demo: no shells.md yet' 'dev-hud config init      write a starter config' 'dev-hud-ctl demo dismiss stop showing this demo'
+- mode: oneshot
+- lines: 3
+- visible: always
+- position: top-left
+
+# clock
+- command: while :; do date +%T; sleep 1; done
+- mode: stream
+- lines: 1
+- visible: always
+- position: top-right
+
+# uptime
+- command: uptime
+- mode: oneshot
+- visible: always
+- position: bottom-left
+"#;
+
+/// Expand a leading `~/` to the home directory.
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|h| h.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Build the command used for a `- path:` source: follow the file from its
+/// last `lines` lines, surviving rotation and truncation.
+fn tail_command(path: &std::path::Path, lines: usize) -> String {
+    let path = path.to_string_lossy();
+    format!("tail -n {lines} -F '{}'", path.replace('\'', "'\\''"))
+}
+
+/// Resolve where an entry's output comes from.
+/// Returns `(command, path, default_format)`, or None if the entry has no source.
+/// Fifo widgets have no command; a bare `path` is followed with `tail -F`.
+fn resolve_source(
+    mode: Option<ShellMode>,
+    command: Option<String>,
+    path: Option<String>,
+    lines: usize,
+) -> Option<(String, Option<PathBuf>, OutputFormat)> {
+    if mode == Some(ShellMode::Fifo) {
+        let path = path
+            .as_deref()
+            .map(expand_home)
+            // Default FIFO for widgets without `- path:`
+            .unwrap_or_else(|| crate::instance::state_dir().join("feed"));
+        return Some((String::new(), Some(path), OutputFormat::Text));
+    }
+    if let Some(command) = command {
+        return Some((command, None, OutputFormat::Text));
+    }
+    let path = expand_home(&path?);
+    Some((tail_command(&path, lines), Some(path), OutputFormat::Jsonl))
+}
+
+/// HUD variables that can appear as `{name}` in a widget command.
+pub const TEMPLATE_VARS: &[&str] = &["target_output", "theme", "hud_mode"];
+
+/// Replace `{name}` placeholders for the names in `TEMPLATE_VARS` with their
+/// values (empty if unset). Other braces — `awk '{print $1}'`, `${HOME}` —
+/// are left alone.
+pub fn expand_template(command: &str, vars: &std::collections::BTreeMap<String, String>) -> String {
+    let mut out = command.to_string();
+    for name in TEMPLATE_VARS {
+        let placeholder = format!("{{{name}}}");
+        if out.contains(&placeholder) {
+            let value = vars.get(*name).map(String::as_str).unwrap_or("");
+            out = out.replace(&placeholder, value);
+        }
+    }
+    out
+}
+
+/// Split a command line into argv the way a shell would for plain words:
+/// whitespace separates arguments, single quotes are literal, double quotes
+/// allow `\"` and `\\` escapes, and a backslash outside quotes escapes the
+/// next character. No expansion, globbing, pipes or redirections.
+pub fn split_args(command: &str) -> Result<Vec<String>, String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\')) => current.push(c),
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err("unterminated double quote".to_string()),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err("unterminated double quote".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => current.push(c),
+                    None => return Err("trailing backslash".to_string()),
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        }
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
 }
 
 /// Parse `~/.config/viz/shells.md` into a list of shell configs.
@@ -97,25 +432,49 @@ pub fn config_file_path() -> PathBuf {
 /// - rows: 40
 /// - font_size: 5.0
 /// - position: top-left
+///
+/// # output-info
+/// - command: wlr-randr --output {target_output}
+///
+/// # build
+/// - command: cargo build --message-format short
+/// - shell: false
+/// - stderr: separate
+/// - clean_env: true
+/// - nice: 10
+/// - cpu_limit: 10m
+/// - memory_limit: 2G
+///
+/// # agent-events
+/// - path: ~/.cache/my-agent/events.jsonl
+/// - type_field: .event
+/// - text_field: .payload.message
+/// - error_field: .payload.error
+///
+/// # feed
+/// - mode: fifo
+/// - path: ~/.cache/dev-hud/feed
 /// ```
 ///
-/// Only `# heading` and `- command:` are required. See `ShellConfig` fields for defaults.
+/// Only `# heading` and `- command:` (or `- path:`) are required. A `path`
+/// is followed with `tail -F` and defaults to `format: jsonl`; `mode: fifo`
+/// needs neither (the FIFO defaults to `~/.cache/dev-hud/feed`). See
+/// `ShellConfig` fields for defaults.
 pub fn parse_config(content: &str) -> Vec<ShellConfig> {
-    let mut configs = Vec::new();
-    let mut current_label: Option<String> = None;
-    let mut current_command: Option<String> = None;
-    let mut current_mode: Option<ShellMode> = None;
-    let defaults = ShellConfig::defaults();
-    let mut current_lines: usize = defaults.lines;
-    let mut current_visible: Visibility = defaults.visible;
-    let mut current_cols: usize = defaults.cols;
-    let mut current_rows: usize = defaults.rows;
-    let mut current_font_size: Option<f32> = defaults.font_size;
-    let mut current_position: Position = defaults.position;
+    check_config(content).configs
+}
 
+/// Parse the config and report every problem with its line number:
+/// unknown keys, malformed lines, invalid or out-of-range values, widgets
+/// without a command and duplicate labels.
+pub fn check_config(content: &str) -> ParsedConfig {
+    let mut configs: Vec<ShellConfig> = Vec::new();
+    let mut issues = Vec::new();
+    let mut current: Option<Pending> = None;
     let mut in_comment = false;
 
-    for line in content.lines() {
+    for (idx, line) in content.lines().enumerate() {
+        let line_no = idx + 1;
         let trimmed = line.trim();
 
         // Skip HTML comment blocks: <!-- ... -->
@@ -131,99 +490,387 @@ pub fn parse_config(content: &str) -> Vec<ShellConfig> {
 
         if let Some(heading) = trimmed.strip_prefix("# ") {
             // Flush previous instance
-            if let (Some(label), Some(command)) = (current_label.take(), current_command.take()) {
-                configs.push(ShellConfig {
-                    label,
-                    command,
-                    mode: current_mode.take(),
-                    lines: current_lines,
-                    visible: current_visible,
-                    cols: current_cols,
-                    rows: current_rows,
-                    font_size: current_font_size,
-                    position: current_position,
-                });
-            } else {
-                current_command = None;
-                current_mode = None;
+            if let Some(pending) = current.take() {
+                pending.finish(&mut configs, &mut issues);
             }
-            current_label = Some(heading.trim().to_string());
-            current_lines = defaults.lines;
-            current_visible = defaults.visible;
-            current_cols = defaults.cols;
-            current_rows = defaults.rows;
-            current_font_size = defaults.font_size;
-            current_position = defaults.position;
+            current = Some(Pending::new(heading.trim(), line_no));
             continue;
         }
 
-        if current_label.is_none() {
+        // Anything that isn't a `- key: value` list item is free-form markdown
+        let Some(item) = trimmed.strip_prefix("- ") else {
             continue;
+        };
+        let Some(pending) = current.as_mut() else {
+            issues.push(ConfigIssue {
+                line: line_no,
+                label: None,
+                severity: Severity::Warning,
+                message: format!("'{trimmed}' is not under a '# widget' heading (ignored)"),
+            });
+            continue;
+        };
+        let problem = match item.split_once(':') {
+            Some((key, value)) => pending.set(key.trim(), value.trim()),
+            // A note in the widget's section, not a setting
+            None => Some((
+                Severity::Warning,
+                format!("'{trimmed}' is not a '- key: value' entry (ignored)"),
+            )),
+        };
+        if let Some((severity, message)) = problem {
+            issues.push(ConfigIssue {
+                line: line_no,
+                label: Some(pending.label.clone()),
+                severity,
+                message,
+            });
+        }
+    }
+
+    // Flush last instance
+    if let Some(pending) = current {
+        pending.finish(&mut configs, &mut issues);
+    }
+    // Dropped-widget issues are raised at the next heading; keep file order
+    issues.sort_by_key(|i| i.line);
+
+    ParsedConfig { configs, issues }
+}
+
+/// Keys accepted under a widget heading.
+const KNOWN_KEYS: &[&str] = &[
+    "command",
+    "mode",
+    "lines",
+    "visible",
+    "cols",
+    "rows",
+    "font_size",
+    "position",
+    "path",
+    "format",
+    "type_field",
+    "text_field",
+    "error_field",
+    "shell",
+    "stderr",
+    "clean_env",
+    "nice",
+    "cpu_limit",
+    "memory_limit",
+    "record",
+    "priority",
+];
+
+/// A widget whose `# heading` has been seen but whose entries are still being read.
+struct Pending {
+    label: String,
+    line: usize,
+    command: Option<String>,
+    mode: Option<ShellMode>,
+    lines: usize,
+    visible: Visibility,
+    cols: usize,
+    rows: usize,
+    font_size: Option<f32>,
+    position: Position,
+    path: Option<String>,
+    format: Option<OutputFormat>,
+    fields: JsonlFields,
+    shell: bool,
+    stderr: StderrMode,
+    limits: ResourceLimits,
+    record: Option<String>,
+    priority: i32,
+}
+
+/// A problem with a single `- key: value` line.
+pub(crate) type Problem = Option<(Severity, String)>;
+
+impl Pending {
+    fn new(label: &str, line: usize) -> Self {
+        let defaults = ShellConfig::defaults();
+        Self {
+            label: label.to_string(),
+            line,
+            command: None,
+            mode: None,
+            lines: defaults.lines,
+            visible: defaults.visible,
+            cols: defaults.cols,
+            rows: defaults.rows,
+            font_size: defaults.font_size,
+            position: defaults.position,
+            path: None,
+            format: None,
+            fields: JsonlFields::default(),
+            shell: true,
+            stderr: StderrMode::default(),
+            limits: ResourceLimits::default(),
+            record: None,
+            priority: 0,
         }
+    }
 
-        if let Some(rest) = trimmed.strip_prefix("- command:") {
-            let cmd = rest.trim();
-            if !cmd.is_empty() {
-                current_command = Some(cmd.to_string());
+    /// Apply one `- key: value` entry, returning what was wrong with it (if anything).
+    fn set(&mut self, key: &str, value: &str) -> Problem {
+        if !KNOWN_KEYS.contains(&key) {
+            return Some((
+                Severity::Error,
+                format!(
+                    "unknown key '{key}' (expected one of: {})",
+                    KNOWN_KEYS.join(", ")
+                ),
+            ));
+        }
+        if value.is_empty() {
+            return Some((Severity::Error, format!("'{key}' has no value")));
+        }
+        let lower = value.to_lowercase();
+        match key {
+            "command" => self.command = Some(value.to_string()),
+            "path" => self.path = Some(value.to_string()),
+            "record" => self.record = Some(value.to_string()),
+            "mode" => {
+                self.mode = Some(match lower.as_str() {
+                    "stream" => ShellMode::Stream,
+                    "oneshot" => ShellMode::Oneshot,
+                    "tui" => ShellMode::Tui,
+                    "fifo" => ShellMode::Fifo,
+                    "plugin" => ShellMode::Plugin,
+                    _ => return invalid(key, value, "oneshot, stream, tui, fifo, plugin"),
+                })
             }
-        } else if let Some(rest) = trimmed.strip_prefix("- mode:") {
-            let mode_str = rest.trim().to_lowercase();
-            current_mode = match mode_str.as_str() {
-                "stream" => Some(ShellMode::Stream),
-                "oneshot" => Some(ShellMode::Oneshot),
-                "tui" => Some(ShellMode::Tui),
-                _ => None,
-            };
-        } else if let Some(rest) = trimmed.strip_prefix("- lines:") {
-            if let Ok(n) = rest.trim().parse::<usize>() {
-                current_lines = n.clamp(1, 64);
+            "visible" => {
+                self.visible = match lower.as_str() {
+                    "always" => Visibility::Always,
+                    "focus" => Visibility::Focus,
+                    _ => return invalid(key, value, "focus, always"),
+                }
             }
-        } else if let Some(rest) = trimmed.strip_prefix("- visible:") {
-            let vis_str = rest.trim().to_lowercase();
-            current_visible = match vis_str.as_str() {
-                "always" => Visibility::Always,
-                _ => Visibility::Focus,
-            };
-        } else if let Some(rest) = trimmed.strip_prefix("- cols:") {
-            if let Ok(n) = rest.trim().parse::<usize>() {
-                current_cols = n.clamp(40, 512);
+            "position" => {
+                self.position = match Position::parse(&lower) {
+                    Some(position) => position,
+                    None => return invalid(key, value, Position::EXPECTED),
+                }
+            }
+            "format" => {
+                self.format = Some(match lower.as_str() {
+                    "jsonl" | "json" => OutputFormat::Jsonl,
+                    "text" => OutputFormat::Text,
+                    _ => return invalid(key, value, "text, jsonl"),
+                })
+            }
+            "shell" => {
+                self.shell = match parse_bool(&lower) {
+                    Some(b) => b,
+                    None => return invalid(key, value, "true, false"),
+                }
+            }
+            "clean_env" => {
+                self.limits.clean_env = match parse_bool(&lower) {
+                    Some(b) => b,
+                    None => return invalid(key, value, "true, false"),
+                }
+            }
+            "nice" => {
+                let mut nice = 0;
+                let problem = clamped(key, value, 0, 19, &mut nice);
+                if !matches!(problem, Some((Severity::Error, _))) {
+                    self.limits.nice = Some(nice);
+                }
+                return problem;
             }
-        } else if let Some(rest) = trimmed.strip_prefix("- rows:") {
-            if let Ok(n) = rest.trim().parse::<usize>() {
-                current_rows = n.clamp(4, 200);
+            "cpu_limit" => match parse_duration_secs(&lower) {
+                Some(secs) if secs > 0 => self.limits.cpu_secs = Some(secs),
+                _ => return invalid(key, value, "CPU seconds, e.g. 30, 90s, 5m"),
+            },
+            "memory_limit" => match parse_bytes(&lower) {
+                Some(bytes) if bytes >= 1 << 20 => self.limits.memory_bytes = Some(bytes),
+                _ => return invalid(key, value, "a size of at least 1M, e.g. 512M, 2G"),
+            },
+            "stderr" => {
+                self.stderr = match lower.as_str() {
+                    "merge" => StderrMode::Merge,
+                    "separate" => StderrMode::Separate,
+                    "hide" => StderrMode::Hide,
+                    _ => return invalid(key, value, "merge, separate, hide"),
+                }
             }
-        } else if let Some(rest) = trimmed.strip_prefix("- font_size:") {
-            if let Ok(f) = rest.trim().parse::<f32>() {
-                current_font_size = Some(f.clamp(2.0, 32.0));
+            "lines" => return clamped(key, value, 1, 64, &mut self.lines),
+            "cols" => return clamped(key, value, 40, 512, &mut self.cols),
+            "rows" => return clamped(key, value, 4, 200, &mut self.rows),
+            "priority" => return clamped(key, value, -100, 100, &mut self.priority),
+            "font_size" => {
+                let mut size = self.font_size.unwrap_or(0.0);
+                let problem = clamped(key, value, 2.0, 32.0, &mut size);
+                if !matches!(problem, Some((Severity::Error, _))) {
+                    self.font_size = Some(size);
+                }
+                return problem;
             }
-        } else if let Some(rest) = trimmed.strip_prefix("- position:") {
-            let pos_str = rest.trim().to_lowercase();
-            current_position = match pos_str.as_str() {
-                "top-left" => Position::TopLeft,
-                "top-right" => Position::TopRight,
-                "bottom-left" => Position::BottomLeft,
-                "bottom-right" => Position::BottomRight,
-                _ => defaults.position,
-            };
+            "type_field" => self.fields.type_field = value.to_string(),
+            "text_field" => self.fields.text_field = value.to_string(),
+            "error_field" => self.fields.error_field = value.to_string(),
+            _ => unreachable!("key checked against KNOWN_KEYS"),
         }
+        None
     }
 
-    // Flush last instance
-    if let (Some(label), Some(command)) = (current_label, current_command) {
+    /// Push the finished widget, or an issue explaining why it was dropped.
+    fn finish(self, configs: &mut Vec<ShellConfig>, issues: &mut Vec<ConfigIssue>) {
+        let drop_issue = |message: String| ConfigIssue {
+            line: self.line,
+            label: Some(self.label.clone()),
+            severity: Severity::Error,
+            message,
+        };
+        if let Some(first) = configs.iter().find(|c| c.label == self.label) {
+            issues.push(drop_issue(format!(
+                "duplicate widget '{}' (ignored; labels must be unique)",
+                first.label
+            )));
+            return;
+        }
+        let Some((command, path, default_format)) =
+            resolve_source(self.mode, self.command, self.path, self.lines)
+        else {
+            issues.push(drop_issue(format!(
+                "widget '{}' has no '- command:' (or '- path:')",
+                self.label
+            )));
+            return;
+        };
+        if !self.shell && self.mode != Some(ShellMode::Fifo) {
+            match split_args(&command) {
+                Ok(argv) if argv.is_empty() => {
+                    issues.push(drop_issue(format!(
+                        "widget '{}' has an empty command",
+                        self.label
+                    )));
+                    return;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    issues.push(drop_issue(format!(
+                        "widget '{}': cannot split command with 'shell: false': {e}",
+                        self.label
+                    )));
+                    return;
+                }
+            }
+        }
+        let record = match self.record {
+            Some(_) if self.mode != Some(ShellMode::Tui) => {
+                issues.push(ConfigIssue {
+                    severity: Severity::Warning,
+                    ..drop_issue(format!(
+                        "widget '{}': 'record' only applies to 'mode: tui' (ignored)",
+                        self.label
+                    ))
+                });
+                None
+            }
+            record => record.as_deref().map(expand_home),
+        };
         configs.push(ShellConfig {
-            label,
+            label: self.label,
             command,
-            mode: current_mode,
-            lines: current_lines,
-            visible: current_visible,
-            cols: current_cols,
-            rows: current_rows,
-            font_size: current_font_size,
-            position: current_position,
+            mode: self.mode,
+            lines: self.lines,
+            visible: self.visible,
+            cols: self.cols,
+            rows: self.rows,
+            font_size: self.font_size,
+            position: self.position,
+            format: self.format.unwrap_or(default_format),
+            fields: self.fields,
+            path,
+            shell: self.shell,
+            stderr: self.stderr,
+            limits: self.limits,
+            record,
+            priority: self.priority,
         });
     }
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "true" | "yes" => Some(true),
+        "false" | "no" => Some(false),
+        _ => None,
+    }
+}
 
-    configs
+/// Parse `30`, `30s`, `5m` or `1h` into seconds.
+pub(crate) fn parse_duration_secs(value: &str) -> Option<u64> {
+    let (num, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, ""),
+    };
+    let n: u64 = num.parse().ok()?;
+    let mult = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return None,
+    };
+    n.checked_mul(mult)
+}
+
+/// Parse a byte size with an optional binary suffix:
+/// `1048576`, `512k`, `512m`/`512mb`/`512mib`, `2g`.
+fn parse_bytes(value: &str) -> Option<u64> {
+    let (num, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => value.split_at(i),
+        None => (value, ""),
+    };
+    let n: u64 = num.parse().ok()?;
+    let unit = unit.trim();
+    let unit = unit
+        .strip_suffix("ib")
+        .or_else(|| unit.strip_suffix('b'))
+        .unwrap_or(unit);
+    let mult: u64 = match unit {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        _ => return None,
+    };
+    n.checked_mul(mult)
+}
+
+pub(crate) fn invalid(key: &str, value: &str, expected: &str) -> Problem {
+    Some((
+        Severity::Error,
+        format!("invalid {key} '{value}' (expected {expected})"),
+    ))
+}
+
+/// Parse a number into `slot`, clamping it to `min..=max` with a warning.
+pub(crate) fn clamped<T>(key: &str, value: &str, min: T, max: T, slot: &mut T) -> Problem
+where
+    T: std::str::FromStr + PartialOrd + Copy + std::fmt::Display,
+{
+    let Ok(n) = value.parse::<T>() else {
+        return Some((
+            Severity::Error,
+            format!("invalid {key} '{value}' (expected a number)"),
+        ));
+    };
+    if n < min || n > max {
+        let c = if n < min { min } else { max };
+        *slot = c;
+        return Some((
+            Severity::Warning,
+            format!("{key} {n} out of range {min}..={max}, using {c}"),
+        ));
+    }
+    *slot = n;
+    None
 }
 
 /// Compute the diff between old and new config lists.
@@ -250,6 +897,14 @@ pub fn reconcile(old: &[ShellConfig], new: &[ShellConfig]) -> ConfigDiff {
                     || old_cfg.rows != new_cfg.rows
                     || old_cfg.font_size != new_cfg.font_size
                     || old_cfg.position != new_cfg.position
+                    || old_cfg.format != new_cfg.format
+                    || old_cfg.fields != new_cfg.fields
+                    || old_cfg.path != new_cfg.path
+                    || old_cfg.shell != new_cfg.shell
+                    || old_cfg.stderr != new_cfg.stderr
+                    || old_cfg.limits != new_cfg.limits
+                    || old_cfg.record != new_cfg.record
+                    || old_cfg.priority != new_cfg.priority
                 {
                     changed.push(new_cfg.clone());
                 }
@@ -285,6 +940,14 @@ mod tests {
             rows: 24,
             font_size: None,
             position: Position::BottomRight,
+            format: OutputFormat::Text,
+            fields: JsonlFields::default(),
+            path: None,
+            shell: true,
+            stderr: StderrMode::Merge,
+            limits: ResourceLimits::default(),
+            record: None,
+            priority: 0,
         }
     }
 
@@ -353,6 +1016,15 @@ mod tests {
         assert_eq!(configs[0].font_size, Some(5.0));
     }
 
+    #[test]
+    fn parse_plugin_mode() {
+        let input = "# ci\n- command: ~/bin/ci-plugin\n- mode: plugin\n- lines: 4\n";
+        let configs = parse_config(input);
+        assert_eq!(configs[0].mode, Some(ShellMode::Plugin));
+        assert_eq!(configs[0].command, "~/bin/ci-plugin");
+        assert_eq!(configs[0].lines, 4);
+    }
+
     #[test]
     fn parse_position() {
         let input = r#"
@@ -538,4 +1210,308 @@ mod tests {
         let diff = reconcile(&old, &new);
         assert_eq!(diff.changed.len(), 1);
     }
+
+    #[test]
+    fn parse_jsonl_path_source() {
+        let input = r#"
+# agent
+- path: /tmp/agent events.jsonl
+- lines: 8
+- type_field: .event
+- text_field: .payload.msg
+"#;
+        let configs = parse_config(input);
+        assert_eq!(configs.len(), 1);
+        assert_eq!(configs[0].command, "tail -n 8 -F '/tmp/agent events.jsonl'");
+        assert_eq!(configs[0].format, OutputFormat::Jsonl);
+        assert_eq!(configs[0].fields.type_field, ".event");
+        assert_eq!(configs[0].fields.text_field, ".payload.msg");
+        assert_eq!(configs[0].fields.error_field, ".error"); // default
+    }
+
+    #[test]
+    fn parse_format_on_command() {
+        let input = r#"
+# bot
+- command: ./bot --json
+- format: jsonl
+
+# plain
+- command: echo hi
+"#;
+        let configs = parse_config(input);
+        assert_eq!(configs[0].format, OutputFormat::Jsonl);
+        assert_eq!(configs[0].command, "./bot --json");
+        assert_eq!(configs[1].format, OutputFormat::Text);
+    }
+
+    #[test]
+    fn tail_command_quotes_path() {
+        assert_eq!(
+            tail_command(std::path::Path::new("/tmp/it's.jsonl"), 4),
+            "tail -n 4 -F '/tmp/it'\\''s.jsonl'"
+        );
+    }
+
+    #[test]
+    fn reconcile_detects_fields_change() {
+        let old = vec![default_config("a", "echo a")];
+        let mut new_cfg = default_config("a", "echo a");
+        new_cfg.fields.text_field = ".msg".into();
+        let diff = reconcile(&old, &[new_cfg]);
+        assert_eq!(diff.changed.len(), 1);
+    }
+
+    #[test]
+    fn parse_fifo_mode() {
+        let input = r#"
+# feed
+- mode: fifo
+- lines: 5
+
+# named-feed
+- mode: fifo
+- path: /run/user/1000/hud-feed
+"#;
+        let configs = parse_config(input);
+        assert_eq!(configs.len(), 2);
+        assert_eq!(configs[0].mode, Some(ShellMode::Fifo));
+        assert_eq!(configs[0].command, "");
+        assert!(
+            configs[0]
+                .path
+                .as_ref()
+                .unwrap()
+                .ends_with(".cache/dev-hud/feed")
+        );
+        assert_eq!(configs[0].format, OutputFormat::Text);
+        assert_eq!(
+            configs[1].path.as_deref(),
+            Some(std::path::Path::new("/run/user/1000/hud-feed"))
+        );
+    }
+
+    #[test]
+    fn check_reports_unknown_key_and_bad_values() {
+        let input = r#"# a
+- command: echo a
+- colz: 80
+- mode: daemon
+- visible: sometimes
+- lines: lots
+"#;
+        let parsed = check_config(input);
+        assert_eq!(parsed.configs.len(), 1);
+        let lines: Vec<usize> = parsed.issues.iter().map(|i| i.line).collect();
+        assert_eq!(lines, vec![3, 4, 5, 6]);
+        assert!(parsed.issues.iter().all(|i| i.severity == Severity::Error));
+        assert!(parsed.issues[0].message.contains("unknown key 'colz'"));
+        assert!(parsed.issues[1].message.contains("invalid mode 'daemon'"));
+        assert_eq!(parsed.issues[0].label.as_deref(), Some("a"));
+        // Bad values fall back to defaults
+        assert_eq!(parsed.configs[0].mode, None);
+        assert_eq!(parsed.configs[0].lines, 16);
+    }
+
+    #[test]
+    fn check_reports_missing_command_at_heading() {
+        let parsed = check_config("intro text\n\n# broken\n- mode: stream\n");
+        assert!(parsed.configs.is_empty());
+        assert_eq!(parsed.issues.len(), 1);
+        assert_eq!(parsed.issues[0].line, 3);
+        assert_eq!(parsed.issues[0].label.as_deref(), Some("broken"));
+        assert!(parsed.issues[0].message.contains("no '- command:'"));
+    }
+
+    #[test]
+    fn check_warns_on_clamped_values() {
+        let parsed = check_config("# a\n- command: x\n- cols: 9999\n");
+        assert_eq!(parsed.configs[0].cols, 512);
+        assert_eq!(parsed.issues.len(), 1);
+        assert_eq!(parsed.issues[0].severity, Severity::Warning);
+        assert_eq!(
+            parsed.issues[0].to_string(),
+            "line 3: cols 9999 out of range 40..=512, using 512"
+        );
+    }
+
+    #[test]
+    fn check_rejects_duplicate_labels() {
+        let parsed = check_config("# a\n- command: one\n\n# a\n- command: two\n");
+        assert_eq!(parsed.configs.len(), 1);
+        assert_eq!(parsed.configs[0].command, "one");
+        assert_eq!(parsed.issues.len(), 1);
+        assert_eq!(parsed.issues[0].line, 4);
+    }
+
+    #[test]
+    fn check_flags_malformed_and_orphan_items() {
+        let parsed =
+            check_config("- command: orphan\n# a\n- command: x\n- just a bullet\n- colour: red\n");
+        assert_eq!(parsed.configs.len(), 1);
+        assert_eq!(parsed.issues.len(), 3);
+        assert_eq!(parsed.issues[0].severity, Severity::Warning);
+        assert_eq!(parsed.issues[0].label, None);
+        assert_eq!(parsed.issues[1].line, 4);
+        assert_eq!(parsed.issues[1].severity, Severity::Warning);
+        assert!(parsed.issues[1].message.ends_with("(ignored)"));
+        assert_eq!(parsed.issues[2].severity, Severity::Error);
+        assert!(parsed.issues[2].message.starts_with("unknown key 'colour'"));
+    }
+
+    #[test]
+    fn check_clean_config_has_no_issues() {
+        let input = r#"
+# top
+- command: top -b
+- mode: tui
+- rows: 20
+- font_size: 6.5
+- position: top-right
+- visible: always
+"#;
+        assert!(check_config(input).issues.is_empty());
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let parsed = check_config(EXAMPLE_CONFIG);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        let labels: Vec<&str> = parsed.configs.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["uptime", "feed"]);
+
+        let parsed = check_config(DEMO_CONFIG);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert_eq!(parsed.configs.len(), 3);
+    }
+
+    #[test]
+    fn split_args_quoting() {
+        assert_eq!(
+            split_args(r#"grep -r "two words" 'a "b"' c\ d"#).unwrap(),
+            vec!["grep", "-r", "two words", "a \"b\"", "c d"]
+        );
+        assert_eq!(
+            split_args(r#""say \"hi\"" x\$y"#).unwrap(),
+            vec!["say \"hi\"", "x$y"]
+        );
+        assert_eq!(split_args("  ''  ").unwrap(), vec![""]);
+        assert!(split_args("").unwrap().is_empty());
+        assert!(split_args("echo 'oops").is_err());
+        assert!(split_args("echo \"oops").is_err());
+    }
+
+    #[test]
+    fn parse_shell_and_stderr() {
+        let input = r#"
+# build
+- command: cargo build
+- shell: false
+- stderr: separate
+
+# quiet
+- command: make 2>/dev/null
+- stderr: hide
+"#;
+        let parsed = check_config(input);
+        assert!(parsed.issues.is_empty(), "{:?}", parsed.issues);
+        assert!(!parsed.configs[0].shell);
+        assert_eq!(parsed.configs[0].stderr, StderrMode::Separate);
+        assert!(parsed.configs[1].shell);
+        assert_eq!(parsed.configs[1].stderr, StderrMode::Hide);
+    }
+
+    #[test]
+    fn argv_mode_with_bad_quoting_is_dropped() {
+        let input = "# broken\n- command: echo 'oops\n- shell: false\n- stderr: loud\n";
+        let parsed = check_config(input);
+        assert!(parsed.configs.is_empty());
+        assert_eq!(parsed.issues.len(), 2);
+        assert_eq!(parsed.issues[0].line, 1);
+        assert!(
+            parsed.issues[0]
+                .message
+                .contains("unterminated single quote")
+        );
+        assert!(parsed.issues[1].message.contains("invalid stderr 'loud'"));
+    }
+
+    #[test]
+    fn reconcile_detects_stderr_change() {
+        let old = vec![default_config("a", "make")];
+        let mut changed = default_config("a", "make");
+        changed.stderr = StderrMode::Separate;
+        let diff = reconcile(&old, &[changed]);
+        assert_eq!(diff.changed.len(), 1);
+    }
+
+    #[test]
+    fn parse_resource_limits() {
+        let input = r#"
+# build
+- command: make
+- clean_env: yes
+- nice: 25
+- cpu_limit: 5m
+- memory_limit: 512MiB
+"#;
+        let parsed = check_config(input);
+        let limits = parsed.configs[0].limits;
+        assert!(limits.clean_env);
+        assert_eq!(limits.nice, Some(19));
+        assert_eq!(limits.cpu_secs, Some(300));
+        assert_eq!(limits.memory_bytes, Some(512 << 20));
+        assert_eq!(parsed.issues.len(), 1);
+        assert_eq!(parsed.issues[0].severity, Severity::Warning);
+    }
+
+    #[test]
+    fn parse_resource_limits_invalid() {
+        let input = "# x\n- command: make\n- cpu_limit: soon\n- memory_limit: 10k\n";
+        let parsed = check_config(input);
+        assert_eq!(parsed.configs[0].limits, ResourceLimits::default());
+        assert_eq!(parsed.issues.len(), 2);
+        assert!(parsed.issues.iter().all(|i| i.severity == Severity::Error));
+    }
+
+    #[test]
+    fn parse_record_only_for_tui() {
+        let input = "# top\n- command: htop\n- mode: tui\n- record: /tmp/casts\n\
+                     # log\n- command: tail -f x\n- record: /tmp/casts\n";
+        let parsed = check_config(input);
+        assert_eq!(parsed.configs[0].record, Some(PathBuf::from("/tmp/casts")));
+        assert_eq!(parsed.configs[1].record, None);
+        assert_eq!(parsed.issues.len(), 1);
+        assert_eq!(parsed.issues[0].severity, Severity::Warning);
+        assert_eq!(parsed.issues[0].label.as_deref(), Some("log"));
+    }
+
+    #[test]
+    fn parse_sizes_and_durations() {
+        assert_eq!(parse_bytes("2g"), Some(2 << 30));
+        assert_eq!(parse_bytes("64kb"), Some(64 << 10));
+        assert_eq!(parse_bytes("4096"), Some(4096));
+        assert_eq!(parse_bytes("1t"), None);
+        assert_eq!(parse_duration_secs("90s"), Some(90));
+        assert_eq!(parse_duration_secs("1h"), Some(3600));
+        assert_eq!(parse_duration_secs("m"), None);
+    }
+
+    #[test]
+    fn expand_template_known_vars_only() {
+        let mut vars = std::collections::BTreeMap::new();
+        vars.insert("target_output".to_string(), "DP-2".to_string());
+        assert_eq!(
+            expand_template(
+                "wlr-randr --output {target_output} | awk '{print $1}'",
+                &vars
+            ),
+            "wlr-randr --output DP-2 | awk '{print $1}'"
+        );
+        assert_eq!(expand_template("echo {theme}-{hud_mode}", &vars), "echo -");
+        assert_eq!(
+            expand_template("echo ${HOME} {other}", &vars),
+            "echo ${HOME} {other}"
+        );
+    }
 }
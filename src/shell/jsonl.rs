@@ -0,0 +1,144 @@
+use serde_json::Value;
+
+use super::config::JsonlFields;
+
+/// Evaluate a jq-like selector against a JSON value.
+///
+/// Supported syntax: `.` (the whole value), `.key`, `.a.b.c`, array indexes
+/// (`.items[0]`, `.[2]`) and quoted keys (`."key with.dots"`).
+/// Returns `None` if any step is missing.
+pub fn select<'a>(value: &'a Value, selector: &str) -> Option<&'a Value> {
+    let mut current = value;
+    let mut rest = selector.trim();
+    rest = rest.strip_prefix('.').unwrap_or(rest);
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let idx: usize = after[..end].trim().parse().ok()?;
+            current = current.get(idx)?;
+            rest = &after[end + 1..];
+        } else if let Some(after) = rest.strip_prefix('"') {
+            let end = after.find('"')?;
+            current = current.get(&after[..end])?;
+            rest = &after[end + 1..];
+        } else {
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            current = current.get(&rest[..end])?;
+            rest = &rest[end..];
+        }
+        rest = rest.strip_prefix('.').unwrap_or(rest);
+    }
+    Some(current)
+}
+
+/// Render a selected value as display text. Null renders as nothing; objects
+/// and arrays render as compact JSON.
+fn value_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) if s.is_empty() => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// Whether an error field value means "this event is an error".
+fn is_error_value(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => false,
+        Value::String(s) => !s.is_empty(),
+        _ => true,
+    }
+}
+
+/// Turn one JSONL line into `(display_text, is_error)`.
+///
+/// Lines that aren't valid JSON pass through unchanged so a source can mix
+/// plain log lines with events. Error events show the error text when the
+/// error field is a string, otherwise the regular description.
+pub fn format_line(fields: &JsonlFields, line: &str) -> (String, bool) {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(v) => v,
+        Err(_) => return (line.to_string(), false),
+    };
+
+    let kind = select(&value, &fields.type_field).and_then(value_text);
+    let error = select(&value, &fields.error_field).filter(|v| is_error_value(v));
+    let is_error = error.is_some();
+    let text = match error {
+        Some(Value::String(msg)) => Some(msg.clone()),
+        _ => select(&value, &fields.text_field).and_then(value_text),
+    };
+
+    let rendered = match (kind, text) {
+        (Some(kind), Some(text)) => format!("{kind}: {text}"),
+        (Some(only), None) | (None, Some(only)) => only,
+        (None, None) => line.to_string(),
+    };
+    (rendered, is_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(t: &str, d: &str, e: &str) -> JsonlFields {
+        JsonlFields {
+            type_field: t.into(),
+            text_field: d.into(),
+            error_field: e.into(),
+        }
+    }
+
+    #[test]
+    fn select_nested_and_indexed() {
+        let v: Value =
+            serde_json::from_str(r#"{"a":{"b":[{"c":1},{"c":"two"}]},"x.y":true}"#).unwrap();
+        assert_eq!(select(&v, ".a.b[1].c"), Some(&Value::from("two")));
+        assert_eq!(select(&v, "a.b[0].c"), Some(&Value::from(1)));
+        assert_eq!(select(&v, r#"."x.y""#), Some(&Value::Bool(true)));
+        assert_eq!(select(&v, "."), Some(&v));
+        assert_eq!(select(&v, ".a.missing"), None);
+        assert_eq!(select(&v, ".a.b[9]"), None);
+    }
+
+    #[test]
+    fn format_default_fields() {
+        let (text, err) = format_line(
+            &JsonlFields::default(),
+            r#"{"type":"deploy","description":"rolling out v2"}"#,
+        );
+        assert_eq!(text, "deploy: rolling out v2");
+        assert!(!err);
+    }
+
+    #[test]
+    fn format_error_prefers_error_message() {
+        let f = fields(".event", ".msg", ".err");
+        let (text, err) = format_line(&f, r#"{"event":"build","msg":"compiling","err":"exit 2"}"#);
+        assert_eq!(text, "build: exit 2");
+        assert!(err);
+
+        let (text, err) = format_line(&f, r#"{"event":"build","msg":"compiling","err":true}"#);
+        assert_eq!(text, "build: compiling");
+        assert!(err);
+
+        let (_, err) = format_line(&f, r#"{"event":"build","msg":"ok","err":null}"#);
+        assert!(!err);
+    }
+
+    #[test]
+    fn format_non_json_passthrough() {
+        let (text, err) = format_line(&JsonlFields::default(), "plain log line");
+        assert_eq!(text, "plain log line");
+        assert!(!err);
+    }
+
+    #[test]
+    fn format_missing_fields_falls_back_to_raw() {
+        let line = r#"{"other":1}"#;
+        let (text, _) = format_line(&JsonlFields::default(), line);
+        assert_eq!(text, line);
+    }
+}
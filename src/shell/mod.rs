@@ -1,12 +1,18 @@
+pub mod cast;
 pub mod config;
+pub mod jsonl;
+pub mod plugin;
 
-use std::collections::VecDeque;
-use std::io::{BufRead, Read as _};
-use std::process::{Child, Command, Stdio};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::io::{BufRead, Read as _, Write as _};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant, SystemTime};
 
-use config::ShellConfig;
+use config::{OutputFormat, ResourceLimits, ShellConfig, StderrMode};
 pub use config::{Position, ShellMode, Visibility};
 
 /// Maximum lines kept in the ring buffer per instance.
@@ -15,23 +21,83 @@ const MAX_BUFFER_LINES: usize = 256;
 /// How quickly we poll for new output (ms).
 const POLL_INTERVAL_MS: u64 = 50;
 
+/// Output messages a widget's reader threads can queue. When full, the
+/// readers block and the command's own writes stall until the next poll.
+const OUTPUT_QUEUE: usize = 1024;
+
+/// Events the shell thread can queue for the UI. When full, the thread
+/// waits, which in turn lets the readers' queues fill up.
+const EVENT_QUEUE: usize = 256;
+
+/// How often a FIFO reader waiting for input checks whether it was stopped.
+const FIFO_STOP_CHECK: Duration = Duration::from_millis(200);
+
+/// How long to keep reading an exited command's output. Its readers may
+/// still be blocked on a full queue; a background child that inherited
+/// the pipe can keep it open forever.
+const EXIT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Config file mtime check interval (polls).
 const CONFIG_CHECK_POLLS: u64 = 40; // ~2s at 50ms
 
 /// If mode is auto-detect and process exits within this duration, treat as oneshot.
 const ONESHOT_DETECT_SECS: u64 = 3;
 
+/// Current values of the `{name}` placeholders in widget commands.
+/// Written by the UI, polled by the shell thread.
+static TEMPLATE_VALUES: Mutex<BTreeMap<String, String>> = Mutex::new(BTreeMap::new());
+
+/// Publish the HUD's template variables. Widgets whose expanded command
+/// changes are respawned by the shell thread on its next poll.
+pub fn set_template_vars(vars: BTreeMap<String, String>) {
+    *TEMPLATE_VALUES.lock().unwrap_or_else(|e| e.into_inner()) = vars;
+}
+
+fn template_vars() -> BTreeMap<String, String> {
+    TEMPLATE_VALUES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+/// Events waiting to be written to plugin widgets' stdin, as
+/// `(label, json line)`. Written by the UI, drained by the shell thread.
+static PLUGIN_EVENTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Queue a protocol event (see `plugin`) for the plugin widget `label`.
+pub fn send_to_plugin(label: &str, event: String) {
+    PLUGIN_EVENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push((label.to_string(), event));
+}
+
 /// A running shell widget instance.
 pub struct ShellInstance {
     pub config: ShellConfig,
-    pub buffer: VecDeque<String>,
+    pub buffer: VecDeque<ShellLine>,
     pub exit_code: Option<i32>,
+    /// Signal that terminated the process, if any (e.g. "Killed").
+    pub exit_signal: Option<String>,
     pub last_update: SystemTime,
     pub error: Option<String>,
     /// Resolved mode (after auto-detection).
     pub resolved_mode: ShellMode,
     /// TUI screen snapshot (only used when resolved_mode == Tui).
     pub tui_screen: Option<Vec<String>>,
+    /// Latest plugin frame (only used when resolved_mode == Plugin).
+    pub plugin_blocks: Option<Vec<plugin::Block>>,
+    /// Config check errors for this widget (shown above its output).
+    pub config_error: Option<String>,
+    /// Placeholder for a widget the config check dropped.
+    dropped: bool,
+    /// Lines never shown because the command wrote faster than the HUD
+    /// shows them (more than a buffer's worth in one event). Hooks and
+    /// rules still saw them. Shown in focused mode.
+    pub skipped_lines: u64,
+    /// `ShellState::revision` when this instance last changed; the view
+    /// rebuilds a corner only when one of its instances' revision moved.
+    pub revision: u64,
     /// When the process was spawned (for oneshot auto-detection).
     spawned_at: Instant,
 }
@@ -42,10 +108,62 @@ fn new_instance(cfg: &ShellConfig) -> ShellInstance {
         config: cfg.clone(),
         buffer: VecDeque::new(),
         exit_code: None,
+        exit_signal: None,
         last_update: SystemTime::now(),
         error: None,
         tui_screen: None,
+        plugin_blocks: None,
+        config_error: None,
+        dropped: false,
         spawned_at: Instant::now(),
+        skipped_lines: 0,
+        revision: 0,
+    }
+}
+
+/// Coarse widget health, for the minimal display's status dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellStatus {
+    Running,
+    /// Exited 0.
+    Done,
+    /// Spawn/config error, a non-zero exit, or killed by a signal.
+    Failed,
+}
+
+impl ShellInstance {
+    /// Rows the widget wants below its label: the TUI screen, or buffered
+    /// lines up to `lines` (at least one for "..." or an error).
+    pub fn output_lines(&self) -> usize {
+        let rows = match (&self.tui_screen, &self.plugin_blocks) {
+            (Some(screen), _) if self.resolved_mode == ShellMode::Tui => screen.len(),
+            (_, Some(blocks)) if self.resolved_mode == ShellMode::Plugin => {
+                blocks.len().min(self.config.lines)
+            }
+            _ => self.buffer.len().min(self.config.lines),
+        };
+        rows.max(1)
+    }
+
+    pub fn status(&self) -> ShellStatus {
+        if self.error.is_some() || self.config_error.is_some() || self.exit_signal.is_some() {
+            return ShellStatus::Failed;
+        }
+        match self.exit_code {
+            None => ShellStatus::Running,
+            Some(0) => ShellStatus::Done,
+            Some(_) => ShellStatus::Failed,
+        }
+    }
+
+    /// Status line for a finished process: "exit 137", or the signal name
+    /// when it was killed by one. `None` while still running.
+    pub fn exit_text(&self) -> Option<String> {
+        match (&self.exit_signal, self.exit_code) {
+            (Some(sig), _) => Some(format!("signal: {sig}")),
+            (None, Some(code)) => Some(format!("exit {code}")),
+            (None, None) => None,
+        }
     }
 }
 
@@ -61,42 +179,81 @@ fn placeholder_instance(label: &str, error: String) -> ShellInstance {
             rows: 24,
             font_size: None,
             position: Position::BottomRight,
+            format: OutputFormat::Text,
+            fields: config::JsonlFields::default(),
+            path: None,
+            shell: true,
+            stderr: StderrMode::Merge,
+            limits: ResourceLimits::default(),
+            record: None,
+            priority: 0,
         },
         buffer: VecDeque::new(),
         exit_code: None,
+        exit_signal: None,
         last_update: SystemTime::now(),
         error: Some(error),
         resolved_mode: ShellMode::Stream,
         tui_screen: None,
+        plugin_blocks: None,
+        config_error: None,
+        dropped: false,
         spawned_at: Instant::now(),
+        skipped_lines: 0,
+        revision: 0,
     }
 }
 
+/// One line of widget output.
+#[derive(Debug, Clone)]
+pub struct ShellLine {
+    pub text: String,
+    /// Rendered in the error color: stderr with `stderr: separate`, or a
+    /// JSONL error event.
+    pub error: bool,
+}
+
 /// Top-level state for the shell output widget system.
 #[derive(Default)]
 pub struct ShellState {
     pub instances: Vec<ShellInstance>,
     pub most_recent: Option<usize>,
+    /// Bumped by every event.
+    revision: u64,
 }
 
 /// Events sent from the shell background thread to the UI.
 #[derive(Debug, Clone)]
 pub enum ShellEvent {
     /// New output lines for a shell instance (identified by label).
-    Output { label: String, lines: Vec<String> },
+    Output {
+        label: String,
+        lines: Vec<ShellLine>,
+    },
     /// Full TUI screen update (replaces the entire screen snapshot).
     TuiUpdate { label: String, rows: Vec<String> },
+    /// New frame from a plugin widget (replaces its blocks).
+    PluginFrame {
+        label: String,
+        blocks: Vec<plugin::Block>,
+    },
     /// A shell process exited.
     Exited {
         label: String,
         exit_code: Option<i32>,
+        /// Set when the process was killed by a signal.
+        signal: Option<String>,
     },
     /// A shell process failed to spawn.
     Error { label: String, error: String },
+    /// A widget was restarted because its expanded command changed.
+    Respawned { label: String },
     /// Initial config loaded — list of configs to create instances for.
     ConfigLoaded(Vec<ShellConfig>),
     /// Config file changed — new list of configs (UI should reconcile).
     ConfigReloaded(Vec<ShellConfig>),
+    /// Result of checking the config file (sent on every load, may be empty).
+    ConfigIssues(Vec<config::ConfigIssue>),
 }
 
 /// Return the config file path (re-exported for convenience).
@@ -109,10 +266,13 @@ pub fn config_file_path() -> std::path::PathBuf {
 /// 2. Spawns child processes (regular or PTY-based for TUI mode)
 /// 3. Reads their output via per-process reader threads
 /// 4. Watches the config file for changes and reconciles
-pub fn shell_stream() -> impl futures::Stream<Item = ShellEvent> {
-    let (tx, rx) = futures::channel::mpsc::unbounded();
+///
+/// With `demo`, a missing config file runs the demo widgets instead; once
+/// the file is created it is picked up like any other change.
+pub fn shell_stream(demo: bool) -> impl futures::Stream<Item = ShellEvent> {
+    let (tx, rx) = futures::channel::mpsc::channel(EVENT_QUEUE);
     std::thread::spawn(move || {
-        if let Err(e) = shell_thread(tx) {
+        if let Err(e) = shell_thread(tx, demo) {
             eprintln!("[dev-hud] shell thread error: {e}");
         }
     });
@@ -122,21 +282,26 @@ pub fn shell_stream() -> impl futures::Stream<Item = ShellEvent> {
 /// Internal: a managed child process with its reader channel.
 struct ManagedProcess {
     label: String,
-    #[allow(dead_code)]
     config: ShellConfig,
     child: ManagedChild,
     line_rx: mpsc::Receiver<ProcessOutput>,
+    /// Plugin widgets' stdin, for protocol events.
+    stdin: Option<ChildStdin>,
     #[allow(dead_code)]
     spawned_at: Instant,
 }
 
-/// Either a regular Child or a PTY-based child.
+/// Either a regular Child, a PTY-based child, or a FIFO reader (no process).
 enum ManagedChild {
     Regular(Child),
     Pty {
         child: Box<dyn portable_pty::Child + Send>,
         _pair: portable_pty::PtyPair,
     },
+    Fifo {
+        /// Checked by the reader thread between waits for input.
+        stop: Arc<AtomicBool>,
+    },
 }
 
 impl ManagedChild {
@@ -150,24 +315,25 @@ impl ManagedChild {
                 let _ = child.kill();
                 let _ = child.wait();
             }
+            ManagedChild::Fifo { stop } => {
+                // Not woken through the FIFO: a restarted widget on the same
+                // path could read the wake-up instead
+                stop.store(true, Ordering::Relaxed);
+            }
         }
     }
 
     fn try_wait(&mut self) -> Result<Option<portable_pty::ExitStatus>, String> {
         match self {
             ManagedChild::Regular(child) => match child.try_wait() {
-                Ok(Some(status)) => Ok(Some(if status.success() {
-                    portable_pty::ExitStatus::with_exit_code(0)
-                } else {
-                    portable_pty::ExitStatus::with_exit_code(status.code().unwrap_or(1) as u32)
-                })),
-                Ok(None) => Ok(None),
+                Ok(status) => Ok(status.map(portable_pty::ExitStatus::from)),
                 Err(e) => Err(e.to_string()),
             },
             ManagedChild::Pty { child, .. } => match child.try_wait() {
                 Ok(status) => Ok(status),
                 Err(e) => Err(e.to_string()),
             },
+            ManagedChild::Fifo { .. } => Ok(None),
         }
     }
 
@@ -178,6 +344,7 @@ impl ManagedChild {
                 .process_id()
                 .map(|id| id.to_string())
                 .unwrap_or_else(|| "pty".to_string()),
+            ManagedChild::Fifo { .. } => "fifo".to_string(),
         }
     }
 }
@@ -186,61 +353,235 @@ impl ManagedChild {
 enum ProcessOutput {
     /// A single line (for stream/oneshot modes).
     Line(String),
+    /// A single stderr line (regular commands with `stderr: separate`).
+    Stderr(String),
     /// A full TUI screen update (for tui mode).
     Screen(Vec<String>),
 }
 
+/// Convert a raw output line into its display form according to the widget's format.
+fn render_line(cfg: &ShellConfig, line: String, stderr: bool) -> ShellLine {
+    let mut stripped = line;
+    crate::util::strip_ansi_in_place(&mut stripped);
+    let (text, error) = match cfg.format {
+        OutputFormat::Text => (stripped, false),
+        OutputFormat::Jsonl => match jsonl::format_line(&cfg.fields, &stripped) {
+            (text, true) => (format!("\u{f071} {text}"), true),
+            (text, false) => (text, false),
+        },
+    };
+    ShellLine {
+        text,
+        error: error || stderr,
+    }
+}
+
+/// Handle one stdout line: a frame for plugin widgets, a display line
+/// otherwise. Malformed frames become error lines.
+fn read_stdout_line(
+    cfg: &ShellConfig,
+    line: String,
+    lines: &mut Vec<ShellLine>,
+    frame: &mut Option<Vec<plugin::Block>>,
+) {
+    if cfg.mode != Some(ShellMode::Plugin) {
+        lines.push(render_line(cfg, line, false));
+        return;
+    }
+    match plugin::parse_frame(&line) {
+        Ok(blocks) => *frame = Some(blocks),
+        Err(e) => lines.push(ShellLine {
+            text: format!("\u{f071} {e}"),
+            error: true,
+        }),
+    }
+}
+
+/// The program and arguments for `cfg.command`: `sh -c <command>`, or the
+/// command split into argv when `shell: false`.
+fn command_argv(cfg: &ShellConfig) -> Result<Vec<String>, String> {
+    if cfg.shell {
+        return Ok(vec!["sh".into(), "-c".into(), cfg.command.clone()]);
+    }
+    let argv = config::split_args(&cfg.command)
+        .map_err(|e| format!("cannot split '{}': {e}", cfg.command))?;
+    if argv.is_empty() {
+        return Err(format!("widget '{}' has an empty command", cfg.label));
+    }
+    Ok(argv)
+}
+
+/// The variables of our environment that survive `clean_env: true`.
+fn kept_env() -> Vec<(&'static str, std::ffi::OsString)> {
+    config::CLEAN_ENV_KEEP
+        .iter()
+        .filter_map(|&key| std::env::var_os(key).map(|val| (key, val)))
+        .collect()
+}
+
+/// Apply nice level and rlimits to process `pid` (0 = the calling process).
+/// Only makes raw syscalls, so it is safe to call between fork and exec.
+fn apply_limits(pid: libc::pid_t, limits: &ResourceLimits) -> std::io::Result<()> {
+    let set_limit = |resource, value: u64| {
+        let lim = libc::rlimit {
+            rlim_cur: value as libc::rlim_t,
+            rlim_max: value as libc::rlim_t,
+        };
+        // SAFETY: `lim` is a valid rlimit; a null old-limit pointer is allowed.
+        if unsafe { libc::prlimit(pid, resource, &lim, std::ptr::null_mut()) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    if let Some(secs) = limits.cpu_secs {
+        set_limit(libc::RLIMIT_CPU, secs)?;
+    }
+    if let Some(bytes) = limits.memory_bytes {
+        set_limit(libc::RLIMIT_AS, bytes)?;
+    }
+    if let Some(nice) = limits.nice {
+        // SAFETY: plain syscall on a pid we own.
+        if unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Forward each line of `source` to the process channel until EOF.
+fn spawn_line_reader(
+    source: impl std::io::Read + Send + 'static,
+    line_tx: mpsc::SyncSender<ProcessOutput>,
+    stderr: bool,
+    label: String,
+) {
+    std::thread::spawn(move || {
+        let mut reader = std::io::BufReader::new(source);
+        // One read buffer for the whole stream; each line is copied out once
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+            let l = crate::util::line_from_bytes(&buf);
+            let out = if stderr {
+                ProcessOutput::Stderr(l)
+            } else {
+                ProcessOutput::Line(l)
+            };
+            if line_tx.send(out).is_err() {
+                break;
+            }
+        }
+        drop(line_tx);
+        if !stderr {
+            eprintln!("[dev-hud] shell reader done: {label}");
+        }
+    });
+}
+
 /// Spawn a shell command, returning the managed process.
 fn spawn_shell(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
-    let is_tui = cfg.mode == Some(ShellMode::Tui);
-
-    if is_tui {
-        spawn_tui(cfg)
-    } else {
-        spawn_regular(cfg)
+    match cfg.mode {
+        Some(ShellMode::Tui) => spawn_tui(cfg),
+        Some(ShellMode::Fifo) => spawn_fifo(cfg),
+        _ => spawn_regular(cfg),
     }
 }
 
 /// Spawn a regular (non-PTY) shell command.
 fn spawn_regular(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
-    let mut child = Command::new("sh")
-        .args(["-c", &format!("{} 2>&1", cfg.command)])
-        .stdout(Stdio::piped())
-        .stdin(Stdio::null())
-        .stderr(Stdio::null())
+    use std::os::unix::process::CommandExt;
+
+    let plugin = cfg.mode == Some(ShellMode::Plugin);
+    let argv = command_argv(cfg)?;
+    let mut cmd = Command::new(&argv[0]);
+    cmd.args(&argv[1..]).stdin(if plugin {
+        Stdio::piped()
+    } else {
+        Stdio::null()
+    });
+    if cfg.limits.clean_env {
+        cmd.env_clear();
+        for (key, val) in kept_env() {
+            cmd.env(key, val);
+        }
+    }
+    let limits = cfg.limits;
+    // SAFETY: apply_limits only makes async-signal-safe syscalls.
+    unsafe {
+        cmd.pre_exec(move || apply_limits(0, &limits));
+    }
+
+    // Merged output shares one pipe so stdout/stderr ordering is preserved.
+    // A plugin's stdout carries frames only, so its stderr goes to our log.
+    let merged = match cfg.stderr {
+        StderrMode::Merge | StderrMode::Separate if plugin => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::inherit());
+            None
+        }
+        StderrMode::Merge => {
+            let (reader, writer) =
+                std::io::pipe().map_err(|e| format!("failed to create pipe: {e}"))?;
+            let err_writer = writer
+                .try_clone()
+                .map_err(|e| format!("failed to clone pipe: {e}"))?;
+            cmd.stdout(writer).stderr(err_writer);
+            Some(reader)
+        }
+        StderrMode::Separate => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+            None
+        }
+        StderrMode::Hide => {
+            cmd.stdout(Stdio::piped()).stderr(Stdio::null());
+            None
+        }
+    };
+
+    let mut child = cmd
         .spawn()
         .map_err(|e| format!("failed to spawn '{}': {e}", cfg.command))?;
+    // Close our copies of the pipe's write end, or the reader never sees EOF
+    drop(cmd);
 
-    let stdout = child
-        .stdout
-        .take()
-        .ok_or_else(|| "failed to capture stdout".to_string())?;
-
-    let (line_tx, line_rx) = mpsc::channel();
+    let (line_tx, line_rx) = mpsc::sync_channel(OUTPUT_QUEUE);
     let label = cfg.label.clone();
 
-    // Per-process reader thread
-    std::thread::spawn(move || {
-        let reader = std::io::BufReader::new(stdout);
-        for line in reader.lines() {
-            match line {
-                Ok(l) => {
-                    if line_tx.send(ProcessOutput::Line(l)).is_err() {
-                        break;
-                    }
-                }
-                Err(_) => break,
-            }
+    // Per-stream reader threads
+    if let Some(stderr) = child.stderr.take() {
+        spawn_line_reader(stderr, line_tx.clone(), true, label.clone());
+    }
+    match merged {
+        Some(reader) => spawn_line_reader(reader, line_tx, false, label),
+        None => {
+            let stdout = child
+                .stdout
+                .take()
+                .ok_or_else(|| "failed to capture stdout".to_string())?;
+            spawn_line_reader(stdout, line_tx, false, label);
         }
-        drop(line_tx);
-        eprintln!("[dev-hud] shell reader done: {label}");
-    });
+    }
+
+    let mut stdin = child.stdin.take();
+    if let Some(pipe) = &mut stdin {
+        let hello = plugin::hello_event(&cfg.label, cfg.cols, cfg.lines);
+        if let Err(e) = writeln!(pipe, "{hello}") {
+            eprintln!(
+                "[dev-hud] shell '{}': cannot write to plugin: {e}",
+                cfg.label
+            );
+        }
+    }
 
     Ok(ManagedProcess {
         label: cfg.label.clone(),
         config: cfg.clone(),
         child: ManagedChild::Regular(child),
         line_rx,
+        stdin,
         spawned_at: Instant::now(),
     })
 }
@@ -261,25 +602,55 @@ fn spawn_tui(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
         .openpty(pty_size)
         .map_err(|e| format!("failed to open pty: {e}"))?;
 
-    let mut cmd = CommandBuilder::new("sh");
-    cmd.args(["-c", &cfg.command]);
+    let argv = command_argv(cfg)?;
+    let mut cmd = CommandBuilder::new(&argv[0]);
+    cmd.args(&argv[1..]);
+    if cfg.limits.clean_env {
+        cmd.env_clear();
+        for (key, val) in kept_env() {
+            cmd.env(key, val);
+        }
+    }
     cmd.env("TERM", "xterm-256color");
 
-    let child = pair
+    let mut child = pair
         .slave
         .spawn_command(cmd)
         .map_err(|e| format!("failed to spawn tui '{}': {e}", cfg.command))?;
 
+    // CommandBuilder has no pre_exec hook, so limits go on right after spawn
+    if cfg.limits != ResourceLimits::default()
+        && let Some(pid) = child.process_id()
+        && let Err(e) = apply_limits(pid as libc::pid_t, &cfg.limits)
+    {
+        let _ = child.kill();
+        return Err(format!("failed to apply limits to '{}': {e}", cfg.label));
+    }
+
     let mut reader = pair
         .master
         .try_clone_reader()
         .map_err(|e| format!("failed to clone pty reader: {e}"))?;
 
-    let (line_tx, line_rx) = mpsc::channel();
+    let (line_tx, line_rx) = mpsc::sync_channel(OUTPUT_QUEUE);
     let label = cfg.label.clone();
     let rows = cfg.rows;
     let cols = cfg.cols;
 
+    // A recording failure is logged but never stops the widget
+    let mut cast = cfg.record.as_deref().and_then(|dir| {
+        match cast::CastWriter::create(dir, &label, cols, rows) {
+            Ok((writer, path)) => {
+                eprintln!("[dev-hud] shell '{label}': recording to {path:?}");
+                Some(writer)
+            }
+            Err(e) => {
+                eprintln!("[dev-hud] shell '{label}': cannot record to {dir:?}: {e}");
+                None
+            }
+        }
+    });
+
     // PTY reader thread: reads raw bytes, feeds to vt100 parser, extracts screen rows
     std::thread::spawn(move || {
         let mut parser = vt100::Parser::new(rows as u16, cols as u16, 0);
@@ -288,6 +659,12 @@ fn spawn_tui(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
             match reader.read(&mut buf) {
                 Ok(0) => break,
                 Ok(n) => {
+                    if let Some(writer) = &mut cast
+                        && let Err(e) = writer.output(&buf[..n])
+                    {
+                        eprintln!("[dev-hud] shell '{label}': recording stopped: {e}");
+                        cast = None;
+                    }
                     parser.process(&buf[..n]);
                     let screen = parser.screen();
                     let screen_rows: Vec<String> = (0..rows)
@@ -314,53 +691,144 @@ fn spawn_tui(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
         config: cfg.clone(),
         child: ManagedChild::Pty { child, _pair: pair },
         line_rx,
+        stdin: None,
+        spawned_at: Instant::now(),
+    })
+}
+
+/// Create the named pipe at `path` (and its parent dirs) unless it already exists.
+fn ensure_fifo(path: &Path) -> Result<(), String> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::FileTypeExt;
+
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.file_type().is_fifo() => Ok(()),
+        Ok(_) => Err(format!("{} exists and is not a fifo", path.display())),
+        Err(_) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("cannot create {}: {e}", parent.display()))?;
+            }
+            let c_path = std::ffi::CString::new(path.as_os_str().as_bytes())
+                .map_err(|_| format!("invalid fifo path {}", path.display()))?;
+            // SAFETY: c_path is a valid NUL-terminated string for the duration of the call.
+            if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+                return Err(format!(
+                    "mkfifo {}: {}",
+                    path.display(),
+                    std::io::Error::last_os_error()
+                ));
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Open a named pipe and stream every non-empty line written to it,
+/// prefixed with the local time it arrived.
+fn spawn_fifo(cfg: &ShellConfig) -> Result<ManagedProcess, String> {
+    use std::os::fd::AsFd;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let path = cfg
+        .path
+        .clone()
+        .ok_or_else(|| format!("fifo widget '{}' has no path", cfg.label))?;
+    ensure_fifo(&path)?;
+
+    // Opening read+write keeps a writer on our side: the open doesn't block
+    // until a script connects, and reads don't hit EOF when writers go away.
+    // Non-blocking, so the reader can wait with a timeout and notice `stop`.
+    let file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NONBLOCK)
+        .open(&path)
+        .map_err(|e| format!("failed to open fifo {}: {e}", path.display()))?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let (line_tx, line_rx) = mpsc::sync_channel(OUTPUT_QUEUE);
+    let label = cfg.label.clone();
+    let reader_stop = stop.clone();
+
+    std::thread::spawn(move || {
+        let mut pending = Vec::new();
+        let mut chunk = [0u8; 4096];
+        'read: while !reader_stop.load(Ordering::Relaxed) {
+            match crate::util::wait_readable(file.as_fd(), FIFO_STOP_CHECK) {
+                Ok(true) if !reader_stop.load(Ordering::Relaxed) => {}
+                Ok(_) => continue,
+                Err(_) => break,
+            }
+            match (&file).read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => pending.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => continue,
+                Err(_) => break,
+            }
+            while let Some(end) = pending.iter().position(|&b| b == b'\n') {
+                let buf: Vec<u8> = pending.drain(..=end).collect();
+                let l = crate::util::line_from_bytes(&buf);
+                if l.trim().is_empty() {
+                    continue;
+                }
+                let stamped = format!(
+                    "{} {l}",
+                    crate::util::clock_time(std::time::SystemTime::now())
+                );
+                if line_tx.send(ProcessOutput::Line(stamped)).is_err() {
+                    break 'read;
+                }
+            }
+        }
+        drop(line_tx);
+        eprintln!("[dev-hud] fifo reader done: {label}");
+    });
+
+    Ok(ManagedProcess {
+        label: cfg.label.clone(),
+        config: cfg.clone(),
+        child: ManagedChild::Fifo { stop },
+        line_rx,
+        stdin: None,
         spawned_at: Instant::now(),
     })
 }
 
 /// Main shell management thread.
-fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Result<(), String> {
+fn shell_thread(mut tx: EventSender, demo: bool) -> Result<(), String> {
     let config_path = config::config_file_path();
 
     // Read initial config
-    let content = std::fs::read_to_string(&config_path)
-        .map_err(|e| format!("cannot read {}: {e}", config_path.display()))?;
-    let configs = config::parse_config(&content);
+    let (content, source) = match std::fs::read_to_string(&config_path) {
+        Ok(content) => (content, config_path.display().to_string()),
+        Err(e) if demo && e.kind() == std::io::ErrorKind::NotFound => {
+            (config::DEMO_CONFIG.to_string(), "the demo".to_string())
+        }
+        Err(e) => return Err(format!("cannot read {}: {e}", config_path.display())),
+    };
+    let parsed = config::check_config(&content);
+    let configs = parsed.configs;
 
     eprintln!(
-        "[dev-hud] shell: loaded {} widget(s) from {}",
+        "[dev-hud] shell: loaded {} widget(s) from {source}",
         configs.len(),
-        config_path.display()
     );
+    log_issues(&parsed.issues);
 
     // Send initial config to UI
-    if tx
-        .unbounded_send(ShellEvent::ConfigLoaded(configs.clone()))
-        .is_err()
+    if send_event(&mut tx, ShellEvent::ConfigLoaded(configs.clone())).is_err()
+        || send_event(&mut tx, ShellEvent::ConfigIssues(parsed.issues)).is_err()
     {
         return Ok(());
     }
 
     // Spawn initial processes
     let mut processes: Vec<ManagedProcess> = Vec::new();
+    let mut vars = template_vars();
+    let mut expanded: HashMap<String, String> = HashMap::new();
     for cfg in &configs {
-        match spawn_shell(cfg) {
-            Ok(proc) => {
-                eprintln!(
-                    "[dev-hud] shell: spawned '{}' (pid {})",
-                    cfg.label,
-                    proc.child.id_string()
-                );
-                processes.push(proc);
-            }
-            Err(e) => {
-                eprintln!("[dev-hud] shell: {e}");
-                let _ = tx.unbounded_send(ShellEvent::Error {
-                    label: cfg.label.clone(),
-                    error: e,
-                });
-            }
-        }
+        start_process(cfg, &vars, &mut processes, &mut expanded, &mut tx);
     }
 
     let mut last_configs = configs;
@@ -374,12 +842,17 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
         for proc in &mut processes {
             let mut lines = Vec::new();
             let mut tui_screen: Option<Vec<String>> = None;
+            let mut frame: Option<Vec<plugin::Block>> = None;
 
-            loop {
+            // At most one queue's worth per poll, so a command that never
+            // stops writing can't keep this loop from moving on
+            for _ in 0..OUTPUT_QUEUE {
                 match proc.line_rx.try_recv() {
                     Ok(ProcessOutput::Line(line)) => {
-                        let stripped = crate::util::strip_ansi(&line);
-                        lines.push(stripped);
+                        read_stdout_line(&proc.config, line, &mut lines, &mut frame);
+                    }
+                    Ok(ProcessOutput::Stderr(line)) => {
+                        lines.push(render_line(&proc.config, line, true));
                     }
                     Ok(ProcessOutput::Screen(screen)) => {
                         // For TUI, keep only the latest screen snapshot
@@ -391,71 +864,142 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
             }
 
             if !lines.is_empty()
-                && tx
-                    .unbounded_send(ShellEvent::Output {
+                && send_event(
+                    &mut tx,
+                    ShellEvent::Output {
                         label: proc.label.clone(),
                         lines,
-                    })
-                    .is_err()
+                    },
+                )
+                .is_err()
             {
                 kill_all(&mut processes);
                 return Ok(());
             }
 
             if let Some(rows) = tui_screen
-                && tx
-                    .unbounded_send(ShellEvent::TuiUpdate {
+                && send_event(
+                    &mut tx,
+                    ShellEvent::TuiUpdate {
                         label: proc.label.clone(),
                         rows,
-                    })
-                    .is_err()
+                    },
+                )
+                .is_err()
+            {
+                kill_all(&mut processes);
+                return Ok(());
+            }
+
+            if let Some(blocks) = frame
+                && send_event(
+                    &mut tx,
+                    ShellEvent::PluginFrame {
+                        label: proc.label.clone(),
+                        blocks,
+                    },
+                )
+                .is_err()
             {
                 kill_all(&mut processes);
                 return Ok(());
             }
         }
 
+        // Deliver queued events to plugin widgets
+        let events = std::mem::take(&mut *PLUGIN_EVENTS.lock().unwrap_or_else(|e| e.into_inner()));
+        for (label, event) in events {
+            let Some(proc) = processes.iter_mut().find(|p| p.label == label) else {
+                continue;
+            };
+            let Some(stdin) = &mut proc.stdin else {
+                continue;
+            };
+            if let Err(e) = writeln!(stdin, "{event}") {
+                eprintln!("[dev-hud] shell '{label}': cannot write to plugin: {e}");
+                proc.stdin = None;
+            }
+        }
+
         // Check for exited processes
         let mut i = 0;
         while i < processes.len() {
             match processes[i].child.try_wait() {
                 Ok(Some(status)) => {
                     let label = processes[i].label.clone();
-                    let code = if status.success() {
-                        Some(0)
-                    } else {
-                        // portable_pty ExitStatus doesn't expose code directly for failure,
-                        // but we can check success. Non-success = report as 1.
-                        Some(1)
-                    };
-                    eprintln!("[dev-hud] shell: '{}' exited (code {:?})", label, code);
+                    let signal = status.signal().map(str::to_string);
+                    let code = Some(status.exit_code() as i32);
+                    eprintln!("[dev-hud] shell: '{label}' exited ({status})");
 
-                    // Drain any remaining output
+                    // Drain the remaining output until the readers are done
                     let mut final_lines = Vec::new();
+                    let mut final_frame = None;
+                    let deadline = Instant::now() + EXIT_DRAIN_TIMEOUT;
                     loop {
-                        match processes[i].line_rx.try_recv() {
+                        let wait = deadline.saturating_duration_since(Instant::now());
+                        match processes[i].line_rx.recv_timeout(wait) {
                             Ok(ProcessOutput::Line(line)) => {
-                                final_lines.push(crate::util::strip_ansi(&line));
+                                read_stdout_line(
+                                    &processes[i].config,
+                                    line,
+                                    &mut final_lines,
+                                    &mut final_frame,
+                                );
+                            }
+                            Ok(ProcessOutput::Stderr(line)) => {
+                                final_lines.push(render_line(&processes[i].config, line, true));
                             }
                             Ok(ProcessOutput::Screen(_)) => {
                                 // Ignore final screen updates on exit
                             }
-                            Err(_) => break,
+                            Err(mpsc::RecvTimeoutError::Timeout) => {
+                                eprintln!(
+                                    "[dev-hud] shell: '{label}' output still open after exit, \
+                                     stopped reading"
+                                );
+                                break;
+                            }
+                            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                        }
+                        // Hand over a poll's worth at a time instead of holding it all
+                        if final_lines.len() >= OUTPUT_QUEUE {
+                            let _ = send_event(
+                                &mut tx,
+                                ShellEvent::Output {
+                                    label: label.clone(),
+                                    lines: std::mem::take(&mut final_lines),
+                                },
+                            );
                         }
                     }
                     if !final_lines.is_empty() {
-                        let _ = tx.unbounded_send(ShellEvent::Output {
-                            label: label.clone(),
-                            lines: final_lines,
-                        });
+                        let _ = send_event(
+                            &mut tx,
+                            ShellEvent::Output {
+                                label: label.clone(),
+                                lines: final_lines,
+                            },
+                        );
+                    }
+                    if let Some(blocks) = final_frame {
+                        let _ = send_event(
+                            &mut tx,
+                            ShellEvent::PluginFrame {
+                                label: label.clone(),
+                                blocks,
+                            },
+                        );
                     }
 
-                    if tx
-                        .unbounded_send(ShellEvent::Exited {
+                    if send_event(
+                        &mut tx,
+                        ShellEvent::Exited {
                             label: label.clone(),
                             exit_code: code,
-                        })
-                        .is_err()
+                            signal,
+                        },
+                    )
+                    .is_err()
                     {
                         kill_all(&mut processes);
                         return Ok(());
@@ -475,6 +1019,30 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
             }
         }
 
+        // Respawn widgets whose command expands differently now
+        let current_vars = template_vars();
+        if current_vars != vars {
+            vars = current_vars;
+            for cfg in &last_configs {
+                let command = config::expand_template(&cfg.command, &vars);
+                if expanded.get(&cfg.label) == Some(&command) {
+                    continue;
+                }
+                if let Some(pos) = processes.iter().position(|p| p.label == cfg.label) {
+                    let mut proc = processes.remove(pos);
+                    proc.child.kill_and_wait();
+                }
+                eprintln!("[dev-hud] shell: respawning '{}': {command}", cfg.label);
+                let _ = send_event(
+                    &mut tx,
+                    ShellEvent::Respawned {
+                        label: cfg.label.clone(),
+                    },
+                );
+                start_process(cfg, &vars, &mut processes, &mut expanded, &mut tx);
+            }
+        }
+
         // Periodic config file check
         poll_count += 1;
         if poll_count.is_multiple_of(CONFIG_CHECK_POLLS) {
@@ -485,11 +1053,13 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
             if current_mtime != last_mtime {
                 last_mtime = current_mtime;
                 if let Ok(content) = std::fs::read_to_string(&config_path) {
-                    let new_configs = config::parse_config(&content);
+                    let parsed = config::check_config(&content);
+                    let new_configs = parsed.configs;
                     let diff = config::reconcile(&last_configs, &new_configs);
 
                     // Kill removed processes
                     for label in &diff.removed {
+                        expanded.remove(label);
                         if let Some(pos) = processes.iter().position(|p| &p.label == label) {
                             eprintln!("[dev-hud] shell: killing removed '{label}'");
                             let mut proc = processes.remove(pos);
@@ -508,23 +1078,7 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
 
                     // Spawn added + changed
                     for cfg in diff.added.iter().chain(diff.changed.iter()) {
-                        match spawn_shell(cfg) {
-                            Ok(proc) => {
-                                eprintln!(
-                                    "[dev-hud] shell: spawned '{}' (pid {})",
-                                    cfg.label,
-                                    proc.child.id_string()
-                                );
-                                processes.push(proc);
-                            }
-                            Err(e) => {
-                                eprintln!("[dev-hud] shell: {e}");
-                                let _ = tx.unbounded_send(ShellEvent::Error {
-                                    label: cfg.label.clone(),
-                                    error: e,
-                                });
-                            }
-                        }
+                        start_process(cfg, &vars, &mut processes, &mut expanded, &mut tx);
                     }
 
                     if !diff.added.is_empty()
@@ -537,8 +1091,11 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
                             diff.removed.len(),
                             diff.changed.len()
                         );
-                        let _ = tx.unbounded_send(ShellEvent::ConfigReloaded(new_configs.clone()));
+                        let _ =
+                            send_event(&mut tx, ShellEvent::ConfigReloaded(new_configs.clone()));
                     }
+                    log_issues(&parsed.issues);
+                    let _ = send_event(&mut tx, ShellEvent::ConfigIssues(parsed.issues));
 
                     last_configs = new_configs;
                 }
@@ -549,6 +1106,61 @@ fn shell_thread(tx: futures::channel::mpsc::UnboundedSender<ShellEvent>) -> Resu
     }
 }
 
+/// Spawn `cfg` with its command template expanded, recording the expanded
+/// command. Spawn failures are reported to the UI as `ShellEvent::Error`.
+fn start_process(
+    cfg: &ShellConfig,
+    vars: &BTreeMap<String, String>,
+    processes: &mut Vec<ManagedProcess>,
+    expanded: &mut HashMap<String, String>,
+    tx: &mut EventSender,
+) {
+    let mut cfg = cfg.clone();
+    cfg.command = config::expand_template(&cfg.command, vars);
+    expanded.insert(cfg.label.clone(), cfg.command.clone());
+    match spawn_shell(&cfg) {
+        Ok(proc) => {
+            eprintln!(
+                "[dev-hud] shell: spawned '{}' (pid {})",
+                cfg.label,
+                proc.child.id_string()
+            );
+            processes.push(proc);
+        }
+        Err(e) => {
+            eprintln!("[dev-hud] shell: {e}");
+            let _ = send_event(
+                tx,
+                ShellEvent::Error {
+                    label: cfg.label,
+                    error: e,
+                },
+            );
+        }
+    }
+}
+
+type EventSender = futures::channel::mpsc::Sender<ShellEvent>;
+
+/// Send an event to the UI, waiting while its queue is full.
+fn send_event(
+    tx: &mut EventSender,
+    event: ShellEvent,
+) -> Result<(), futures::channel::mpsc::SendError> {
+    futures::executor::block_on(futures::SinkExt::send(tx, event))
+}
+
+/// Log config check results (the UI only shows errors).
+fn log_issues(issues: &[config::ConfigIssue]) {
+    for issue in issues {
+        let kind = match issue.severity {
+            config::Severity::Warning => "warning",
+            config::Severity::Error => "error",
+        };
+        eprintln!("[dev-hud] shell: config {kind}: {issue}");
+    }
+}
+
 /// Kill all managed child processes.
 fn kill_all(processes: &mut Vec<ManagedProcess>) {
     for proc in processes.iter_mut() {
@@ -569,7 +1181,10 @@ impl ShellState {
             ShellEvent::Output { label, lines } => {
                 if let Some(idx) = self.instances.iter().position(|i| i.config.label == *label) {
                     let inst = &mut self.instances[idx];
-                    for line in lines {
+                    // Lines that would be pushed out right away are never shown
+                    let skipped = lines.len().saturating_sub(MAX_BUFFER_LINES);
+                    inst.skipped_lines += skipped as u64;
+                    for line in &lines[skipped..] {
                         inst.buffer.push_back(line.clone());
                         while inst.buffer.len() > MAX_BUFFER_LINES {
                             inst.buffer.pop_front();
@@ -587,10 +1202,23 @@ impl ShellState {
                     self.most_recent = Some(idx);
                 }
             }
-            ShellEvent::Exited { label, exit_code } => {
+            ShellEvent::PluginFrame { label, blocks } => {
+                if let Some(idx) = self.instances.iter().position(|i| i.config.label == *label) {
+                    let inst = &mut self.instances[idx];
+                    inst.plugin_blocks = Some(blocks.clone());
+                    inst.last_update = SystemTime::now();
+                    self.most_recent = Some(idx);
+                }
+            }
+            ShellEvent::Exited {
+                label,
+                exit_code,
+                signal,
+            } => {
                 if let Some(idx) = self.instances.iter().position(|i| i.config.label == *label) {
                     let inst = &mut self.instances[idx];
                     inst.exit_code = *exit_code;
+                    inst.exit_signal = signal.clone();
 
                     // Auto-detect: if mode was unspecified and exited quickly, mark as oneshot
                     if inst.config.mode.is_none()
@@ -608,6 +1236,20 @@ impl ShellState {
                         .push(placeholder_instance(label, error.clone()));
                 }
             }
+            ShellEvent::Respawned { label } => {
+                if let Some(inst) = self.instances.iter_mut().find(|i| i.config.label == *label) {
+                    inst.buffer.clear();
+                    inst.skipped_lines = 0;
+                    inst.exit_code = None;
+                    inst.exit_signal = None;
+                    inst.error = None;
+                    inst.tui_screen = None;
+                    inst.plugin_blocks = None;
+                    inst.last_update = SystemTime::now();
+                    inst.resolved_mode = inst.config.mode.unwrap_or(ShellMode::Stream);
+                    inst.spawned_at = Instant::now();
+                }
+            }
             ShellEvent::ConfigLoaded(configs) => {
                 self.instances = configs.iter().map(new_instance).collect();
                 self.most_recent = None;
@@ -626,11 +1268,17 @@ impl ShellState {
                             config: cfg.clone(),
                             buffer: existing.buffer.clone(),
                             exit_code: existing.exit_code,
+                            exit_signal: existing.exit_signal.clone(),
                             last_update: existing.last_update,
                             error: existing.error.clone(),
                             resolved_mode: existing.resolved_mode,
                             tui_screen: existing.tui_screen.clone(),
+                            plugin_blocks: existing.plugin_blocks.clone(),
+                            config_error: existing.config_error.clone(),
+                            dropped: false,
                             spawned_at: existing.spawned_at,
+                            skipped_lines: existing.skipped_lines,
+                            revision: existing.revision,
                         });
                     } else {
                         new_instances.push(new_instance(cfg));
@@ -644,6 +1292,134 @@ impl ShellState {
                     self.most_recent = None;
                 }
             }
+            ShellEvent::ConfigIssues(issues) => {
+                // Replace the previous check's results entirely
+                self.instances.retain(|i| !i.dropped);
+                for inst in &mut self.instances {
+                    inst.config_error = None;
+                }
+                for issue in issues {
+                    if issue.severity != config::Severity::Error {
+                        continue;
+                    }
+                    let label = issue.label.as_deref().unwrap_or("shells.md");
+                    let msg = issue.to_string();
+                    if let Some(inst) = self.instances.iter_mut().find(|i| i.config.label == label)
+                    {
+                        inst.config_error = Some(match inst.config_error.take() {
+                            Some(prev) => format!("{prev}; {msg}"),
+                            None => msg,
+                        });
+                    } else {
+                        let mut placeholder = placeholder_instance(label, msg);
+                        placeholder.dropped = true;
+                        self.instances.push(placeholder);
+                    }
+                }
+                if let Some(idx) = self.most_recent
+                    && idx >= self.instances.len()
+                {
+                    self.most_recent = None;
+                }
+            }
         }
+
+        // Mark what changed; config events can touch every instance
+        self.revision += 1;
+        let label = match event {
+            ShellEvent::Output { label, .. }
+            | ShellEvent::TuiUpdate { label, .. }
+            | ShellEvent::PluginFrame { label, .. }
+            | ShellEvent::Exited { label, .. }
+            | ShellEvent::Error { label, .. }
+            | ShellEvent::Respawned { label } => Some(label),
+            _ => None,
+        };
+        for inst in &mut self.instances {
+            if label.is_none_or(|label| *label == inst.config.label) {
+                inst.revision = self.revision;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: String) -> ShellLine {
+        ShellLine { text, error: false }
+    }
+
+    fn output(lines: impl Iterator<Item = usize>) -> ShellEvent {
+        ShellEvent::Output {
+            label: "build".into(),
+            lines: lines.map(|n| line(n.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn lines_past_the_buffer_are_counted_as_skipped() {
+        let mut state = ShellState::default();
+        state
+            .instances
+            .push(placeholder_instance("build", String::new()));
+
+        state.apply_event(&output(0..300));
+        let inst = &state.instances[0];
+        assert_eq!(inst.skipped_lines, (300 - MAX_BUFFER_LINES) as u64);
+        assert_eq!(inst.buffer.len(), MAX_BUFFER_LINES);
+        assert_eq!(inst.buffer.front().unwrap().text, "44");
+        assert_eq!(inst.buffer.back().unwrap().text, "299");
+
+        // Lines scrolled out after being shown don't count
+        state.apply_event(&output(300..310));
+        let inst = &state.instances[0];
+        assert_eq!(inst.skipped_lines, 44);
+        assert_eq!(inst.buffer.back().unwrap().text, "309");
+    }
+
+    #[test]
+    fn restarted_fifo_gets_every_line() {
+        let path = std::env::temp_dir().join(format!("dev-hud-fifo-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let mut cfg = placeholder_instance("feed", String::new()).config;
+        cfg.mode = Some(ShellMode::Fifo);
+        cfg.path = Some(path.clone());
+
+        let mut old = spawn_fifo(&cfg).unwrap();
+        old.child.kill_and_wait();
+        let new = spawn_fifo(&cfg).unwrap();
+
+        std::fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"hello\n")
+            .unwrap();
+        match new.line_rx.recv_timeout(Duration::from_secs(2)) {
+            Ok(ProcessOutput::Line(line)) => assert!(line.ends_with(" hello"), "{line}"),
+            _ => panic!("no line from the new reader"),
+        }
+        // The old reader stopped without taking anything
+        assert!(matches!(
+            old.line_rx.recv_timeout(Duration::from_secs(2)),
+            Err(mpsc::RecvTimeoutError::Disconnected)
+        ));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn exit_text_shows_the_code_or_signal() {
+        let mut inst = placeholder_instance("build", String::new());
+        assert_eq!(inst.exit_text(), None);
+
+        inst.exit_code = Some(2);
+        assert_eq!(inst.exit_text().as_deref(), Some("exit 2"));
+
+        // A signal wins over the code the shell reports for it
+        inst.exit_code = Some(137);
+        inst.exit_signal = Some("Killed".into());
+        assert_eq!(inst.exit_text().as_deref(), Some("signal: Killed"));
     }
 }
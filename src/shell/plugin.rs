@@ -0,0 +1,164 @@
+//! Plugin widgets (`mode: plugin`): an executable that draws its own widget.
+//!
+//! The plugin writes one JSON frame per line to stdout; each frame replaces
+//! the widget's content:
+//!
+//! ```json
+//! {"blocks": [{"text": "build ok", "color": "#a6e3a1"}, {"text": "[rerun]", "action": "rerun"}]}
+//! ```
+//!
+//! Every block is one row. `color` is `#rrggbb[aa]`, `muted` or `error`;
+//! blocks with an `action` are clickable in focused mode. dev-hud writes
+//! events back to the plugin's stdin, one JSON object per line:
+//!
+//! ```json
+//! {"event": "hello", "protocol": 1, "widget": "ci", "cols": 120, "lines": 16}
+//! {"event": "click", "action": "rerun"}
+//! ```
+//!
+//! A line that isn't a JSON object is shown as a single plain block, so `echo` works
+//! for a first version of a plugin.
+
+use serde_json::{Value, json};
+
+/// Protocol version announced in the hello event.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Color of a block: an explicit RGBA, or one of the theme's colors.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockColor {
+    Rgba([f32; 4]),
+    Muted,
+    Error,
+}
+
+/// One row of plugin output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    pub text: String,
+    pub color: Option<BlockColor>,
+    /// Sent back in a click event when the block is clicked.
+    pub action: Option<String>,
+}
+
+/// Parse one line of plugin stdout into the blocks of a frame.
+pub fn parse_frame(line: &str) -> Result<Vec<Block>, String> {
+    let Ok(value @ Value::Object(_)) = serde_json::from_str::<Value>(line) else {
+        return Ok(vec![Block {
+            text: clean_text(line),
+            color: None,
+            action: None,
+        }]);
+    };
+    let blocks = value
+        .get("blocks")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "plugin frame has no \"blocks\" array".to_string())?;
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(i, b)| parse_block(i, b))
+        .collect()
+}
+
+fn parse_block(index: usize, block: &Value) -> Result<Block, String> {
+    let text = match block.get("text") {
+        Some(Value::String(s)) => clean_text(s),
+        Some(_) => return Err(format!("block {index}: \"text\" must be a string")),
+        None => String::new(),
+    };
+    let color = match block.get("color") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) => Some(match s.as_str() {
+            "muted" => BlockColor::Muted,
+            "error" => BlockColor::Error,
+            hex => BlockColor::Rgba(
+                crate::hud_config::parse_color(hex)
+                    .ok_or_else(|| format!("block {index}: invalid color {hex:?}"))?,
+            ),
+        }),
+        Some(_) => return Err(format!("block {index}: \"color\" must be a string")),
+    };
+    let action = match block.get("action") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(s)) if !s.is_empty() => Some(s.clone()),
+        Some(_) => {
+            return Err(format!(
+                "block {index}: \"action\" must be a non-empty string"
+            ));
+        }
+    };
+    Ok(Block {
+        text,
+        color,
+        action,
+    })
+}
+
+/// Strip escape sequences and keep the block on one row.
+fn clean_text(text: &str) -> String {
+    crate::util::strip_ansi(text).replace(['\n', '\r', '\t'], " ")
+}
+
+/// The event sent to a plugin once it has started.
+pub fn hello_event(widget: &str, cols: usize, lines: usize) -> String {
+    json!({
+        "event": "hello",
+        "protocol": PROTOCOL_VERSION,
+        "widget": widget,
+        "cols": cols,
+        "lines": lines,
+    })
+    .to_string()
+}
+
+/// The event sent when a block with `action` is clicked.
+pub fn click_event(action: &str) -> String {
+    json!({"event": "click", "action": action}).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_and_plain_lines() {
+        let blocks = parse_frame(
+            r##"{"blocks":[{"text":"ok","color":"#ff0000"},{"text":"[x]","action":"stop","color":"muted"},{}]}"##,
+        )
+        .unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[0].color,
+            Some(BlockColor::Rgba([1.0, 0.0, 0.0, 1.0]))
+        );
+        assert_eq!(blocks[1].action.as_deref(), Some("stop"));
+        assert_eq!(blocks[1].color, Some(BlockColor::Muted));
+        assert_eq!(blocks[2].text, "");
+
+        let plain = parse_frame("\x1b[1mhello\x1b[0m\tworld").unwrap();
+        assert_eq!(plain[0].text, "hello world");
+        assert_eq!(plain[0].action, None);
+        assert_eq!(parse_frame("42").unwrap()[0].text, "42");
+        assert_eq!(parse_frame(r#"{"blocks":[]}"#).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn invalid_frames() {
+        assert!(parse_frame(r#"{"text":"no blocks"}"#).is_err());
+        assert!(parse_frame(r#"{"blocks":[{"text":1}]}"#).is_err());
+        let err = parse_frame(r#"{"blocks":[{"text":"a"},{"color":"red"}]}"#).unwrap_err();
+        assert_eq!(err, "block 1: invalid color \"red\"");
+        assert!(parse_frame(r#"{"blocks":[{"action":""}]}"#).is_err());
+    }
+
+    #[test]
+    fn events() {
+        let click: Value = serde_json::from_str(&click_event("rerun")).unwrap();
+        assert_eq!(click, json!({"event": "click", "action": "rerun"}));
+        let hello: Value = serde_json::from_str(&hello_event("ci", 80, 4)).unwrap();
+        assert_eq!(hello["protocol"], 1);
+        assert_eq!(hello["widget"], "ci");
+        assert_eq!(hello["lines"], 4);
+    }
+}
@@ -0,0 +1,47 @@
+//! `snapshot <path>`: the HUD surface as rendered by iced, saved as a PNG
+//! with its transparency, for sharing a setup or attaching to bug reports.
+
+use std::path::Path;
+
+use iced::window::Screenshot;
+
+/// Encode `screenshot` as a PNG at `path`.
+pub fn save(path: &Path, screenshot: &Screenshot) -> Result<(), String> {
+    if path
+        .extension()
+        .is_none_or(|ext| !ext.eq_ignore_ascii_case("png"))
+    {
+        return Err(format!("{}: expected a .png path", path.display()));
+    }
+    let size = screenshot.size;
+    image::save_buffer(
+        path,
+        &screenshot.rgba,
+        size.width,
+        size.height,
+        image::ColorType::Rgba8,
+    )
+    .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saves_rgba_png() {
+        let dir = std::env::temp_dir().join(format!("dev-hud-snapshot-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pixels = vec![255, 0, 0, 128, 0, 255, 0, 255];
+        let screenshot = Screenshot::new(pixels.clone(), iced::Size::new(2, 1), 1.0);
+
+        let path = dir.join("hud.png");
+        save(&path, &screenshot).unwrap();
+        let image = image::open(&path).unwrap().into_rgba8();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.into_raw(), pixels);
+
+        assert!(save(&dir.join("hud.jpg"), &screenshot).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
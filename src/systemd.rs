@@ -0,0 +1,169 @@
+//! systemd integration: `sd_notify` readiness/watchdog pings and the
+//! `--install-service` unit writer.
+
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::instance;
+
+/// Watchdog timeout written into the generated unit.
+const WATCHDOG_SEC: u32 = 30;
+
+/// Send a state string ("READY=1", "WATCHDOG=1", ...) to the service
+/// manager. A no-op when not started by systemd with `Type=notify`.
+pub(crate) fn notify(state: &str) {
+    let Some(path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return;
+    };
+    let result = UnixDatagram::unbound().and_then(|socket| {
+        // '@' marks a socket in the abstract namespace
+        match path.as_bytes().strip_prefix(b"@") {
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+                socket.send_to_addr(state.as_bytes(), &addr)
+            }
+            None => socket.send_to(state.as_bytes(), &path),
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("[dev-hud] sd_notify {state:?}: {e}");
+    }
+}
+
+/// How often to send `WATCHDOG=1`: half the timeout systemd passed in
+/// `WATCHDOG_USEC`, or None if the watchdog isn't enabled for this process.
+pub(crate) fn watchdog_interval() -> Option<Duration> {
+    watchdog_interval_from(
+        std::env::var("WATCHDOG_USEC").ok().as_deref(),
+        std::env::var("WATCHDOG_PID").ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn watchdog_interval_from(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if pid.is_some_and(|p| p.parse() != Ok(own_pid)) {
+        return None;
+    }
+    let usec: u64 = usec?.parse().ok().filter(|&u| u > 0)?;
+    Some(Duration::from_micros(usec / 2))
+}
+
+/// The user unit for running `exe` as a notify service with a watchdog.
+/// `screen` pins the output via `DEV_HUD_SCREEN`; `instance` is passed on
+/// as `--instance`.
+fn unit_file(exe: &Path, screen: Option<&str>, instance: Option<&str>) -> String {
+    let environment = screen
+        .map(|s| format!("Environment=DEV_HUD_SCREEN={s}\n"))
+        .unwrap_or_default();
+    let args = instance
+        .map(|name| format!(" --instance {name}"))
+        .unwrap_or_default();
+    format!(
+        "[Unit]
+Description=dev-hud — developer heads-up display overlay
+After=graphical-session.target
+PartOf=graphical-session.target
+
+[Service]
+Type=notify
+NotifyAccess=main
+{environment}ExecStart={exe}{args}
+Restart=on-failure
+RestartSec=3
+WatchdogSec={WATCHDOG_SEC}
+
+[Install]
+WantedBy=graphical-session.target
+",
+        exe = exe.display()
+    )
+}
+
+/// `dev-hud.service`, or `dev-hud-<instance>.service`.
+pub(crate) fn unit_name() -> String {
+    format!("{}.service", instance::suffixed("dev-hud"))
+}
+
+fn unit_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("/tmp"))
+        .join("systemd/user")
+        .join(unit_name())
+}
+
+/// Handle `dev-hud --install-service`: write the user unit for this binary
+/// (never overwriting one). Returns the process exit code.
+pub(crate) fn install_service() -> i32 {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("cannot locate the dev-hud binary: {e}");
+            return 1;
+        }
+    };
+    let path = unit_path();
+    if path.exists() || path.is_symlink() {
+        eprintln!(
+            "{}: already exists, not overwriting (remove it first to regenerate)",
+            path.display()
+        );
+        return 1;
+    }
+    if let Some(parent) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!("{}: cannot create directory: {e}", parent.display());
+        return 1;
+    }
+    let screen = std::env::var("DEV_HUD_SCREEN")
+        .ok()
+        .filter(|s| !s.is_empty());
+    if let Err(e) = std::fs::write(&path, unit_file(&exe, screen.as_deref(), instance::name())) {
+        eprintln!("{}: cannot write: {e}", path.display());
+        return 1;
+    }
+    println!("{}: wrote user unit for {}", path.display(), exe.display());
+    println!(
+        "enable it with: systemctl --user daemon-reload && systemctl --user enable --now {}",
+        unit_name()
+    );
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_interval_is_half_the_timeout() {
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), None, 42),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), Some("42"), 42),
+            Some(Duration::from_secs(15))
+        );
+        // Meant for another process, disabled, or missing
+        assert_eq!(
+            watchdog_interval_from(Some("30000000"), Some("7"), 42),
+            None
+        );
+        assert_eq!(watchdog_interval_from(Some("0"), None, 42), None);
+        assert_eq!(watchdog_interval_from(None, None, 42), None);
+    }
+
+    #[test]
+    fn unit_uses_notify_and_watchdog() {
+        let unit = unit_file(Path::new("/opt/dev-hud"), Some("DP-2"), None);
+        assert!(unit.contains("Type=notify\n"));
+        assert!(unit.contains("WatchdogSec=30\n"));
+        assert!(unit.contains("Environment=DEV_HUD_SCREEN=DP-2\nExecStart=/opt/dev-hud\n"));
+        let unit = unit_file(Path::new("/opt/dev-hud"), None, Some("work"));
+        assert!(!unit.contains("Environment="));
+        assert!(unit.contains("ExecStart=/opt/dev-hud --instance work\n"));
+    }
+}
@@ -1,5 +1,19 @@
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::{Duration, Instant};
+
 use iced::{Background, Color};
 
+use crate::util;
+
+/// How long a dark↔light switch takes to crossfade.
+pub const TRANSITION_DURATION: Duration = Duration::from_millis(300);
+
 /// How the theme is selected.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ThemeMode {
@@ -13,6 +27,7 @@ pub enum ThemeMode {
 
 /// All colors and font sizes used throughout the HUD, derived from the active theme.
 #[allow(dead_code)] // Modal-related fields retained for future notification/alert modals
+#[derive(Debug, Clone, PartialEq)]
 pub struct ThemeColors {
     pub is_dark: bool,
     // Text
@@ -190,8 +205,43 @@ impl ThemeColors {
         }
     }
 
+    /// Blend towards `other` by `t` (0.0 = self, 1.0 = other). `is_dark` is
+    /// taken from `other` so theme logic sees the destination immediately.
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        // Weighted form is exact at both ends (t = 0 and t = 1)
+        let f = |a: f32, b: f32| a * (1.0 - t) + b * t;
+        let c = |a: Color, b: Color| Color {
+            r: f(a.r, b.r),
+            g: f(a.g, b.g),
+            b: f(a.b, b.b),
+            a: f(a.a, b.a),
+        };
+        Self {
+            is_dark: other.is_dark,
+            marker: c(self.marker, other.marker),
+            muted: c(self.muted, other.muted),
+            hover_text: c(self.hover_text, other.hover_text),
+            error: c(self.error, other.error),
+            approval: c(self.approval, other.approval),
+            modal_bg: c(self.modal_bg, other.modal_bg),
+            detail_bg: c(self.detail_bg, other.detail_bg),
+            selected: c(self.selected, other.selected),
+            hover: c(self.hover, other.hover),
+            hud_backdrop: c(self.hud_backdrop, other.hud_backdrop),
+            marker_size: f(self.marker_size, other.marker_size),
+            widget_text: f(self.widget_text, other.widget_text),
+            modal_title: f(self.modal_title, other.modal_title),
+            modal_text: f(self.modal_text, other.modal_text),
+            label_text: f(self.label_text, other.label_text),
+            info_text: f(self.info_text, other.info_text),
+        }
+    }
+
     #[allow(dead_code)]
-    pub fn modal_bg_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style {
+    pub fn modal_bg_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
         let color = self.modal_bg;
         move |_theme: &iced::Theme| iced::widget::container::Style {
             background: Some(Background::Color(color)),
@@ -200,7 +250,9 @@ impl ThemeColors {
     }
 
     #[allow(dead_code)]
-    pub fn detail_bg_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style {
+    pub fn detail_bg_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
         let color = self.detail_bg;
         move |_theme: &iced::Theme| iced::widget::container::Style {
             background: Some(Background::Color(color)),
@@ -209,7 +261,9 @@ impl ThemeColors {
     }
 
     #[allow(dead_code)]
-    pub fn selected_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style {
+    pub fn selected_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
         let color = self.selected;
         move |_theme: &iced::Theme| iced::widget::container::Style {
             background: Some(Background::Color(color)),
@@ -218,7 +272,7 @@ impl ThemeColors {
     }
 
     #[allow(dead_code)]
-    pub fn hover_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style {
+    pub fn hover_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
         let color = self.hover;
         move |_theme: &iced::Theme| iced::widget::container::Style {
             background: Some(Background::Color(color)),
@@ -226,7 +280,9 @@ impl ThemeColors {
         }
     }
 
-    pub fn hud_backdrop_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style {
+    pub fn hud_backdrop_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
         let color = self.hud_backdrop;
         move |_theme: &iced::Theme| iced::widget::container::Style {
             background: Some(Background::Color(color)),
@@ -237,6 +293,100 @@ impl ThemeColors {
             ..Default::default()
         }
     }
+
+    pub fn tooltip_style(&self) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
+        let color = self.modal_bg;
+        move |_theme: &iced::Theme| iced::widget::container::Style {
+            background: Some(Background::Color(color)),
+            border: iced::Border {
+                radius: 6.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Solid error-colored band for battery/thermal alerts.
+    pub fn alert_band_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
+        let color = Color {
+            a: 0.9,
+            ..self.error
+        };
+        move |_theme: &iced::Theme| iced::widget::container::Style {
+            background: Some(Background::Color(color)),
+            text_color: Some(Color::WHITE),
+            border: iced::Border {
+                radius: 6.0.into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Text sizes multiplied by `scale`; marker size is left to `hud.md`.
+    /// Scaled from the base sizes (shared by both palettes), so repeated
+    /// calls don't drift.
+    pub fn with_text_scale(self, scale: f32) -> Self {
+        let base = ThemeColors::dark();
+        Self {
+            widget_text: base.widget_text * scale,
+            modal_title: base.modal_title * scale,
+            modal_text: base.modal_text * scale,
+            label_text: base.label_text * scale,
+            info_text: base.info_text * scale,
+            ..self
+        }
+    }
+
+    pub fn highlight_style(
+        &self,
+    ) -> impl Fn(&iced::Theme) -> iced::widget::container::Style + use<> {
+        let color = self.approval;
+        move |_theme: &iced::Theme| iced::widget::container::Style {
+            border: iced::Border {
+                color,
+                width: 3.0,
+                radius: 4.0.into(),
+            },
+            ..Default::default()
+        }
+    }
+}
+
+/// An in-progress crossfade from one palette to another.
+pub struct ThemeTransition {
+    from: ThemeColors,
+    to: ThemeColors,
+    started: Instant,
+}
+
+impl ThemeTransition {
+    pub fn new(from: ThemeColors, to: ThemeColors) -> Self {
+        Self {
+            from,
+            to,
+            started: Instant::now(),
+        }
+    }
+
+    /// The palette being transitioned to.
+    pub fn target(&self) -> &ThemeColors {
+        &self.to
+    }
+
+    /// Colors at `now` (eased), and whether the transition has finished.
+    pub fn sample(&self, now: Instant) -> (ThemeColors, bool) {
+        let elapsed = now.saturating_duration_since(self.started);
+        let t = elapsed.as_secs_f32() / TRANSITION_DURATION.as_secs_f32();
+        if t >= 1.0 {
+            return (self.to.clone(), true);
+        }
+        // Smoothstep: gentle start and finish
+        let eased = t * t * (3.0 - 2.0 * t);
+        (self.from.lerp(&self.to, eased), false)
+    }
 }
 
 /// Detect system dark mode using the claude-viz detection cascade.
@@ -318,11 +468,196 @@ pub fn detect_system_dark() -> bool {
     true
 }
 
-/// Sample background luminance by capturing a screenshot and computing the
-/// average perceptual luminance of the bottom-left quadrant (where HUD sessions
-/// render). Tries `grim` first, falls back to `cosmic-screenshot`.
-/// Returns None if no screenshot tool is available.
-pub fn sample_bg_luminance() -> Option<f32> {
+/// How often the reader of a watched directory checks whether to stop.
+const WATCH_STOP_CHECK: Duration = Duration::from_secs(1);
+
+/// A place the current wallpaper can be read from. Sources with change
+/// events are watched (see `watch`); the others are polled, and a changed
+/// answer means a new wallpaper.
+pub enum WallpaperSource {
+    /// A CLI query (`swww query`, `wpaperctl all-wallpapers`, ...). These
+    /// daemons' IPC only answers requests, so they are polled.
+    Command(&'static str, &'static [&'static str]),
+    /// A CLI query plus a command that prints a line on every change
+    /// (`gsettings monitor`).
+    Monitor {
+        cmd: &'static str,
+        query: &'static [&'static str],
+        monitor: &'static [&'static str],
+    },
+    /// A config directory rewritten on change (COSMIC background), watched
+    /// with inotify.
+    Dir(std::path::PathBuf),
+}
+
+/// A running change watch; dropping it stops the watch.
+pub struct WallpaperWatch {
+    monitor: Option<std::process::Child>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for WallpaperWatch {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(child) = &mut self.monitor {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl WallpaperSource {
+    /// Sources that answer right now. Spawns CLI tools synchronously.
+    pub fn detect() -> Vec<Self> {
+        let mut sources = vec![
+            WallpaperSource::Command("swww", &["query"]),
+            WallpaperSource::Command("wpaperctl", &["all-wallpapers"]),
+            WallpaperSource::Command("hyprctl", &["hyprpaper", "listactive"]),
+            WallpaperSource::Monitor {
+                cmd: "gsettings",
+                query: &["get", "org.gnome.desktop.background", "picture-uri"],
+                monitor: &["monitor", "org.gnome.desktop.background", "picture-uri"],
+            },
+        ];
+        if let Some(home) = dirs::home_dir() {
+            sources.push(WallpaperSource::Dir(
+                home.join(".config/cosmic/com.system76.CosmicBackground/v1"),
+            ));
+        }
+        sources.retain(|s| s.query().is_some());
+        sources
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            WallpaperSource::Command(cmd, _) | WallpaperSource::Monitor { cmd, .. } => {
+                cmd.to_string()
+            }
+            WallpaperSource::Dir(path) => path.display().to_string(),
+        }
+    }
+
+    /// Start watching for changes, sending on `changed` for each. None
+    /// when the source has no change events (or the watch can't start)
+    /// and has to be polled with `query` instead.
+    pub fn watch(&self, changed: mpsc::Sender<()>) -> Option<WallpaperWatch> {
+        let stop = Arc::new(AtomicBool::new(false));
+        match self {
+            WallpaperSource::Command(..) => None,
+            WallpaperSource::Monitor { cmd, monitor, .. } => {
+                let mut child = std::process::Command::new(cmd)
+                    .args(*monitor)
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::piped())
+                    .stderr(std::process::Stdio::null())
+                    .spawn()
+                    .ok()?;
+                let stdout = child.stdout.take()?;
+                // Ends when the watch is dropped and the monitor killed
+                std::thread::spawn(move || {
+                    for _ in BufReader::new(stdout).lines().map_while(Result::ok) {
+                        if changed.send(()).is_err() {
+                            break;
+                        }
+                    }
+                });
+                Some(WallpaperWatch {
+                    monitor: Some(child),
+                    stop,
+                })
+            }
+            WallpaperSource::Dir(path) => {
+                let inotify = watch_dir(path)?;
+                let stop_reader = stop.clone();
+                std::thread::spawn(move || {
+                    let mut buf = [0u8; 4096];
+                    while !stop_reader.load(Ordering::Relaxed) {
+                        match util::wait_readable(inotify.as_fd(), WATCH_STOP_CHECK) {
+                            Ok(false) => continue,
+                            Ok(true) => {}
+                            Err(_) => break,
+                        }
+                        match (&inotify).read(&mut buf) {
+                            Ok(0) => break,
+                            Ok(_) => {
+                                if changed.send(()).is_err() {
+                                    break;
+                                }
+                            }
+                            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
+                            Err(_) => break,
+                        }
+                    }
+                });
+                Some(WallpaperWatch {
+                    monitor: None,
+                    stop,
+                })
+            }
+        }
+    }
+
+    /// The current wallpaper state, or None if the source is unavailable.
+    pub fn query(&self) -> Option<String> {
+        match self {
+            WallpaperSource::Command(cmd, args)
+            | WallpaperSource::Monitor {
+                cmd, query: args, ..
+            } => {
+                let output = std::process::Command::new(cmd)
+                    .args(*args)
+                    .stderr(std::process::Stdio::null())
+                    .output()
+                    .ok()?;
+                if !output.status.success() || output.stdout.is_empty() {
+                    return None;
+                }
+                Some(String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+            WallpaperSource::Dir(path) => {
+                let mut entries: Vec<String> = std::fs::read_dir(path)
+                    .ok()?
+                    .flatten()
+                    .filter_map(|e| {
+                        let modified = e.metadata().and_then(|m| m.modified()).ok()?;
+                        Some(format!("{:?} {modified:?}", e.file_name()))
+                    })
+                    .collect();
+                entries.sort();
+                Some(entries.join("\n"))
+            }
+        }
+    }
+}
+
+/// An inotify instance watching `dir` for files being written, added,
+/// renamed or removed.
+fn watch_dir(dir: &std::path::Path) -> Option<File> {
+    let path = CString::new(dir.as_os_str().as_bytes()).ok()?;
+    // SAFETY: plain syscall; the returned fd is checked and immediately owned.
+    let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC | libc::IN_NONBLOCK) };
+    if fd < 0 {
+        return None;
+    }
+    // SAFETY: fd is a freshly created descriptor nobody else owns.
+    let inotify = File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+    let mask = libc::IN_CLOSE_WRITE | libc::IN_MOVED_TO | libc::IN_CREATE | libc::IN_DELETE;
+    // SAFETY: a valid inotify fd and a NUL-terminated path.
+    if unsafe { libc::inotify_add_watch(inotify.as_raw_fd(), path.as_ptr(), mask) } < 0 {
+        return None;
+    }
+    Some(inotify)
+}
+
+/// Sample background luminance behind the HUD on `output` (or the first
+/// output). Uses wlr-screencopy directly when the compositor supports it;
+/// otherwise captures a screenshot with `grim` or `cosmic-screenshot` and
+/// averages its bottom-left quadrant (where HUD widgets render).
+/// Returns None if no capture method is available.
+pub fn sample_bg_luminance(output: Option<&str>) -> Option<f32> {
+    if let Some(lum) = crate::screencopy::sample_luminance(output) {
+        return Some(lum);
+    }
     // Try grim first (wlroots compositors: sway, wayfire, etc.)
     if let Some(img) = capture_via_grim() {
         return Some(luminance_bottom_left(&img));
@@ -331,7 +666,9 @@ pub fn sample_bg_luminance() -> Option<f32> {
     if let Some(img) = capture_via_cosmic() {
         return Some(luminance_bottom_left(&img));
     }
-    eprintln!("[dev-hud] adaptive: no screenshot tool found (tried grim, cosmic-screenshot)");
+    eprintln!(
+        "[dev-hud] adaptive: no capture method (no wlr-screencopy; tried grim, cosmic-screenshot)"
+    );
     None
 }
 
@@ -423,3 +760,60 @@ pub fn resolve(mode: ThemeMode) -> ThemeColors {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lerp_endpoints_and_midpoint() {
+        let dark = ThemeColors::dark();
+        let light = ThemeColors::light();
+        assert_eq!(dark.lerp(&light, 0.0).marker, dark.marker);
+        assert_eq!(dark.lerp(&light, 1.0), light);
+        let mid = dark.lerp(&light, 0.5);
+        assert!((mid.marker.r - (dark.marker.r + light.marker.r) / 2.0).abs() < 1e-6);
+        assert!(!mid.is_dark);
+    }
+
+    #[test]
+    fn text_scale_is_absolute() {
+        let big = ThemeColors::light().with_text_scale(1.5);
+        assert_eq!(big.widget_text, 12.0);
+        assert_eq!(big.marker_size, ThemeColors::light().marker_size);
+        assert!(!big.is_dark);
+        assert_eq!(big.clone().with_text_scale(1.0), ThemeColors::light());
+    }
+
+    #[test]
+    fn transition_finishes_at_target() {
+        let tr = ThemeTransition::new(ThemeColors::dark(), ThemeColors::light());
+        let (colors, done) = tr.sample(tr.started);
+        assert!(!done);
+        assert_eq!(colors.marker, ThemeColors::dark().marker);
+        let (colors, done) = tr.sample(tr.started + TRANSITION_DURATION);
+        assert!(done);
+        assert_eq!(colors, ThemeColors::light());
+    }
+
+    #[test]
+    fn watched_dir_reports_writes() {
+        let dir = std::env::temp_dir().join(format!("dev-hud-wallpaper-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let (tx, rx) = mpsc::channel();
+        let watch = WallpaperSource::Dir(dir.clone()).watch(tx).unwrap();
+
+        std::fs::write(dir.join("output.all"), "/wallpapers/new.png").unwrap();
+        assert!(rx.recv_timeout(Duration::from_secs(2)).is_ok());
+
+        while rx.recv_timeout(Duration::from_millis(100)).is_ok() {}
+
+        // The reader stops once the watch is dropped
+        drop(watch);
+        std::thread::sleep(WATCH_STOP_CHECK * 2);
+        std::fs::write(dir.join("output.all"), "/wallpapers/other.png").unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(500)).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
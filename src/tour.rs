@@ -0,0 +1,156 @@
+//! First-run onboarding tour: tooltips stepping over the parts of the HUD,
+//! shown once and again on `dev-hud-ctl tour`.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::instance;
+
+/// How long a step stays up before the tour moves on by itself.
+pub const STEP_DURATION: Duration = Duration::from_secs(10);
+
+/// Where a step's tooltip is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+pub struct Step {
+    pub title: &'static str,
+    pub body: &'static str,
+    pub anchor: Anchor,
+}
+
+pub const STEPS: &[Step] = &[
+    Step {
+        title: "Corners",
+        body: "The markers frame the HUD. It stays on top of your windows \
+               and lets clicks through until you focus it.",
+        anchor: Anchor::TopLeft,
+    },
+    Step {
+        title: "Widgets",
+        body: "Shell widgets from shells.md live in the corners (or a side \
+               panel, see hud.md). Edits are picked up without a restart.",
+        anchor: Anchor::BottomLeft,
+    },
+    Step {
+        title: "Focus mode",
+        body: "dev-hud-ctl focus makes the HUD clickable: hover cut-off \
+               lines, click plugin actions, Ctrl+scroll to zoom text.",
+        anchor: Anchor::Center,
+    },
+    Step {
+        title: "Alerts",
+        body: "Low battery, heat and rule attention texts show up in a band \
+               up here (hud.md, rules.md).",
+        anchor: Anchor::TopRight,
+    },
+    Step {
+        title: "Next steps",
+        body: "dev-hud config init writes commented configs; \
+               dev-hud-ctl tour shows this again.",
+        anchor: Anchor::BottomRight,
+    },
+];
+
+/// Position in the onboarding tour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tour {
+    index: usize,
+    shown_at: Instant,
+}
+
+impl Tour {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            index: 0,
+            shown_at: now,
+        }
+    }
+
+    pub fn step(&self) -> &'static Step {
+        &STEPS[self.index]
+    }
+
+    /// 1-based step number, for the "2/5" counter.
+    pub fn number(&self) -> usize {
+        self.index + 1
+    }
+
+    /// Move to the next step. Returns false when the tour is over.
+    pub fn advance(&mut self, now: Instant) -> bool {
+        if self.index + 1 >= STEPS.len() {
+            return false;
+        }
+        self.index += 1;
+        self.shown_at = now;
+        true
+    }
+
+    /// Advance once the current step has been up for `STEP_DURATION`.
+    /// Returns false when the tour is over.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if now.saturating_duration_since(self.shown_at) < STEP_DURATION {
+            return true;
+        }
+        self.advance(now)
+    }
+}
+
+/// Marker file written once the tour has been seen, so it runs only on the
+/// first start.
+fn done_path() -> PathBuf {
+    instance::state_dir().join("tour-done")
+}
+
+pub fn seen() -> bool {
+    done_path().exists()
+}
+
+pub fn mark_seen() {
+    let path = done_path();
+    if let Some(parent) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        eprintln!("[dev-hud] tour: {}: {e}", parent.display());
+        return;
+    }
+    if let Err(e) = std::fs::write(&path, "") {
+        eprintln!("[dev-hud] tour: {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_advance_manually_and_on_timeout() {
+        let start = Instant::now();
+        let mut tour = Tour::new(start);
+        assert_eq!(tour.number(), 1);
+        assert!(tour.tick(start + STEP_DURATION - Duration::from_millis(1)));
+        assert_eq!(tour.number(), 1);
+        assert!(tour.tick(start + STEP_DURATION));
+        assert_eq!(tour.number(), 2);
+
+        // A manual step restarts the timer
+        let later = start + STEP_DURATION + Duration::from_secs(3);
+        assert!(tour.advance(later));
+        assert_eq!(tour.number(), 3);
+        assert!(tour.tick(later + Duration::from_secs(9)));
+        assert_eq!(tour.number(), 3);
+
+        while tour.number() < STEPS.len() {
+            assert!(tour.advance(later));
+        }
+        assert_eq!(tour.step().anchor, Anchor::BottomRight);
+        assert!(!tour.advance(later));
+        assert!(!tour.tick(later + STEP_DURATION));
+    }
+}
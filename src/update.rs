@@ -0,0 +1,104 @@
+//! Release check (`# updates` in `hud.md`, or `dev-hud-ctl update check`):
+//! asks GitHub for the latest release and compares it with this build.
+//! Nothing is ever downloaded; a newer version only shows in the info line.
+
+use std::process::Command;
+
+/// Latest published release (pre-releases excluded).
+const RELEASES_URL: &str = "https://api.github.com/repos/Fuabioo/dev-hud/releases/latest";
+
+/// Give up on the request after this long.
+const TIMEOUT_SECS: u32 = 15;
+
+/// A `major.minor.patch` version; a pre-release sorts before its release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version {
+    core: (u64, u64, u64),
+    release: bool,
+}
+
+impl Version {
+    /// Parse `v1.2.3`, `1.2.3-rc.1` or `1.2.3+build`.
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let text = text.strip_prefix('v').unwrap_or(text);
+        let text = text.split_once('+').map_or(text, |(v, _)| v);
+        let (core, pre) = match text.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (text, None),
+        };
+        let mut parts = core.split('.').map(|p| p.parse::<u64>().ok());
+        let version = (parts.next()??, parts.next()??, parts.next()??);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            core: version,
+            release: pre.is_none(),
+        })
+    }
+}
+
+/// `latest` if it is a newer version than `current`. Unparsable versions
+/// (e.g. a build from a branch) never report an update.
+pub fn newer(current: &str, latest: &str) -> Option<String> {
+    let (current_version, latest_version) = (Version::parse(current)?, Version::parse(latest)?);
+    (latest_version > current_version).then(|| latest.trim().to_string())
+}
+
+/// The latest release's tag. Blocks on the request, so call it off the UI
+/// thread.
+pub fn latest_tag() -> Result<String, String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", &TIMEOUT_SECS.to_string()])
+        .args(["--header", "Accept: application/vnd.github+json"])
+        .arg(RELEASES_URL)
+        .output()
+        .map_err(|e| format!("curl: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "curl failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    tag_name(&output.stdout)
+}
+
+/// `tag_name` of a GitHub release response.
+fn tag_name(body: &[u8]) -> Result<String, String> {
+    let release: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| format!("release response: {e}"))?;
+    release
+        .get("tag_name")
+        .and_then(serde_json::Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| "release response has no tag_name".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert_eq!(newer("0.1.0", "v0.2.0").as_deref(), Some("v0.2.0"));
+        assert_eq!(newer("0.1.9", "0.1.10").as_deref(), Some("0.1.10"));
+        assert_eq!(newer("0.2.0-rc.1", "v0.2.0").as_deref(), Some("v0.2.0"));
+        assert_eq!(newer("0.2.0", "v0.2.0"), None);
+        assert_eq!(newer("0.3.0", "v0.2.9"), None);
+        assert_eq!(newer("0.2.0+abc", "v0.2.0"), None);
+        assert_eq!(newer("main", "v0.2.0"), None);
+        assert_eq!(newer("0.1.0", "nightly"), None);
+        assert_eq!(newer("0.1.0", "v1.2"), None);
+    }
+
+    #[test]
+    fn reads_tag_name() {
+        let body = br#"{"tag_name": "v0.4.1", "name": "dev-hud 0.4.1", "prerelease": false}"#;
+        assert_eq!(tag_name(body).unwrap(), "v0.4.1");
+        assert!(tag_name(br#"{"message": "Not Found"}"#).is_err());
+        assert!(tag_name(b"<html>").is_err());
+    }
+}
@@ -1,40 +1,149 @@
-/// UTF-8 safe string truncation by character count.
-/// If the string exceeds `max_chars`, truncates and appends "...".
-/// When `max_chars` is 3 or less, returns exactly `max_chars` characters
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Terminal display width of a string (CJK and most emoji count as 2 columns,
+/// combining marks and zero-width joiners as 0).
+pub fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// Display-width aware string truncation.
+/// If the string is wider than `max_width` columns, truncates and appends "...".
+/// Cuts only on grapheme cluster boundaries, so combining marks (Arabic,
+/// Hebrew, Devanagari vowel signs) and ZWJ emoji sequences are never split.
+/// When `max_width` is 3 or less, returns at most `max_width` columns
 /// without ellipsis (no room for the "..." suffix).
-pub fn truncate_str(s: &str, max_chars: usize) -> String {
-    let char_count = s.chars().count();
-    if char_count <= max_chars {
-        s.to_string()
-    } else if max_chars <= 3 {
-        // Not enough room for "..." — just hard-truncate
-        s.chars().take(max_chars).collect()
+pub fn truncate_str(s: &str, max_width: usize) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+    // Not enough room for "..." — just hard-truncate
+    let (budget, suffix) = if max_width <= 3 {
+        (max_width, "")
     } else {
-        let end = s
-            .char_indices()
-            .nth(max_chars.saturating_sub(3))
-            .map_or(s.len(), |(i, _)| i);
-        format!("{}...", &s[..end])
+        (max_width - 3, "...")
+    };
+    let mut out = String::with_capacity(s.len().min(budget * 4) + suffix.len());
+    let mut used = 0;
+    for g in s.graphemes(true) {
+        let w = g.width();
+        if used + w > budget {
+            break;
+        }
+        used += w;
+        out.push_str(g);
     }
+    out.push_str(suffix);
+    out
 }
 
 /// Strip ANSI escape sequences from a string.
 pub fn strip_ansi(s: &str) -> String {
-    let mut out = String::with_capacity(s.len());
-    let mut chars = s.chars();
-    while let Some(c) = chars.next() {
-        if c == '\x1b' {
+    let mut out = s.to_string();
+    strip_ansi_in_place(&mut out);
+    out
+}
+
+/// Strip ANSI escape sequences without allocating.
+pub fn strip_ansi_in_place(s: &mut String) {
+    let mut in_escape = false;
+    s.retain(|c| {
+        if in_escape {
             // Skip until we hit a letter (end of escape sequence)
-            for esc in chars.by_ref() {
-                if esc.is_ascii_alphabetic() {
-                    break;
-                }
-            }
+            in_escape = !c.is_ascii_alphabetic();
+            false
+        } else if c == '\x1b' {
+            in_escape = true;
+            false
         } else {
-            out.push(c);
+            true
         }
+    });
+}
+
+/// A line read with `BufRead::read_until(b'\n', ..)` as text, without its
+/// `\n`/`\r\n` ending. Invalid UTF-8 is replaced rather than ending the
+/// stream, and valid lines are copied exactly once.
+pub fn line_from_bytes(buf: &[u8]) -> String {
+    let line = buf.strip_suffix(b"\n").unwrap_or(buf);
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// Format a timestamp as local wall-clock time (`HH:MM:SS`).
+pub fn clock_time(t: SystemTime) -> String {
+    let secs = unix_secs(t);
+    let Some(tm) = local_tm(secs) else {
+        // No timezone info available: fall back to UTC
+        let day_secs = secs.rem_euclid(86_400);
+        return format!(
+            "{:02}:{:02}:{:02}",
+            day_secs / 3600,
+            day_secs / 60 % 60,
+            day_secs % 60
+        );
+    };
+    format!("{:02}:{:02}:{:02}", tm.tm_hour, tm.tm_min, tm.tm_sec)
+}
+
+/// The local day (days since the epoch) and minute of that day at `t`,
+/// for once-a-day schedules. Falls back to UTC without timezone info.
+pub fn local_day_minute(t: SystemTime) -> (i64, u32) {
+    let secs = unix_secs(t);
+    // c_long and time_t are only the same width on 64-bit targets
+    #[allow(clippy::unnecessary_cast)]
+    let local = secs as i64 + local_tm(secs).map_or(0, |tm| tm.tm_gmtoff as i64);
+    (
+        local.div_euclid(86_400),
+        (local.rem_euclid(86_400) / 60) as u32,
+    )
+}
+
+fn unix_secs(t: SystemTime) -> libc::time_t {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as libc::time_t)
+        .unwrap_or(0)
+}
+
+fn local_tm(secs: libc::time_t) -> Option<libc::tm> {
+    // SAFETY: `tm` is a plain C struct we own; localtime_r only writes into it.
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+    if unsafe { libc::localtime_r(&secs, &mut tm) }.is_null() {
+        return None;
     }
-    out
+    Some(tm)
+}
+
+/// Current Unix time in seconds.
+pub fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Wait up to `timeout` for `fd` to become readable (or hung up), so a
+/// reader thread can check whether it should stop in between. An
+/// interrupted wait counts as not ready.
+pub fn wait_readable(fd: BorrowedFd<'_>, timeout: Duration) -> std::io::Result<bool> {
+    let mut pfd = libc::pollfd {
+        fd: fd.as_raw_fd(),
+        events: libc::POLLIN,
+        revents: 0,
+    };
+    let millis = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    // SAFETY: one valid pollfd on our stack, for an fd borrowed for the call.
+    if unsafe { libc::poll(&mut pfd, 1, millis) } < 0 {
+        let err = std::io::Error::last_os_error();
+        return match err.kind() {
+            std::io::ErrorKind::Interrupted => Ok(false),
+            _ => Err(err),
+        };
+    }
+    Ok(pfd.revents != 0)
 }
 
 #[cfg(test)]
@@ -112,7 +221,86 @@ mod tests {
 
     #[test]
     fn truncate_str_max_chars_three_multibyte() {
+        // Each CJK glyph is two columns wide, so only one fits in three.
         let result = truncate_str("こんにちは", 3);
-        assert_eq!(result, "こんに");
+        assert_eq!(result, "こ");
+    }
+
+    #[test]
+    fn truncate_str_wide_glyphs_fit_width_budget() {
+        let result = truncate_str("こんにちは世界", 9);
+        assert_eq!(result, "こんに...");
+        assert!(display_width(&result) <= 9);
+    }
+
+    #[test]
+    fn truncate_str_wide_glyph_never_overflows() {
+        // "a" + "こ" would be 3 columns against a budget of 2.
+        assert_eq!(truncate_str("aこんにちは世界", 5), "a...");
+    }
+
+    #[test]
+    fn truncate_str_keeps_combining_marks_attached() {
+        // Hebrew letters with niqqud: each mark is zero-width and must stay
+        // with its base letter.
+        let s = "שָׁלוֹם עוֹלָם וְכָל הַיְּקוּם";
+        let result = truncate_str(s, 8);
+        assert!(result.ends_with("..."));
+        let body = result.trim_end_matches("...");
+        assert!(s.starts_with(body));
+        let next = s[body.len()..].chars().next().unwrap();
+        assert!(!('\u{591}'..='\u{5C7}').contains(&next));
+        assert!(display_width(&result) <= 8);
+    }
+
+    #[test]
+    fn truncate_str_does_not_split_zwj_sequence() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let s = format!("{family}{family}{family}");
+        // A ZWJ family renders as a single 2-column glyph.
+        let result = truncate_str(&s, 5);
+        assert_eq!(result, format!("{family}..."));
+    }
+
+    #[test]
+    fn strips_ansi_and_line_endings() {
+        let mut s = "\x1b[1;31merror\x1b[0m: disk \x1b[4mfull\x1b[0m".to_string();
+        strip_ansi_in_place(&mut s);
+        assert_eq!(s, "error: disk full");
+        assert_eq!(strip_ansi("plain"), "plain");
+        assert_eq!(line_from_bytes(b"crlf\r\n"), "crlf");
+        assert_eq!(line_from_bytes(b"last line"), "last line");
+        assert_eq!(line_from_bytes(b"bad \xff byte\n"), "bad \u{fffd} byte");
+    }
+
+    #[test]
+    fn display_width_counts_columns() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("日本"), 4);
+        assert_eq!(display_width(""), 0);
+    }
+
+    // -----------------------------------------------------------------------
+    // clock_time
+    // -----------------------------------------------------------------------
+
+    #[test]
+    fn clock_time_is_hh_mm_ss() {
+        let t = clock_time(SystemTime::now());
+        assert_eq!(t.len(), 8);
+        let parts: Vec<u32> = t.split(':').map(|p| p.parse().unwrap()).collect();
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0] < 24 && parts[1] < 60 && parts[2] < 61);
+    }
+
+    #[test]
+    fn local_day_minute_matches_clock_time() {
+        let t = SystemTime::now();
+        let (day, minute) = local_day_minute(t);
+        let (next_day, _) = local_day_minute(t + std::time::Duration::from_secs(86_400));
+        assert_eq!(next_day, day + 1);
+        let clock = clock_time(t);
+        let hh_mm = format!("{:02}:{:02}", minute / 60, minute % 60);
+        assert!(clock.starts_with(&hh_mm), "{clock} vs {hh_mm}");
     }
 }
@@ -1,21 +1,62 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::Instant;
+
+use iced::alignment::{Horizontal, Vertical};
 use iced::widget::text::Shaping;
-use iced::widget::{column, container, image as iced_image, row, space, svg, text};
-use iced::{Element, Length};
+use iced::widget::{
+    column, container, image as iced_image, lazy, mouse_area, row, space, stack, svg, text, tooltip,
+};
+use iced::{Background, Color, Element, Font, Length};
 
 use crate::app::{EDGE_MARGIN, Hud, HudMode, Message};
+use crate::calendar::Urgency;
+use crate::hud_config::{Guides, LayoutConfig, LayoutMode, MarkerCount, Overflow};
 use crate::loader::*;
+use crate::measure;
+use crate::power::Alert;
 use crate::shell;
-use crate::util::truncate_str;
+use crate::theme::ThemeColors;
+use crate::tour::{self, Anchor};
+use crate::util::{display_width, truncate_str, unix_now};
+
+/// Tooltips wrap long lines at this width (logical pixels).
+const TOOLTIP_MAX_WIDTH: f32 = 720.0;
+
+/// Width of the onboarding tour's step card (logical pixels).
+const TOUR_CARD_WIDTH: f32 = 360.0;
 
 impl Hud {
     pub(crate) fn view_hud(&self) -> Element<'_, Message> {
         let mono = self.current_font();
         let shaped = Shaping::Advanced;
         let colors = &self.colors;
-        let marker = || text("+").size(colors.marker_size).color(colors.marker);
+        let markers = &self.hud_config.markers;
+        let marker_color = markers
+            .color
+            .map(|[r, g, b, a]| Color::from_rgba(r, g, b, a))
+            .unwrap_or(colors.marker);
+        let marker_size = markers.size.unwrap_or(colors.marker_size);
+        // Hidden markers stay in the layout (transparent) so rows keep their height
+        let marker = |shown: bool| {
+            text(markers.glyph.as_str())
+                .size(marker_size)
+                .color(if shown {
+                    marker_color
+                } else {
+                    Color::TRANSPARENT
+                })
+                .shaping(shaped)
+        };
+        let diagonal = markers.count != MarkerCount::None;
+        let corners = markers.count.corners();
+        let layout = &self.hud_config.layout;
+        // Left and right corners share a row; leave room for the backdrop padding
+        let corner_width = self
+            .surface_width
+            .map(|w| (w - 2.0 * EDGE_MARGIN as f32) / 2.0 - 12.0);
 
         // Top row: corner markers only
-        let top_row = row![marker(), space::horizontal(), marker()];
+        let top_row = row![marker(diagonal), space::horizontal(), marker(corners)];
 
         // Build bottom row (with optional loader widget)
         let bottom_row = if let Some(loader) = &self.demo_loader {
@@ -24,26 +65,16 @@ impl Hud {
                 .color(colors.muted)
                 .into();
 
-            let widget: Element<'_, Message> = match loader.style {
-                LoaderStyle::Braille | LoaderStyle::Bounce | LoaderStyle::Pipe => {
-                    let frames = loader.style.text_frames();
-                    let ch = frames[loader.frame % frames.len()];
-                    text(format!(" {ch}"))
-                        .size(colors.label_text)
-                        .color(colors.marker)
-                        .font(mono)
-                        .shaping(shaped)
-                        .into()
-                }
-                LoaderStyle::Gif => {
-                    if loader.gif_frames.is_empty() {
+            let widget: Element<'_, Message> = match (loader.style, &loader.assets) {
+                (LoaderStyle::Gif, Some(assets)) => {
+                    if assets.gif_frames.is_empty() {
                         text(" ?")
                             .size(colors.label_text)
                             .color(colors.marker)
                             .into()
                     } else {
                         let handle =
-                            loader.gif_frames[loader.frame % loader.gif_frames.len()].clone();
+                            assets.gif_frames[loader.frame % assets.gif_frames.len()].clone();
                         container(
                             iced_image(handle)
                                 .width(LOADER_IMAGE_SIZE)
@@ -53,15 +84,15 @@ impl Hud {
                         .into()
                     }
                 }
-                LoaderStyle::Svg => {
-                    if loader.svg_frames.is_empty() {
+                (LoaderStyle::Svg, Some(assets)) => {
+                    if assets.svg_frames.is_empty() {
                         text(" ?")
                             .size(colors.label_text)
                             .color(colors.marker)
                             .into()
                     } else {
                         let handle =
-                            loader.svg_frames[loader.frame % loader.svg_frames.len()].clone();
+                            assets.svg_frames[loader.frame % assets.svg_frames.len()].clone();
                         container(
                             svg(handle)
                                 .width(LOADER_IMAGE_SIZE)
@@ -71,11 +102,27 @@ impl Hud {
                         .into()
                     }
                 }
+                _ => {
+                    let frames = loader.text_frames();
+                    let ch = frames[loader.frame % frames.len()];
+                    text(format!(" {ch}"))
+                        .size(colors.label_text)
+                        .color(colors.marker)
+                        .font(mono)
+                        .shaping(shaped)
+                        .into()
+                }
             };
 
-            row![marker(), widget, label, space::horizontal(), marker()]
+            row![
+                marker(corners),
+                widget,
+                label,
+                space::horizontal(),
+                marker(diagonal)
+            ]
         } else {
-            row![marker(), space::horizontal(), marker()]
+            row![marker(corners), space::horizontal(), marker(diagonal)]
         };
 
         // Build main column
@@ -87,11 +134,16 @@ impl Hud {
         // Uses a macro instead of a closure to avoid lifetime issues with
         // iced's Column type (which doesn't implement Default).
         macro_rules! render_shell_inst {
-            ($col:expr, $inst:expr, $full:expr) => {{
+            ($col:expr, $inst:expr, $full:expr, $max_lines:expr) => {{
                 let inst = $inst;
                 let full: bool = $full;
+                let max_lines: usize = $max_lines;
                 let inst_font_size = inst.config.font_size.unwrap_or(colors.widget_text);
-                let inst_cols = inst.config.cols;
+                let inst_cols = layout.fit_cols(
+                    inst.config.cols,
+                    measure::cell_width(mono, inst_font_size),
+                    corner_width,
+                );
                 let icon = "\u{f120}";
 
                 let label_row = row![
@@ -100,7 +152,7 @@ impl Hud {
                         .color(colors.muted)
                         .font(mono)
                         .shaping(shaped),
-                    text(&inst.config.label)
+                    text(inst.config.label.clone())
                         .size(inst_font_size)
                         .color(colors.muted)
                         .font(mono)
@@ -108,9 +160,27 @@ impl Hud {
                 ];
                 $col = $col.push(label_row);
 
+                if let Some(ref cfg_err) = inst.config_error {
+                    let shown = truncate_str(cfg_err, inst_cols.saturating_sub(4));
+                    let err_row = row![
+                        text(format!("  \u{f071} {shown}"))
+                            .size(inst_font_size)
+                            .color(colors.error)
+                            .font(mono)
+                            .shaping(shaped)
+                    ];
+                    $col = $col.push(with_full_text(
+                        err_row,
+                        (full && shown != *cfg_err).then(|| cfg_err.clone()),
+                        colors,
+                        mono,
+                        inst_font_size,
+                    ));
+                }
+
                 if inst.resolved_mode == shell::ShellMode::Tui {
                     if let Some(ref screen) = inst.tui_screen {
-                        for row_str in screen {
+                        for row_str in screen.iter().take(max_lines) {
                             let truncated = truncate_str(row_str, inst_cols);
                             let out_line = row![
                                 text(format!("  {truncated}"))
@@ -131,9 +201,57 @@ impl Hud {
                         ]);
                     }
                     if full {
-                        if let Some(code) = inst.exit_code {
+                        if let Some(status) = inst.exit_text() {
                             $col = $col.push(row![
-                                text(format!("  exit {code}"))
+                                text(format!("  {status}"))
+                                    .size(inst_font_size)
+                                    .color(colors.muted)
+                                    .font(mono)
+                                    .shaping(shaped)
+                            ]);
+                        }
+                    }
+                } else if let Some(ref blocks) = inst.plugin_blocks {
+                    for block in blocks.iter().take(inst.config.lines.min(max_lines)) {
+                        let truncated = truncate_str(&block.text, inst_cols);
+                        let block_color = match block.color {
+                            Some(shell::plugin::BlockColor::Rgba([r, g, b, a])) => {
+                                Color::from_rgba(r, g, b, a)
+                            }
+                            Some(shell::plugin::BlockColor::Muted) => colors.muted,
+                            Some(shell::plugin::BlockColor::Error) => colors.error,
+                            None => colors.marker,
+                        };
+                        let block_row = row![
+                            text(format!("  {truncated}"))
+                                .size(inst_font_size)
+                                .color(block_color)
+                                .font(mono)
+                                .shaping(shaped)
+                        ];
+                        $col = match &block.action {
+                            // Clicks only reach the surface in focused mode
+                            Some(action) if full => $col.push(
+                                mouse_area(block_row)
+                                    .on_press(Message::PluginClick {
+                                        label: inst.config.label.clone(),
+                                        action: action.clone(),
+                                    })
+                                    .interaction(iced::mouse::Interaction::Pointer),
+                            ),
+                            _ => $col.push(with_full_text(
+                                block_row,
+                                (full && truncated != block.text).then(|| block.text.clone()),
+                                colors,
+                                mono,
+                                inst_font_size,
+                            )),
+                        };
+                    }
+                    if full {
+                        if let Some(status) = inst.exit_text() {
+                            $col = $col.push(row![
+                                text(format!("  {status}"))
                                     .size(inst_font_size)
                                     .color(colors.muted)
                                     .font(mono)
@@ -142,21 +260,26 @@ impl Hud {
                         }
                     }
                 } else if let Some(ref err) = inst.error {
-                    $col = $col.push(row![
-                        text(format!(
-                            "  \u{f071} {}",
-                            truncate_str(err, inst_cols.saturating_sub(4))
-                        ))
-                        .size(inst_font_size)
-                        .color(colors.error)
-                        .font(mono)
-                        .shaping(shaped)
-                    ]);
+                    let shown = truncate_str(err, inst_cols.saturating_sub(4));
+                    let err_row = row![
+                        text(format!("  \u{f071} {shown}"))
+                            .size(inst_font_size)
+                            .color(colors.error)
+                            .font(mono)
+                            .shaping(shaped)
+                    ];
+                    $col = $col.push(with_full_text(
+                        err_row,
+                        (full && shown != *err).then(|| err.clone()),
+                        colors,
+                        mono,
+                        inst_font_size,
+                    ));
                 } else if inst.buffer.is_empty() {
                     if full {
-                        if let Some(code) = inst.exit_code {
+                        if let Some(status) = inst.exit_text() {
                             $col = $col.push(row![
-                                text(format!("  exit {code}"))
+                                text(format!("  {status}"))
                                     .size(inst_font_size)
                                     .color(colors.muted)
                                     .font(mono)
@@ -173,22 +296,46 @@ impl Hud {
                         }
                     }
                 } else {
-                    let visible_lines = inst.config.lines;
+                    let visible_lines = inst.config.lines.min(max_lines);
                     let start = inst.buffer.len().saturating_sub(visible_lines);
                     for line in inst.buffer.iter().skip(start) {
-                        let truncated = truncate_str(line, inst_cols);
-                        $col = $col.push(row![
+                        let truncated = truncate_str(&line.text, inst_cols);
+                        let line_color = if line.error {
+                            colors.error
+                        } else {
+                            colors.marker
+                        };
+                        let line_row = row![
                             text(format!("  {truncated}"))
                                 .size(inst_font_size)
-                                .color(colors.marker)
+                                .color(line_color)
                                 .font(mono)
                                 .shaping(shaped)
+                        ];
+                        $col = $col.push(with_full_text(
+                            line_row,
+                            (full && truncated != line.text).then(|| line.text.clone()),
+                            colors,
+                            mono,
+                            inst_font_size,
+                        ));
+                    }
+                    if full && inst.skipped_lines > 0 {
+                        $col = $col.push(row![
+                            text(format!(
+                                "  {} lines skipped (output too fast)",
+                                inst.skipped_lines
+                            ))
+                            .size(inst_font_size)
+                            .color(colors.muted)
+                            .font(mono)
+                            .shaping(shaped)
                         ]);
                     }
                     if full {
-                        if let Some(code) = inst.exit_code {
+                        if let Some(status) = inst.exit_text() {
                             $col = $col.push(row![
-                                text(format!("  exit {code}"))
+                                text(format!("  {status}"))
                                     .size(inst_font_size)
                                     .color(colors.muted)
                                     .font(mono)
@@ -200,7 +347,8 @@ impl Hud {
             }};
         }
 
-        // Build a shell widget Element for a given screen position.
+        // Build the widget Element for a given screen position: the calendar
+        // countdown (if placed there) followed by shell widgets.
         // In focused mode all instances at that position render fully.
         // In unfocused mode only `visible: always` instances render (plus
         // a single most-recent line for non-always widgets in bottom-right).
@@ -209,19 +357,79 @@ impl Hud {
         macro_rules! build_position_widget {
             ($pos:expr) => {{
                 let pos = $pos;
-                let widget: Element<'_, Message> = if let Some(shells) = &self.shells {
-                    let mut col = column![];
-                    let mut has_content = false;
+                let mut col = column![];
+                let mut has_content = false;
 
-                    for inst in &shells.instances {
-                        if inst.config.position != pos {
-                            continue;
-                        }
-                        if focused {
-                            render_shell_inst!(col, inst, true);
-                            has_content = true;
-                        } else if inst.config.visible == shell::Visibility::Always {
-                            render_shell_inst!(col, inst, false);
+                // Calendar countdown first, colored by how close the meeting is
+                if let Some(up) = &self.calendar
+                    && self.hud_config.calendar.position == pos
+                {
+                    let (countdown, urgency) = up.countdown(unix_now());
+                    let color = match urgency {
+                        Urgency::Later => colors.muted,
+                        Urgency::Soon => colors.marker,
+                        Urgency::Imminent => colors.approval,
+                        Urgency::Now => colors.error,
+                    };
+                    col = col.push(row![
+                        text("\u{f073} ")
+                            .size(colors.widget_text)
+                            .color(colors.muted)
+                            .font(mono)
+                            .shaping(shaped),
+                        text(countdown)
+                            .size(colors.widget_text)
+                            .color(color)
+                            .font(mono)
+                            .shaping(shaped),
+                    ]);
+                    has_content = true;
+                }
+
+                // Shell widgets shown here, highest priority first
+                let mut placed: Vec<&shell::ShellInstance> = self
+                    .shells
+                    .iter()
+                    .flat_map(|s| &s.instances)
+                    .filter(|i| {
+                        i.config.position == pos
+                            && (focused || i.config.visible == shell::Visibility::Always)
+                            && !self.hidden_widgets.contains(&i.config.label)
+                    })
+                    .collect();
+                placed.sort_by_key(|i| std::cmp::Reverse(i.config.priority));
+
+                // Minimal density: one row of status dots with labels
+                if self.shells.is_some() && self.minimal {
+                    let mut dots = row![].spacing(12);
+                    for inst in &placed {
+                        let dot_color = match inst.status() {
+                            shell::ShellStatus::Running => colors.approval,
+                            shell::ShellStatus::Done => colors.muted,
+                            shell::ShellStatus::Failed => colors.error,
+                        };
+                        dots = dots.push(row![
+                            text("\u{25cf} ")
+                                .size(colors.widget_text)
+                                .color(dot_color)
+                                .font(mono)
+                                .shaping(shaped),
+                            text(inst.config.label.clone())
+                                .size(colors.widget_text)
+                                .color(colors.muted)
+                                .font(mono)
+                                .shaping(shaped),
+                        ]);
+                        has_content = true;
+                    }
+                    col = col.push(dots);
+                } else if let Some(shells) = &self.shells {
+                    let wanted: Vec<usize> = placed.iter().map(|i| i.output_lines()).collect();
+                    let page = (unix_now() / LayoutConfig::PAGE_SECS) as u64;
+                    let fitted = layout.fit_lines(&wanted, page);
+                    for (inst, max_lines) in placed.iter().zip(fitted) {
+                        if let Some(max_lines) = max_lines {
+                            render_shell_inst!(col, inst, focused, max_lines);
                             has_content = true;
                         }
                     }
@@ -233,15 +441,29 @@ impl Hud {
                             if let Some(inst) = shells.instances.get(idx) {
                                 if inst.config.visible != shell::Visibility::Always
                                     && inst.config.position == pos
+                                    && !self.hidden_widgets.contains(&inst.config.label)
                                 {
                                     let icon = "\u{f120}";
-                                    let inst_cols = inst.config.cols;
-                                    let last_line = inst
+                                    // Label and its trailing space share the cols budget
+                                    let inst_cols = layout
+                                        .fit_cols(
+                                            inst.config.cols,
+                                            measure::cell_width(mono, colors.widget_text),
+                                            corner_width,
+                                        )
+                                        .saturating_sub(display_width(&inst.config.label) + 1);
+                                    let (last_line, last_color) = inst
                                         .buffer
                                         .back()
-                                        .map(|l| truncate_str(l, inst_cols))
+                                        .map(|l| {
+                                            let color =
+                                                if l.error { colors.error } else { colors.marker };
+                                            (truncate_str(&l.text, inst_cols), color)
+                                        })
                                         .or_else(|| {
-                                            inst.error.as_ref().map(|e| truncate_str(e, inst_cols))
+                                            inst.error
+                                                .as_ref()
+                                                .map(|e| (truncate_str(e, inst_cols), colors.error))
                                         })
                                         .unwrap_or_default();
 
@@ -258,7 +480,7 @@ impl Hud {
                                             .shaping(shaped),
                                         text(last_line)
                                             .size(colors.widget_text)
-                                            .color(colors.marker)
+                                            .color(last_color)
                                             .font(mono)
                                             .shaping(shaped),
                                     ];
@@ -268,18 +490,16 @@ impl Hud {
                             }
                         }
                     }
+                }
 
-                    if has_content {
-                        if self.backdrop {
-                            container(col)
-                                .style(colors.hud_backdrop_style())
-                                .padding(6)
-                                .into()
-                        } else {
-                            col.into()
-                        }
+                let widget: Element<'static, Message> = if has_content {
+                    if self.backdrop {
+                        container(col)
+                            .style(colors.hud_backdrop_style())
+                            .padding(6)
+                            .into()
                     } else {
-                        space::Space::new().height(0).width(0).into()
+                        col.into()
                     }
                 } else {
                     space::Space::new().height(0).width(0).into()
@@ -288,61 +508,395 @@ impl Hud {
             }};
         }
 
-        let shell_top_left = build_position_widget!(shell::Position::TopLeft);
-        let shell_top_right = build_position_widget!(shell::Position::TopRight);
-        let shell_bottom_left = build_position_widget!(shell::Position::BottomLeft);
-        let shell_bottom_right = build_position_widget!(shell::Position::BottomRight);
+        // Each corner is only rebuilt when something it shows changed, not on
+        // every tick or on output from another corner's widgets
+        let corner = |pos: shell::Position| -> Element<'_, Message> {
+            lazy(self.corner_key(pos, corner_width), move |_| {
+                build_position_widget!(pos)
+            })
+            .into()
+        };
+        let shell_top_left = corner(shell::Position::TopLeft);
+        let shell_top_right = corner(shell::Position::TopRight);
+        let shell_bottom_left = corner(shell::Position::BottomLeft);
+        let shell_bottom_right = corner(shell::Position::BottomRight);
 
-        // Top widgets row: top-left shells + space + top-right shells
-        let top_widgets_row = row![shell_top_left, space::horizontal(), shell_top_right,]
-            .width(Length::Fill)
-            .align_y(iced::alignment::Vertical::Top);
+        if layout.mode == LayoutMode::Corners {
+            // Top widgets row: top-left shells + space + top-right shells
+            let top_widgets_row = row![shell_top_left, space::horizontal(), shell_top_right,]
+                .width(Length::Fill)
+                .align_y(iced::alignment::Vertical::Top);
 
-        main_col = main_col.push(top_widgets_row);
-        main_col = main_col.push(space::vertical());
+            main_col = main_col.push(top_widgets_row);
+            main_col = main_col.push(space::vertical());
 
-        // Bottom widgets row: bottom-left shells (left) + space + bottom-right shells
-        let widgets_row = row![shell_bottom_left, space::horizontal(), shell_bottom_right,]
-            .width(Length::Fill)
-            .align_y(iced::alignment::Vertical::Bottom);
+            // Bottom widgets row: bottom-left shells (left) + space + bottom-right shells
+            let widgets_row = row![shell_bottom_left, space::horizontal(), shell_bottom_right,]
+                .width(Length::Fill)
+                .align_y(iced::alignment::Vertical::Bottom);
 
-        main_col = main_col.push(widgets_row);
+            main_col = main_col.push(widgets_row);
+        } else {
+            // Side panel: every position stacked in one column along the edge
+            let panel = column![
+                shell_top_left,
+                shell_top_right,
+                shell_bottom_left,
+                shell_bottom_right
+            ]
+            .spacing(8)
+            .width(layout.width);
+            let side_row = if layout.mode == LayoutMode::Left {
+                row![panel, space::horizontal()]
+            } else {
+                row![space::horizontal(), panel]
+            };
+            main_col = main_col.push(side_row.width(Length::Fill));
+            main_col = main_col.push(space::vertical());
+        }
 
         main_col = main_col.push(bottom_row);
 
-        // Info line: version, commit, font — below the marker rectangle
+        // Info line: version, commit, font (and a demo flag) — below the marker rectangle.
+        // Overlay layers reuse the frame with a transparent info line so
+        // their marker rectangle lines up with the main one.
         let info_size = colors.info_text;
-        let info_row = row![
-            space::horizontal(),
-            text(format!(
-                "v{} {} {}",
-                env!("DEV_HUD_VERSION"),
-                env!("DEV_HUD_COMMIT"),
-                self.current_font_label()
-            ))
-            .size(info_size)
-            .color(colors.muted)
-            .font(mono)
-            .shaping(shaped)
-        ];
-
-        let outer = column![
-            container(main_col)
+        let info_row = |color: Color| {
+            row![
+                space::horizontal(),
+                text(
+                    self.update_available
+                        .as_ref()
+                        .map(|tag| format!("{tag} available  "))
+                        .unwrap_or_default()
+                )
+                .size(info_size)
+                .color(color)
+                .font(mono)
+                .shaping(shaped),
+                text(format!(
+                    "{}v{} {} {}",
+                    if self.demo { "demo " } else { "" },
+                    env!("DEV_HUD_VERSION"),
+                    env!("DEV_HUD_COMMIT"),
+                    self.current_font_label()
+                ))
+                .size(info_size)
+                .color(color)
+                .font(mono)
+                .shaping(shaped)
+            ]
+        };
+
+        let mut layers = stack![framed(main_col, info_row(colors.muted))]
+            .width(Length::Fill)
+            .height(Length::Fill);
+
+        if markers.count == MarkerCount::Edges {
+            let mid = || {
+                text(markers.glyph.clone())
+                    .size(marker_size)
+                    .color(marker_color)
+                    .shaping(shaped)
+            };
+            let edge_col = column![
+                row![space::horizontal(), mid(), space::horizontal()],
+                space::vertical(),
+                row![mid(), space::horizontal(), mid()],
+                space::vertical(),
+                row![space::horizontal(), mid(), space::horizontal()],
+            ]
+            .width(Length::Fill)
+            .height(Length::Fill);
+            layers = layers.push(framed(edge_col, info_row(Color::TRANSPARENT)));
+        }
+
+        if self.guides != Guides::Off {
+            let guide_color = self
+                .hud_config
+                .guides
+                .color
+                .map(|[r, g, b, a]| Color::from_rgba(r, g, b, a))
+                .unwrap_or(colors.muted);
+            layers = layers.push(guides_layer(self.guides, guide_color));
+        }
+
+        // Alert band: top center, above everything but the highlight.
+        // Rule `attention` texts join the battery/thermal alerts.
+        let now = Instant::now();
+        let attention: Vec<&str> = self
+            .attention
+            .iter()
+            .filter(|(_, until)| now < *until)
+            .map(|(text, _)| text.as_str())
+            .collect();
+        if !self.alerts.is_empty() || !attention.is_empty() {
+            let mut band = row![].spacing(16);
+            for message in attention {
+                band = band.push(
+                    text(format!("\u{f0f3} {message}"))
+                        .size(colors.label_text)
+                        .font(mono)
+                        .shaping(shaped),
+                );
+            }
+            for alert in &self.alerts {
+                let icon = match alert {
+                    Alert::LowBattery { .. } => "\u{f244}",
+                    Alert::Hot { .. } => "\u{f2c7}",
+                };
+                band = band.push(
+                    text(format!("{icon} {}", alert.text()))
+                        .size(colors.label_text)
+                        .font(mono)
+                        .shaping(shaped),
+                );
+            }
+            layers = layers.push(
+                container(
+                    container(band)
+                        .padding([6, 16])
+                        .style(colors.alert_band_style()),
+                )
                 .padding(EDGE_MARGIN)
                 .width(Length::Fill)
-                .height(Length::Fill),
-            container(info_row)
+                .height(Length::Fill)
+                .align_x(iced::alignment::Horizontal::Center)
+                .align_y(iced::alignment::Vertical::Top),
+            );
+        }
+
+        // Tour card: near the part of the HUD the step is about
+        if let Some(tour) = &self.tour {
+            let step = tour.step();
+            let controls: Element<'_, Message> = if focused {
+                let button = |label: &'static str, message: Message| {
+                    mouse_area(
+                        text(label)
+                            .size(colors.widget_text)
+                            .color(colors.marker)
+                            .font(mono),
+                    )
+                    .on_press(message)
+                    .interaction(iced::mouse::Interaction::Pointer)
+                };
+                row![
+                    button("[next]", Message::TourNext),
+                    button("[end]", Message::TourEnd)
+                ]
+                .spacing(12)
+                .into()
+            } else {
+                // Only focused mode receives clicks
+                text("dev-hud-ctl tour next | tour end")
+                    .size(colors.info_text)
+                    .color(colors.muted)
+                    .font(mono)
+                    .into()
+            };
+            let card = column![
+                text(format!(
+                    "{} {}/{}",
+                    step.title,
+                    tour.number(),
+                    tour::STEPS.len()
+                ))
+                .size(colors.label_text)
+                .color(colors.marker)
+                .font(mono)
+                .shaping(shaped),
+                text(step.body)
+                    .size(colors.widget_text)
+                    .color(colors.marker)
+                    .shaping(shaped),
+                controls,
+            ]
+            .spacing(6);
+            let (align_x, align_y) = match step.anchor {
+                Anchor::TopLeft => (Horizontal::Left, Vertical::Top),
+                Anchor::TopRight => (Horizontal::Right, Vertical::Top),
+                Anchor::BottomLeft => (Horizontal::Left, Vertical::Bottom),
+                Anchor::BottomRight => (Horizontal::Right, Vertical::Bottom),
+                Anchor::Center => (Horizontal::Center, Vertical::Center),
+            };
+            layers = layers.push(
+                container(
+                    container(card)
+                        .padding([8, 12])
+                        .width(TOUR_CARD_WIDTH)
+                        .style(colors.tooltip_style()),
+                )
+                .padding(2 * EDGE_MARGIN)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(align_x)
+                .align_y(align_y),
+            );
+        }
+
+        if let Some((highlight, _)) = &self.highlight {
+            layers = layers.push(
+                container(
+                    container(space::Space::new())
+                        .width(highlight.width)
+                        .height(highlight.height)
+                        .style(colors.highlight_style()),
+                )
                 .padding(iced::Padding {
-                    top: 0.0,
-                    right: EDGE_MARGIN as f32,
-                    bottom: 8.0,
-                    left: 0.0,
+                    top: highlight.y,
+                    right: 0.0,
+                    bottom: 0.0,
+                    left: highlight.x,
                 })
-                .width(Length::Fill),
-        ]
-        .width(Length::Fill)
-        .height(Length::Fill);
+                .width(Length::Fill)
+                .height(Length::Fill),
+            );
+        }
 
-        outer.into()
+        layers.into()
     }
+
+    /// Hash of everything the widgets at `pos` are drawn from, so `lazy`
+    /// rebuilds the corner only when one of them changes.
+    fn corner_key(&self, pos: shell::Position, corner_width: Option<f32>) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        pos.hash(&mut hasher);
+        (self.mode, self.minimal, self.backdrop, self.font_index).hash(&mut hasher);
+
+        let colors = &self.colors;
+        for color in [
+            colors.marker,
+            colors.muted,
+            colors.error,
+            colors.approval,
+            colors.modal_bg,
+            colors.hud_backdrop,
+        ] {
+            color.into_rgba8().hash(&mut hasher);
+        }
+        colors.widget_text.to_bits().hash(&mut hasher);
+
+        let layout = &self.hud_config.layout;
+        (layout.mode, layout.overflow, layout.max_lines).hash(&mut hasher);
+        if layout.overflow == Overflow::Scroll {
+            (unix_now() / LayoutConfig::PAGE_SECS).hash(&mut hasher);
+        }
+        layout.width.to_bits().hash(&mut hasher);
+        corner_width.map(f32::to_bits).hash(&mut hasher);
+
+        if let Some(up) = &self.calendar
+            && self.hud_config.calendar.position == pos
+        {
+            up.countdown(unix_now()).hash(&mut hasher);
+        }
+
+        self.shells.is_some().hash(&mut hasher);
+        if let Some(shells) = &self.shells {
+            let most_recent = shells.most_recent.and_then(|idx| shells.instances.get(idx));
+            for inst in &shells.instances {
+                // The most recent widget's last line can show in the bottom right
+                if inst.config.position == pos
+                    || most_recent.is_some_and(|recent| std::ptr::eq(recent, inst))
+                {
+                    (&inst.config.label, inst.revision).hash(&mut hasher);
+                    self.hidden_widgets
+                        .contains(&inst.config.label)
+                        .hash(&mut hasher);
+                }
+            }
+            most_recent.map(|inst| &inst.config.label).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// Show `full_text` in a hover tooltip over a truncated `line`. Only
+/// focused mode receives pointer events, so callers pass `None` otherwise.
+fn with_full_text<'a>(
+    line: impl Into<Element<'a, Message>>,
+    full_text: Option<String>,
+    colors: &ThemeColors,
+    font: Font,
+    size: f32,
+) -> Element<'a, Message> {
+    let Some(full_text) = full_text else {
+        return line.into();
+    };
+    tooltip(
+        line,
+        container(
+            text(full_text)
+                .size(size)
+                .color(colors.marker)
+                .font(font)
+                .shaping(Shaping::Advanced),
+        )
+        .max_width(TOOLTIP_MAX_WIDTH),
+        tooltip::Position::Bottom,
+    )
+    .gap(4)
+    .padding(6)
+    .style(colors.tooltip_style())
+    .into()
+}
+
+/// Wrap `content` in the HUD frame: edge padding, with the info line below.
+fn framed<'a>(
+    content: impl Into<Element<'a, Message>>,
+    info_row: impl Into<Element<'a, Message>>,
+) -> Element<'a, Message> {
+    column![
+        container(content)
+            .padding(EDGE_MARGIN)
+            .width(Length::Fill)
+            .height(Length::Fill),
+        container(info_row)
+            .padding(iced::Padding {
+                top: 0.0,
+                right: EDGE_MARGIN as f32,
+                bottom: 8.0,
+                left: 0.0,
+            })
+            .width(Length::Fill),
+    ]
+    .width(Length::Fill)
+    .height(Length::Fill)
+    .into()
+}
+
+/// A 1px line filling the available width (horizontal) or height (vertical).
+fn guide_line<'a>(color: Color, vertical: bool) -> Element<'a, Message> {
+    let line = container(space::Space::new()).style(move |_theme: &iced::Theme| container::Style {
+        background: Some(Background::Color(color)),
+        ..Default::default()
+    });
+    if vertical {
+        line.width(Length::Fixed(1.0)).height(Length::Fill).into()
+    } else {
+        line.width(Length::Fill).height(Length::Fixed(1.0)).into()
+    }
+}
+
+/// Full-surface alignment guides: a center crosshair or a thirds grid.
+/// Lines sit between equal `Fill` spaces, so they divide the output evenly.
+fn guides_layer<'a>(guides: Guides, color: Color) -> Element<'a, Message> {
+    let divisions = match guides {
+        Guides::Off => return space::Space::new().height(0).width(0).into(),
+        Guides::Crosshair => 2,
+        Guides::Thirds => 3,
+    };
+    let mut verticals = row![space::horizontal()].height(Length::Fill);
+    let mut horizontals = column![space::vertical()].width(Length::Fill);
+    for _ in 1..divisions {
+        verticals = verticals
+            .push(guide_line(color, true))
+            .push(space::horizontal());
+        horizontals = horizontals
+            .push(guide_line(color, false))
+            .push(space::vertical());
+    }
+    stack![verticals, horizontals]
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .into()
 }